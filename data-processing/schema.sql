@@ -12,7 +12,10 @@ CREATE TABLE IF NOT EXISTS characters (
     character TEXT UNIQUE NOT NULL,              -- The actual character(s) or word
     simplified TEXT NOT NULL,                     -- Simplified form
     traditional TEXT,                             -- Traditional form (may be same as simplified)
-    mandarin_pinyin TEXT NOT NULL,                -- Mandarin pronunciation (Phase 1)
+    mandarin_pinyin TEXT NOT NULL,                -- Mandarin pronunciation (Phase 1), CEDICT style: 'han4 zi4'
+    pinyin_marked TEXT,                           -- Tone marks: 'hàn zì' (filled at build time by the pinyin module)
+    pinyin_toneless TEXT,                         -- Lowercase, no tones or spaces: 'hanzi' (for search)
+    pinyin_sandhi TEXT,                           -- Tone marks as spoken, after tone sandhi: 'ní hǎo'
     cantonese_jyutping TEXT,                      -- Cantonese pronunciation (Phase 3)
     definition TEXT NOT NULL,                     -- English definition(s)
     frequency_rank INTEGER NOT NULL,              -- From SUBTLEX-CH (lower = more common)
@@ -33,7 +36,38 @@ CREATE INDEX idx_frequency ON characters(frequency_rank);
 CREATE INDEX idx_is_word ON characters(is_word);
 CREATE INDEX idx_character ON characters(character);
 CREATE INDEX idx_simplified ON characters(simplified);
+CREATE INDEX idx_traditional ON characters(traditional);
 CREATE INDEX idx_introduction_rank ON characters(introduction_rank);
+CREATE INDEX idx_pinyin_toneless ON characters(pinyin_toneless);
+
+-- =============================================================================
+-- SENSES TABLE
+-- =============================================================================
+-- One row per dictionary entry, so heteronyms keep every reading
+-- (e.g. 了 le5 and liao3). characters holds the first entry as the primary reading.
+CREATE TABLE IF NOT EXISTS senses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    character_id INTEGER NOT NULL,                -- Reference to the character/word
+    traditional TEXT,                             -- Traditional form for this entry
+    pinyin TEXT NOT NULL,                         -- Reading for this entry
+    definition TEXT NOT NULL,                     -- English definitions, '; ' separated
+    source TEXT NOT NULL DEFAULT 'cedict',        -- Dictionary the entry came from
+    source_line INTEGER,                          -- Line number in the source file
+    FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_senses_character ON senses(character_id);
+
+-- =============================================================================
+-- CHARACTER STROKES TABLE
+-- =============================================================================
+-- Stroke order from Make Me a Hanzi graphics.txt, one row per single character
+CREATE TABLE IF NOT EXISTS character_strokes (
+    character_id INTEGER PRIMARY KEY,             -- Reference to the character
+    strokes TEXT NOT NULL,                        -- JSON array of SVG paths, in stroke order
+    medians TEXT NOT NULL,                        -- JSON array of stroke medians ([[x, y], ...] per stroke)
+    FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+);
 
 -- =============================================================================
 -- USER PROGRESS TABLE (SPACED REPETITION)
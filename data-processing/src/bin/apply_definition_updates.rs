@@ -89,6 +89,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("  ✓ Updated: {} → {}", character, &updated_def[..updated_def.len().min(60)]);
     }
 
+    // Existing users pick up the new definitions on their next launch
+    let version = data_processing::database::stamp_dictionary_version(&conn)?;
+    println!("  ✓ Dictionary version {}", version);
+
     // Save overrides to JSON file for tracking
     println!("\n📝 Saving override history...");
     let overrides_path = "../definition_overrides.json";
@@ -42,17 +42,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     database::create_database(enriched, output_path.to_str().unwrap())?;
     println!();
 
-    // Step 5: Populate component characters
+    // Step 5: Cantonese readings and Cantonese-only words
+    println!("🗣️  Adding CC-Canto Jyutping...");
+    database::populate_cantonese(output_path.to_str().unwrap(), &datasets_dir.join("cccanto"))?;
+    println!();
+
+    // Step 6: Populate component characters
     println!("🔗 Populating component characters...");
     database::populate_component_characters(output_path.to_str().unwrap())?;
     println!();
 
-    // Step 6: Calculate and populate introduction ranks
+    // Step 7: Calculate and populate introduction ranks
     println!("📊 Calculating introduction ranks...");
     database::populate_introduction_ranks(output_path.to_str().unwrap())?;
     println!();
 
-    // Step 7: Verify
+    // Step 8: Stroke order, radicals and etymology
+    println!("✍️  Adding Make Me a Hanzi stroke data...");
+    database::populate_makemeahanzi(output_path.to_str().unwrap(), &datasets_dir.join("makemeahanzi"))?;
+    println!();
+
+    // Step 9: Verify
     println!("✅ Verifying database...");
     database::verify_database(output_path.to_str().unwrap())?;
 
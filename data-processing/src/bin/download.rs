@@ -22,6 +22,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Download CC-CEDICT
     download_cedict(&datasets_dir).await?;
 
+    // Download Make Me a Hanzi (stroke order, radicals, etymology)
+    download_makemeahanzi(&datasets_dir).await?;
+
+    // Download CC-Canto (Cantonese Jyutping and Cantonese-only words)
+    download_cccanto(&datasets_dir).await?;
+
     // Instructions for SUBTLEX-CH (requires manual download)
     show_subtlex_instructions(&datasets_dir);
 
@@ -72,6 +78,77 @@ async fn download_cedict(datasets_dir: &std::path::PathBuf) -> Result<(), Box<dy
     Ok(())
 }
 
+/// Download Make Me a Hanzi dictionary.txt and graphics.txt
+async fn download_makemeahanzi(datasets_dir: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    println!("📥 Downloading Make Me a Hanzi...");
+    println!("   Source: https://github.com/skishore/makemeahanzi");
+    println!("   License: Arphic Public License (dictionary), LGPL 2.1+ (graphics)\n");
+
+    let output_dir = datasets_dir.join("makemeahanzi");
+    fs::create_dir_all(&output_dir)?;
+
+    for file_name in ["dictionary.txt", "graphics.txt"] {
+        let output = output_dir.join(file_name);
+        if output.exists() {
+            println!("   ✓ {} already exists, skipping download", file_name);
+            continue;
+        }
+
+        let url = format!("https://raw.githubusercontent.com/skishore/makemeahanzi/master/{}", file_name);
+        println!("   Downloading {}...", file_name);
+        let bytes = reqwest::get(&url).await?.error_for_status()?.bytes().await?;
+        File::create(&output)?.write_all(&bytes)?;
+        println!("   ✓ Downloaded {} ({} KB)", file_name, bytes.len() / 1024);
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Download CC-Canto and the CC-CEDICT Jyutping readings, each a zip holding one .txt file
+async fn download_cccanto(datasets_dir: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    println!("📥 Downloading CC-Canto...");
+    println!("   Source: https://cantonese.org/download.html");
+    println!("   License: CC BY-SA (see DATA-LICENSES.md)\n");
+
+    let output_dir = datasets_dir.join("cccanto");
+    fs::create_dir_all(&output_dir)?;
+
+    let downloads = [
+        ("https://cantonese.org/cccanto-170202.zip", "cccanto-webdist.txt"),
+        ("https://cantonese.org/cccedict-canto-readings-150923.zip", "cccedict-canto-readings.txt"),
+    ];
+    for (url, file_name) in downloads {
+        let output = output_dir.join(file_name);
+        if output.exists() {
+            println!("   ✓ {} already exists, skipping download", file_name);
+            continue;
+        }
+
+        println!("   Downloading {}...", url);
+        let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+        extract_txt_from_zip(&bytes, &output)?;
+        println!("   ✓ Extracted {} ({} KB download)", file_name, bytes.len() / 1024);
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Write the first .txt member of a zip archive to output path
+fn extract_txt_from_zip(bytes: &[u8], output: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(io::Cursor::new(bytes))?;
+    for i in 0..archive.len() {
+        let mut member = archive.by_index(i)?;
+        if member.is_file() && member.name().ends_with(".txt") {
+            let mut output_file = File::create(output)?;
+            copy(&mut member, &mut output_file)?;
+            return Ok(());
+        }
+    }
+    Err("No .txt file in zip archive".into())
+}
+
 /// Show instructions for manual SUBTLEX-CH download
 fn show_subtlex_instructions(datasets_dir: &std::path::PathBuf) {
     println!("📥 SUBTLEX-CH Download Instructions");
@@ -138,6 +215,31 @@ mod tests {
         fs::remove_file(&test_output).unwrap();
     }
 
+    #[test]
+    fn test_extract_txt_from_zip() {
+        use std::path::PathBuf;
+
+        // Build a zip holding a readme and the dictionary
+        let mut buffer = io::Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut buffer);
+            let options = zip::write::SimpleFileOptions::default();
+            writer.start_file("README", options).unwrap();
+            writer.write_all(b"readme").unwrap();
+            writer.start_file("cccanto-webdist.txt", options).unwrap();
+            writer.write_all("佢 佢 [qu2] {keoi5} /he/".as_bytes()).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let test_output = PathBuf::from("test_cccanto.txt");
+        extract_txt_from_zip(buffer.get_ref(), &test_output).unwrap();
+
+        let content = fs::read_to_string(&test_output).unwrap();
+        assert_eq!(content, "佢 佢 [qu2] {keoi5} /he/");
+
+        fs::remove_file(&test_output).unwrap();
+    }
+
     #[test]
     fn test_datasets_directory_creation() {
         // This would normally be tested in integration tests
@@ -0,0 +1,89 @@
+use data_processing::simulation::{load_state, simulate, SimulationParams};
+use rusqlite::{Connection, Result};
+
+const USAGE: &str = "Usage: simulate-workload [DB_PATH] [--track mandarin|cantonese] [--days N] [--recall RATE]
+    [--scheduler ladder|fsrs] [--retention R] [--initial-batch N] [--batch-size N] [--cooldown-hours H]
+    [--workload-aware] [--max-due N] [--new-per-day N] [--review-limit N]
+    [--mastery-rule RULE] [--mastery-threshold T] [--maintenance-days D]";
+
+fn main() -> Result<()> {
+    let mut db_path = "../src-tauri/chinese.db".to_string();
+    let mut track = "mandarin".to_string();
+    let mut params = SimulationParams::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            db_path = arg;
+            continue;
+        }
+        if arg == "--workload-aware" {
+            params.workload_aware = true;
+            continue;
+        }
+        let Some(value) = args.next() else {
+            eprintln!("Missing value for {}\n{}", arg, USAGE);
+            std::process::exit(1);
+        };
+        let number = || -> f32 {
+            value.parse().unwrap_or_else(|_| {
+                eprintln!("Invalid value for {}: {}", arg, value);
+                std::process::exit(1);
+            })
+        };
+        match arg.as_str() {
+            "--track" => track = value.clone(),
+            "--days" => params.days = number() as u32,
+            "--recall" => params.recall_rate = number(),
+            "--scheduler" => params.scheduler = value.clone(),
+            "--retention" => params.desired_retention = number(),
+            "--initial-batch" => params.initial_batch_size = number() as usize,
+            "--batch-size" => params.batch_size = number() as usize,
+            "--cooldown-hours" => params.cooldown_hours = number(),
+            "--max-due" => params.max_due_reviews = number() as usize,
+            "--new-per-day" => params.new_cards_per_day = number() as usize,
+            "--review-limit" => params.review_limit = number() as usize,
+            "--mastery-rule" => params.mastery.rule = value.clone(),
+            "--mastery-threshold" => params.mastery.threshold = number(),
+            "--maintenance-days" => params.mastery.maintenance_interval_days = number(),
+            _ => {
+                eprintln!("Unknown option {}\n{}", arg, USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    println!("=== Review Workload Simulation ===");
+    println!("Database: {}", db_path);
+
+    let conn = Connection::open(&db_path)?;
+    let state = load_state(&conn, &track)?;
+    println!(
+        "Track: {} | {} cards in review, {} ready to learn, {} still locked",
+        track, state.cards.len(), state.ready_to_learn, state.unlockable
+    );
+    println!(
+        "Scheduler: {} (retention {}) | recall {:.0}% | unlock {} every {}h | {} new/day, {} reviews/day",
+        params.scheduler, params.desired_retention, params.recall_rate * 100.0,
+        params.batch_size, params.cooldown_hours, params.new_cards_per_day, params.review_limit
+    );
+
+    let forecast = simulate(&state, &params);
+
+    println!("\n{:>4} {:>8} {:>6} {:>10} {:>5} {:>9}", "Day", "Reviews", "Cards", "Held back", "New", "Unlocked");
+    for day in &forecast.days {
+        println!(
+            "{:>4} {:>8} {:>6} {:>10} {:>5} {:>9}",
+            day.day, day.reviews, day.cards_reviewed, day.held_back, day.new_cards, day.unlocked
+        );
+    }
+
+    println!("\n=== Totals ===");
+    println!("Reviews: {} (peak {}/day, average {:.1}/day)",
+        forecast.total_reviews, forecast.peak_reviews,
+        forecast.total_reviews as f32 / forecast.days.len().max(1) as f32);
+    println!("New characters: {}", forecast.total_new_cards);
+    println!("Mastered cards at the end: {}", forecast.mastered_cards);
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use rusqlite::{Connection, Result};
 use crate::EnrichedEntry;
+use crate::parsers::{cccanto, makemeahanzi};
 use std::path::Path;
 use std::collections::HashMap;
 
@@ -21,6 +22,15 @@ pub fn create_database(entries: Vec<EnrichedEntry>, output_path: &str) -> Result
     let tx = conn.transaction()?;
 
     insert_characters(&tx, entries)?;
+
+    let normalized = crate::pinyin::store_pinyin_forms(&tx)?;
+    println!("  Normalized pinyin for {} characters/words", normalized);
+
+    let indexed = crate::search::build_search_index(&tx)?;
+    println!("  Indexed {} senses for dictionary search", indexed);
+
+    let version = stamp_dictionary_version(&tx)?;
+    println!("  Dictionary version {}", version);
     // Note: Initial user progress (first 30 characters) is now initialized
     // by the app on first run, not during database build
 
@@ -31,6 +41,18 @@ pub fn create_database(entries: Vec<EnrichedEntry>, output_path: &str) -> Result
     Ok(())
 }
 
+/// Mark the reference data as changed. The app refreshes a user's dictionary tables
+/// whenever the bundled database carries a different dictionary_version.
+pub fn stamp_dictionary_version(conn: &Connection) -> Result<String> {
+    let version = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string();
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value, updated_at)
+         VALUES ('dictionary_version', ?1, datetime('now'))",
+        [&version]
+    )?;
+    Ok(version)
+}
+
 fn insert_characters(conn: &Connection, entries: Vec<EnrichedEntry>) -> Result<()> {
     let mut stmt = conn.prepare(
         "INSERT OR IGNORE INTO characters (
@@ -39,6 +61,12 @@ fn insert_characters(conn: &Connection, entries: Vec<EnrichedEntry>) -> Result<(
         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
     )?;
 
+    // Every entry is kept as a sense, including extra readings of the same character
+    let mut sense_stmt = conn.prepare(
+        "INSERT INTO senses (character_id, traditional, pinyin, definition, source, source_line)
+         SELECT id, ?2, ?3, ?4, 'cedict', ?5 FROM characters WHERE character = ?1"
+    )?;
+
     let mut inserted = 0;
     let mut duplicates = 0;
     let total = entries.len();
@@ -67,6 +95,14 @@ fn insert_characters(conn: &Connection, entries: Vec<EnrichedEntry>) -> Result<(
             cedict.is_word,
         ])?;
 
+        sense_stmt.execute(rusqlite::params![
+            cedict.simplified,
+            cedict.traditional,
+            cedict.pinyin,
+            definition,
+            cedict.line_number as i64,
+        ])?;
+
         if rows_affected > 0 {
             inserted += 1;
             seen_characters.insert(cedict.simplified.clone(), 1);
@@ -78,7 +114,7 @@ fn insert_characters(conn: &Connection, entries: Vec<EnrichedEntry>) -> Result<(
     }
 
     println!("  Inserted {} unique characters/words", inserted);
-    println!("  Skipped {} duplicate entries (multiple CEDICT entries for same character)", duplicates);
+    println!("  Merged {} duplicate entries into senses (multiple CEDICT entries for same character)", duplicates);
 
     Ok(())
 }
@@ -190,6 +226,200 @@ pub fn populate_component_characters(db_path: &str) -> Result<(), Box<dyn std::e
     Ok(())
 }
 
+/// Fill stroke_count, radical, decomposition, etymology and character_strokes from
+/// Make Me a Hanzi (`<dir>/dictionary.txt` and `<dir>/graphics.txt`).
+/// Skipped with a message if the dataset hasn't been downloaded.
+pub fn populate_makemeahanzi(db_path: &str, dataset_dir: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let dictionary_path = dataset_dir.join("dictionary.txt");
+    let graphics_path = dataset_dir.join("graphics.txt");
+    if !dictionary_path.exists() || !graphics_path.exists() {
+        println!("  ⊗ Make Me a Hanzi not found in {:?}, skipping stroke data", dataset_dir);
+        return Ok(0);
+    }
+
+    let dictionary = makemeahanzi::parse_dictionary_file(dictionary_path.to_str().unwrap())?;
+    let graphics = makemeahanzi::parse_graphics_file(graphics_path.to_str().unwrap())?;
+
+    let mut conn = Connection::open(db_path)?;
+    let tx = conn.transaction()?;
+    let updated = insert_character_details(&tx, &dictionary, &graphics)?;
+    tx.commit()?;
+
+    println!("  ✓ Added stroke data for {} characters", updated);
+    Ok(updated)
+}
+
+pub fn insert_character_details(
+    conn: &Connection,
+    dictionary: &HashMap<String, makemeahanzi::HanziEntry>,
+    graphics: &HashMap<String, makemeahanzi::HanziGraphics>,
+) -> Result<usize> {
+    let characters: Vec<(i32, String)> = {
+        let mut stmt = conn.prepare("SELECT id, character FROM characters WHERE is_word = 0")?;
+        let result = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        result
+    };
+
+    let mut update_stmt = conn.prepare(
+        "UPDATE characters
+         SET stroke_count = ?1, radical = ?2, decomposition = ?3, etymology = ?4,
+             updated_at = datetime('now')
+         WHERE id = ?5"
+    )?;
+    let mut strokes_stmt = conn.prepare(
+        "INSERT OR REPLACE INTO character_strokes (character_id, strokes, medians) VALUES (?1, ?2, ?3)"
+    )?;
+
+    let mut updated = 0;
+    for (id, character) in &characters {
+        let entry = dictionary.get(character);
+        let strokes = graphics.get(character);
+        if entry.is_none() && strokes.is_none() {
+            continue;
+        }
+
+        let etymology = entry
+            .and_then(|e| e.etymology.as_ref())
+            .map(|e| serde_json::to_string(e).unwrap());
+
+        update_stmt.execute(rusqlite::params![
+            strokes.map(|g| g.strokes.len() as i32),
+            entry.and_then(|e| e.radical.as_deref()),
+            entry.and_then(|e| e.known_decomposition()),
+            etymology,
+            id,
+        ])?;
+
+        if let Some(strokes) = strokes {
+            strokes_stmt.execute(rusqlite::params![
+                id,
+                serde_json::to_string(&strokes.strokes).unwrap(),
+                serde_json::to_string(&strokes.medians).unwrap(),
+            ])?;
+        }
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+/// Add Cantonese from CC-Canto (`<dir>/cccedict-canto-readings.txt` and `<dir>/cccanto-webdist.txt`).
+/// Run before component characters and introduction ranks so Cantonese-only words get both.
+/// Skipped with a message if the dataset hasn't been downloaded.
+pub fn populate_cantonese(db_path: &str, dataset_dir: &Path) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let readings_path = dataset_dir.join("cccedict-canto-readings.txt");
+    let dictionary_path = dataset_dir.join("cccanto-webdist.txt");
+    if !readings_path.exists() && !dictionary_path.exists() {
+        println!("  ⊗ CC-Canto not found in {:?}, skipping Cantonese", dataset_dir);
+        return Ok((0, 0));
+    }
+
+    let readings = if readings_path.exists() {
+        cccanto::parse_readings_file(readings_path.to_str().unwrap())?
+    } else {
+        Vec::new()
+    };
+    let dictionary = if dictionary_path.exists() {
+        cccanto::parse_cccanto_file(dictionary_path.to_str().unwrap())?
+    } else {
+        Vec::new()
+    };
+
+    let mut conn = Connection::open(db_path)?;
+    let tx = conn.transaction()?;
+    let (with_jyutping, new_words) = insert_cantonese(&tx, &readings, &dictionary)?;
+    crate::pinyin::store_pinyin_forms(&tx)?;
+    let indexed = crate::search::build_search_index(&tx)?;
+    tx.commit()?;
+
+    println!("  ✓ Jyutping for {} characters/words", with_jyutping);
+    println!("  ✓ Added {} Cantonese-only words", new_words);
+    println!("  Re-indexed {} senses for dictionary search", indexed);
+    Ok((with_jyutping, new_words))
+}
+
+/// Set cantonese_jyutping and add CC-Canto senses. A reading whose pinyin matches the
+/// entry's primary Mandarin reading wins; otherwise the first reading in file order is kept.
+/// CC-Canto words missing from CEDICT become new rows ranked after every SUBTLEX entry.
+/// Returns (entries with Jyutping, new rows).
+pub fn insert_cantonese(
+    conn: &Connection,
+    readings: &[cccanto::CantoEntry],
+    dictionary: &[cccanto::CantoEntry],
+) -> Result<(usize, usize)> {
+    let primary_pinyin: HashMap<String, String> = {
+        let mut stmt = conn.prepare("SELECT character, mandarin_pinyin FROM characters")?;
+        let result = stmt
+            .query_map([], |row| Ok((row.get(0)?, pinyin_key(&row.get::<_, String>(1)?))))?
+            .collect::<Result<HashMap<_, _>>>()?;
+        result
+    };
+
+    // character -> (jyutping, matches the primary pinyin).
+    // CC-Canto comes second so its Jyutping only fills gaps left by the readings file.
+    let mut chosen: HashMap<&str, (&str, bool)> = HashMap::new();
+    for entry in readings.iter().chain(dictionary) {
+        let Some(pinyin) = primary_pinyin.get(&entry.simplified) else {
+            continue;
+        };
+        let matches = *pinyin == pinyin_key(&entry.pinyin);
+        match chosen.get(entry.simplified.as_str()) {
+            Some((_, true)) => {}
+            Some((_, false)) if !matches => {}
+            _ => {
+                chosen.insert(&entry.simplified, (&entry.jyutping, matches));
+            }
+        }
+    }
+
+    let mut jyutping_stmt = conn.prepare(
+        "UPDATE characters SET cantonese_jyutping = ?1, updated_at = datetime('now') WHERE character = ?2"
+    )?;
+    for (character, (jyutping, _)) in &chosen {
+        jyutping_stmt.execute(rusqlite::params![jyutping, character])?;
+    }
+
+    let mut word_stmt = conn.prepare(
+        "INSERT OR IGNORE INTO characters (
+            character, simplified, traditional, mandarin_pinyin, cantonese_jyutping,
+            definition, frequency_rank, is_word
+        ) VALUES (?1, ?1, ?2, ?3, ?4, ?5, 999999, ?6)"
+    )?;
+    let mut sense_stmt = conn.prepare(
+        "INSERT INTO senses (character_id, traditional, pinyin, definition, source, source_line)
+         SELECT id, ?2, ?3, ?4, 'cccanto', ?5 FROM characters WHERE character = ?1"
+    )?;
+
+    let mut new_words = 0;
+    for entry in dictionary {
+        let definition = entry.definitions.join("; ");
+        new_words += word_stmt.execute(rusqlite::params![
+            entry.simplified,
+            entry.traditional,
+            entry.pinyin,
+            entry.jyutping,
+            definition,
+            entry.is_word,
+        ])?;
+        sense_stmt.execute(rusqlite::params![
+            entry.simplified,
+            entry.traditional,
+            entry.pinyin,
+            definition,
+            entry.line_number as i64,
+        ])?;
+    }
+
+    Ok((chosen.len() + new_words, new_words))
+}
+
+/// Readings compare equal however the tones and ü are written ("lu:4", "lv4", "Lü4")
+fn pinyin_key(pinyin: &str) -> String {
+    crate::pinyin::to_numbered(pinyin).to_lowercase()
+}
+
 /// Calculate and populate introduction_rank for all characters and words
 pub fn populate_introduction_ranks(db_path: &str) -> Result<(), Box<dyn std::error::Error>> {
     let mut conn = Connection::open(db_path)?;
@@ -324,3 +554,114 @@ pub fn verify_database(path: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::cccanto::CantoEntry;
+    use crate::parsers::cedict::CedictEntry;
+
+    fn dictionary_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../../schema.sql")).unwrap();
+        conn
+    }
+
+    fn cedict_entry(simplified: &str, pinyin: &str, definitions: &[&str], line_number: usize) -> EnrichedEntry {
+        EnrichedEntry {
+            cedict: CedictEntry {
+                traditional: simplified.to_string(),
+                simplified: simplified.to_string(),
+                pinyin: pinyin.to_string(),
+                definitions: definitions.iter().map(|d| d.to_string()).collect(),
+                is_word: simplified.chars().count() > 1,
+                line_number,
+            },
+            frequency_rank: Some(line_number as i32),
+        }
+    }
+
+    fn canto_entry(simplified: &str, pinyin: &str, jyutping: &str, definitions: &[&str]) -> CantoEntry {
+        CantoEntry {
+            traditional: simplified.to_string(),
+            simplified: simplified.to_string(),
+            pinyin: pinyin.to_string(),
+            jyutping: jyutping.to_string(),
+            definitions: definitions.iter().map(|d| d.to_string()).collect(),
+            is_word: simplified.chars().count() > 1,
+            line_number: 1,
+        }
+    }
+
+    #[test]
+    fn test_heteronym_senses_share_one_character() {
+        let conn = dictionary_db();
+        insert_characters(&conn, vec![
+            cedict_entry("了", "le5", &["completed action marker"], 10),
+            cedict_entry("了", "liao3", &["to finish", "to understand"], 11),
+            cedict_entry("好", "hao3", &["good"], 12),
+        ]).unwrap();
+
+        let (id, pinyin): (i32, String) = conn.query_row(
+            "SELECT id, mandarin_pinyin FROM characters WHERE character = '了'", [], |row| Ok((row.get(0)?, row.get(1)?))
+        ).unwrap();
+        assert_eq!(pinyin, "le5");
+        let count: i32 = conn.query_row("SELECT COUNT(*) FROM characters", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2);
+
+        let mut stmt = conn.prepare("SELECT pinyin, definition, source_line FROM senses WHERE character_id = ?1 ORDER BY id").unwrap();
+        let senses: Vec<(String, String, i64)> = stmt
+            .query_map([id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(senses, vec![
+            ("le5".to_string(), "completed action marker".to_string(), 10),
+            ("liao3".to_string(), "to finish; to understand".to_string(), 11),
+        ]);
+    }
+
+    #[test]
+    fn test_insert_cantonese_merges_and_adds_words() {
+        let conn = dictionary_db();
+        insert_characters(&conn, vec![
+            cedict_entry("行", "xing2", &["to walk"], 1),
+            cedict_entry("行", "hang2", &["row"], 2),
+            cedict_entry("好", "hao3", &["good"], 3),
+        ]).unwrap();
+
+        // The reading matching the primary pinyin wins over the first one in the file
+        let readings = vec![
+            canto_entry("行", "hang2", "hong4", &[]),
+            canto_entry("行", "xing2", "hang4", &[]),
+        ];
+        let dictionary = vec![
+            canto_entry("行", "hang2", "hong4", &["line of business"]),
+            canto_entry("佢", "qu2", "keoi5", &["he", "she", "it"]),
+        ];
+        assert_eq!(insert_cantonese(&conn, &readings, &dictionary).unwrap(), (2, 1));
+
+        let jyutping = |character: &str| -> Option<String> {
+            conn.query_row("SELECT cantonese_jyutping FROM characters WHERE character = ?1", [character], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(jyutping("行").as_deref(), Some("hang4"));
+        assert_eq!(jyutping("好"), None);
+        assert_eq!(jyutping("佢").as_deref(), Some("keoi5"));
+
+        let (rank, definition): (i32, String) = conn.query_row(
+            "SELECT frequency_rank, definition FROM characters WHERE character = '佢'", [], |row| Ok((row.get(0)?, row.get(1)?))
+        ).unwrap();
+        assert_eq!((rank, definition.as_str()), (999999, "he; she; it"));
+
+        // CC-Canto senses are added next to the CEDICT ones
+        let mut stmt = conn.prepare(
+            "SELECT s.source, COUNT(*) FROM senses s JOIN characters c ON c.id = s.character_id
+             WHERE c.character = ?1 GROUP BY s.source ORDER BY s.source"
+        ).unwrap();
+        let mut sources = |character: &str| -> Vec<(String, i32)> {
+            stmt.query_map([character], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().collect::<Result<Vec<_>>>().unwrap()
+        };
+        assert_eq!(sources("行"), vec![("cccanto".to_string(), 1), ("cedict".to_string(), 2)]);
+        assert_eq!(sources("佢"), vec![("cccanto".to_string(), 1)]);
+    }
+}
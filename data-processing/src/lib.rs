@@ -1,5 +1,9 @@
 pub mod parsers;
 pub mod database;
+pub mod search;
+pub mod pinyin;
+pub mod srs;
+pub mod simulation;
 
 use parsers::cedict::CedictEntry;
 use parsers::subtlex::FrequencyData;
@@ -0,0 +1,168 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// One line of CC-Canto (cccanto-webdist.txt) or of the CC-CEDICT Cantonese readings file.
+/// Both use the CEDICT layout with the Jyutping in braces; the readings file has no definitions.
+#[derive(Debug, Clone)]
+pub struct CantoEntry {
+    pub traditional: String,
+    pub simplified: String,
+    pub pinyin: String,
+    pub jyutping: String,
+    pub definitions: Vec<String>,
+    pub is_word: bool,
+    pub line_number: usize,
+}
+
+/// Parse the CC-Canto dictionary: Cantonese words and senses that CC-CEDICT doesn't have.
+/// Lines without definitions are skipped.
+pub fn parse_cccanto_file(path: &str) -> Result<Vec<CantoEntry>, Box<dyn std::error::Error>> {
+    let entries: Vec<CantoEntry> = parse_canto_file(path)?
+        .into_iter()
+        .filter(|entry| !entry.definitions.is_empty())
+        .collect();
+
+    println!("Parsed {} entries from CC-Canto", entries.len());
+    Ok(entries)
+}
+
+/// Parse cccedict-canto-readings: Jyutping for CC-CEDICT headwords, keyed by their pinyin.
+pub fn parse_readings_file(path: &str) -> Result<Vec<CantoEntry>, Box<dyn std::error::Error>> {
+    let entries = parse_canto_file(path)?;
+
+    println!("Parsed {} Jyutping readings from CC-CEDICT-Canto", entries.len());
+    Ok(entries)
+}
+
+fn parse_canto_file(path: &str) -> Result<Vec<CantoEntry>, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut entries = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+
+        // Skip comments and empty lines
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_canto_line(&line) {
+            Some(mut entry) => {
+                entry.line_number = line_number;
+                entries.push(entry);
+            }
+            None => {
+                eprintln!("Warning: Could not parse line {} of {}: {}", line_number, path, line);
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+fn parse_canto_line(line: &str) -> Option<CantoEntry> {
+    // Format: Traditional Simplified [pin1 yin1] {jyut6 ping3} /def1/def2/ # comment
+
+    let pinyin_start = line.find('[')?;
+    let pinyin_end = line.find(']')?;
+    let pinyin = line[pinyin_start + 1..pinyin_end].trim().to_string();
+
+    let chars: Vec<&str> = line[..pinyin_start].split_whitespace().collect();
+    if chars.len() < 2 {
+        return None;
+    }
+    let traditional = chars[0].to_string();
+    let simplified = chars[1].to_string();
+
+    let rest = &line[pinyin_end + 1..];
+    let jyutping_start = rest.find('{')?;
+    let jyutping_end = rest.find('}')?;
+    if jyutping_end < jyutping_start {
+        return None;
+    }
+    let jyutping = rest[jyutping_start + 1..jyutping_end].trim().to_string();
+    if jyutping.is_empty() {
+        return None;
+    }
+
+    // Definitions sit between the first and last '/'; anything after is an editor's comment
+    let defs_part = &rest[jyutping_end + 1..];
+    let definitions: Vec<String> = match (defs_part.find('/'), defs_part.rfind('/')) {
+        (Some(first), Some(last)) if last > first => defs_part[first + 1..last]
+            .split('/')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let is_word = simplified.chars().count() > 1;
+
+    Some(CantoEntry {
+        traditional,
+        simplified,
+        pinyin,
+        jyutping,
+        definitions,
+        is_word,
+        line_number: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dictionary_line() {
+        let entry = parse_canto_line("冇問題 冇问题 [mao3 wen4 ti2] {mou5 man6 tai4} /no problem/ # adapted from cc-cedict").unwrap();
+        assert_eq!(entry.traditional, "冇問題");
+        assert_eq!(entry.simplified, "冇问题");
+        assert_eq!(entry.pinyin, "mao3 wen4 ti2");
+        assert_eq!(entry.jyutping, "mou5 man6 tai4");
+        assert_eq!(entry.definitions, vec!["no problem"]);
+        assert!(entry.is_word);
+    }
+
+    #[test]
+    fn test_parse_readings_line() {
+        let entry = parse_canto_line("說 说 [shuo1] {syut3}").unwrap();
+        assert_eq!(entry.simplified, "说");
+        assert_eq!(entry.jyutping, "syut3");
+        assert!(entry.definitions.is_empty());
+        assert!(!entry.is_word);
+    }
+
+    #[test]
+    fn test_parse_line_without_jyutping() {
+        assert!(parse_canto_line("中 中 [zhong1] /middle/").is_none());
+        assert!(parse_canto_line("中 中 [zhong1] {} /middle/").is_none());
+    }
+
+    #[test]
+    fn test_parse_files() {
+        let dir = std::env::temp_dir();
+        let dictionary_path = dir.join("cccanto_dictionary_test.txt");
+        let readings_path = dir.join("cccanto_readings_test.txt");
+        std::fs::write(&dictionary_path, concat!(
+            "# CC-Canto\n",
+            "佢 佢 [qu2] {keoi5} /he; she; it/\n",
+            "啲 啲 [di1] {di1}\n",
+            "not a dictionary line\n",
+        )).unwrap();
+        std::fs::write(&readings_path, "# readings\n了 了 [le5] {liu5}\n了 了 [liao3] {liu5}\n").unwrap();
+
+        let dictionary = parse_cccanto_file(dictionary_path.to_str().unwrap()).unwrap();
+        let readings = parse_readings_file(readings_path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&dictionary_path).ok();
+        std::fs::remove_file(&readings_path).ok();
+
+        assert_eq!(dictionary.len(), 1);
+        assert_eq!(dictionary[0].line_number, 2);
+        assert_eq!(dictionary[0].definitions, vec!["he; she; it"]);
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[1].pinyin, "liao3");
+    }
+}
@@ -8,6 +8,7 @@ pub struct CedictEntry {
     pub pinyin: String,
     pub definitions: Vec<String>,
     pub is_word: bool,  // true if multi-character
+    pub line_number: usize,  // Line in the CEDICT file this entry came from
 }
 
 pub fn parse_cedict_file(path: &str) -> Result<Vec<CedictEntry>, Box<dyn std::error::Error>> {
@@ -26,7 +27,10 @@ pub fn parse_cedict_file(path: &str) -> Result<Vec<CedictEntry>, Box<dyn std::er
         }
 
         match parse_cedict_line(&line) {
-            Some(entry) => entries.push(entry),
+            Some(mut entry) => {
+                entry.line_number = line_number;
+                entries.push(entry);
+            }
             None => {
                 eprintln!("Warning: Could not parse line {}: {}", line_number, line);
             }
@@ -87,6 +91,7 @@ fn parse_cedict_line(line: &str) -> Option<CedictEntry> {
         pinyin,
         definitions,
         is_word,
+        line_number: 0,
     })
 }
 
@@ -117,6 +122,21 @@ mod tests {
         assert!(entry.is_word);
     }
 
+    #[test]
+    fn test_parse_file_keeps_every_reading_with_line_numbers() {
+        let path = std::env::temp_dir().join("cedict_heteronym_test.u8");
+        std::fs::write(&path, "# CC-CEDICT\n了 了 [le5] /(completed action marker)/\n了 了 [liao3] /to finish/\n").unwrap();
+
+        let entries = parse_cedict_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].pinyin, "le5");
+        assert_eq!(entries[0].line_number, 2);
+        assert_eq!(entries[1].pinyin, "liao3");
+        assert_eq!(entries[1].line_number, 3);
+    }
+
     #[test]
     fn test_parse_invalid_line() {
         let line = "Invalid line without proper format";
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// One line of Make Me a Hanzi's dictionary.txt
+#[derive(Debug, Clone, Deserialize)]
+pub struct HanziEntry {
+    pub character: String,
+    #[serde(default)]
+    pub definition: Option<String>,
+    #[serde(default)]
+    pub pinyin: Vec<String>,
+    #[serde(default)]
+    pub decomposition: Option<String>,
+    #[serde(default)]
+    pub etymology: Option<Etymology>,
+    #[serde(default)]
+    pub radical: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Etymology {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phonetic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub semantic: Option<String>,
+}
+
+/// One line of graphics.txt: SVG path per stroke (in stroke order) and the stroke medians
+/// used to animate them. Coordinates are on a 1024×1024 grid with the y axis flipped.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HanziGraphics {
+    pub character: String,
+    pub strokes: Vec<String>,
+    pub medians: Vec<Vec<[i32; 2]>>,
+}
+
+impl HanziEntry {
+    /// Ideographic description, or None when Make Me a Hanzi doesn't know it ("？")
+    pub fn known_decomposition(&self) -> Option<&str> {
+        self.decomposition
+            .as_deref()
+            .filter(|d| !d.is_empty() && !d.chars().all(|c| c == '？'))
+    }
+}
+
+pub fn parse_dictionary_file(path: &str) -> Result<HashMap<String, HanziEntry>, Box<dyn std::error::Error>> {
+    let entries: HashMap<String, HanziEntry> = parse_json_lines::<HanziEntry>(path)?
+        .into_iter()
+        .map(|entry| (entry.character.clone(), entry))
+        .collect();
+
+    println!("Parsed {} Make Me a Hanzi dictionary entries", entries.len());
+    Ok(entries)
+}
+
+pub fn parse_graphics_file(path: &str) -> Result<HashMap<String, HanziGraphics>, Box<dyn std::error::Error>> {
+    let graphics: HashMap<String, HanziGraphics> = parse_json_lines::<HanziGraphics>(path)?
+        .into_iter()
+        .map(|graphics| (graphics.character.clone(), graphics))
+        .collect();
+
+    println!("Parsed {} Make Me a Hanzi stroke graphics", graphics.len());
+    Ok(graphics)
+}
+
+/// Both files are one JSON object per line
+fn parse_json_lines<T: for<'de> Deserialize<'de>>(path: &str) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut items = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<T>(&line) {
+            Ok(item) => items.push(item),
+            Err(e) => eprintln!("Warning: skipping line {} of {}: {}", index + 1, path, e),
+        }
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dictionary_file() {
+        let path = std::env::temp_dir().join("makemeahanzi_dictionary_test.txt");
+        std::fs::write(&path, concat!(
+            r#"{"character":"好","definition":"good, excellent","pinyin":["hǎo","hào"],"decomposition":"⿰女子","etymology":{"type":"ideographic","hint":"A woman 女 with a son 子"},"radical":"女","matches":[[0],[0],[0],[1],[1],[1]]}"#, "\n",
+            r#"{"character":"⺀","pinyin":[],"decomposition":"？","radical":"⺀","matches":[null,null]}"#, "\n",
+            "not json\n",
+        )).unwrap();
+
+        let entries = parse_dictionary_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(entries.len(), 2);
+        let hao = &entries["好"];
+        assert_eq!(hao.radical.as_deref(), Some("女"));
+        assert_eq!(hao.known_decomposition(), Some("⿰女子"));
+        assert_eq!(hao.pinyin, vec!["hǎo", "hào"]);
+        assert_eq!(hao.etymology.as_ref().unwrap().kind, "ideographic");
+        assert_eq!(entries["⺀"].known_decomposition(), None);
+    }
+
+    #[test]
+    fn test_parse_graphics_file() {
+        let path = std::env::temp_dir().join("makemeahanzi_graphics_test.txt");
+        std::fs::write(&path, concat!(
+            r#"{"character":"一","strokes":["M 518 382 Q 572 385 623 389 Z"],"medians":[[[121,393],[890,393]]]}"#, "\n",
+        )).unwrap();
+
+        let graphics = parse_graphics_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let yi = &graphics["一"];
+        assert_eq!(yi.strokes.len(), 1);
+        assert_eq!(yi.medians[0], vec![[121, 393], [890, 393]]);
+    }
+}
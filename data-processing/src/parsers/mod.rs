@@ -1,2 +1,4 @@
+pub mod cccanto;
 pub mod cedict;
+pub mod makemeahanzi;
 pub mod subtlex;
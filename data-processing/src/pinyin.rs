@@ -0,0 +1,511 @@
+// Mandarin pinyin as CC-CEDICT stores it ("han4 zi4", "nu:3", "yi1 dian3 r5") and as people
+// type it ("hànzì", "nv3", "yidianr"): syllable segmentation, tone numbers ↔ tone marks, ü,
+// neutral tones, erhua and tone sandhi display forms.
+use rusqlite::{Connection, Result};
+
+/// Every standard syllable without tones, ü written out (lü, nüe; ju, xue keep u)
+const SYLLABLES: &str = "a ai an ang ao \
+    ba bai ban bang bao bei ben beng bi bian biao bie bin bing bo bu \
+    ca cai can cang cao ce cen ceng cha chai chan chang chao che chen cheng chi chong chou chu chua chuai \
+    chuan chuang chui chun chuo ci cong cou cu cuan cui cun cuo \
+    da dai dan dang dao de dei den deng di dia dian diao die ding diu dong dou du duan dui dun duo \
+    e ei en eng er \
+    fa fan fang fei fen feng fiao fo fou fu \
+    ga gai gan gang gao ge gei gen geng gong gou gu gua guai guan guang gui gun guo \
+    ha hai han hang hao he hei hen heng hm hng hong hou hu hua huai huan huang hui hun huo \
+    ji jia jian jiang jiao jie jin jing jiong jiu ju juan jue jun \
+    ka kai kan kang kao ke kei ken keng kong kou ku kua kuai kuan kuang kui kun kuo \
+    la lai lan lang lao le lei leng li lia lian liang liao lie lin ling liu lo long lou lu luan lun luo lü lüe \
+    m ma mai man mang mao me mei men meng mi mian miao mie min ming miu mo mou mu \
+    n na nai nan nang nao ne nei nen neng ng ni nian niang niao nie nin ning niu nong nou nu nuan nuo nü nüe \
+    o ou \
+    pa pai pan pang pao pei pen peng pi pian piao pie pin ping po pou pu \
+    qi qia qian qiang qiao qie qin qing qiong qiu qu quan que qun \
+    ran rang rao re ren reng ri rong rou ru rua ruan rui run ruo \
+    sa sai san sang sao se sen seng sha shai shan shang shao she shei shen sheng shi shou shu shua shuai \
+    shuan shuang shui shun shuo si song sou su suan sui sun suo \
+    ta tai tan tang tao te teng ti tian tiao tie ting tong tou tu tuan tui tun tuo \
+    wa wai wan wang wei wen weng wo wu \
+    xi xia xian xiang xiao xie xin xing xiong xiu xu xuan xue xun \
+    ya yan yang yao ye yi yin ying yo yong you yu yuan yue yun \
+    za zai zan zang zao ze zei zen zeng zha zhai zhan zhang zhao zhe zhei zhen zheng zhi zhong zhou zhu \
+    zhua zhuai zhuan zhuang zhui zhun zhuo zi zong zou zu zuan zui zun zuo";
+
+const LONGEST_SYLLABLE: usize = 6;
+
+const TONE_MARKS: [(char, [char; 4]); 6] = [
+    ('a', ['ā', 'á', 'ǎ', 'à']),
+    ('e', ['ē', 'é', 'ě', 'è']),
+    ('i', ['ī', 'í', 'ǐ', 'ì']),
+    ('o', ['ō', 'ó', 'ǒ', 'ò']),
+    ('u', ['ū', 'ú', 'ǔ', 'ù']),
+    ('ü', ['ǖ', 'ǘ', 'ǚ', 'ǜ']),
+];
+
+// Combining marks for syllables without a vowel (m, n, ng, hm, hng)
+const COMBINING_TONE_MARKS: [char; 4] = ['\u{0304}', '\u{0301}', '\u{030C}', '\u{0300}'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Syllable {
+    /// Lowercase letters without a tone, ü written as ü ("zhuang", "lü")
+    pub letters: String,
+    /// 1-4, 5 for the neutral tone, None if the input didn't say
+    pub tone: Option<u8>,
+    /// Takes the erhua suffix ("diǎnr", CEDICT "dian3 r5")
+    pub erhua: bool,
+    /// Written with a capital (proper nouns in CEDICT)
+    pub capitalized: bool,
+}
+
+impl Syllable {
+    /// The syllable with its tone mark ("nǚ", "diǎnr"); neutral and unknown tones are unmarked
+    pub fn marked(&self) -> String {
+        let mut chars: Vec<char> = self.letters.chars().collect();
+        if let Some(tone @ 1..=4) = self.tone {
+            let index = tone as usize - 1;
+            match mark_position(&chars) {
+                Some(position) => {
+                    let (_, marks) = TONE_MARKS.iter().find(|(vowel, _)| *vowel == chars[position]).unwrap();
+                    chars[position] = marks[index];
+                }
+                None => chars.insert(1, COMBINING_TONE_MARKS[index]),
+            }
+        }
+
+        let mut marked: String = chars.into_iter().collect();
+        if self.capitalized {
+            marked = capitalize(&marked);
+        }
+        if self.erhua {
+            marked.push('r');
+        }
+        marked
+    }
+
+    /// The syllable as CEDICT writes it ("nu:3", "dian3 r5")
+    pub fn numbered(&self) -> String {
+        let mut numbered = self.letters.replace('ü', "u:");
+        if self.capitalized {
+            numbered = capitalize(&numbered);
+        }
+        if let Some(tone) = self.tone {
+            numbered.push_str(&tone.to_string());
+        }
+        if self.erhua {
+            numbered.push_str(" r5");
+        }
+        numbered
+    }
+}
+
+/// A space-separated piece of pinyin: its syllables, or the text itself if it isn't pinyin
+#[derive(Debug)]
+enum Piece {
+    Word(Vec<Syllable>),
+    Other(String),
+}
+
+/// Split one written word into syllables ("han4zi4", "hànzì", "xi'an", "nv3", "yidianr").
+/// Returns None if the word isn't pinyin. Ambiguous toneless runs prefer the fewest syllables,
+/// then the longest first syllable ("xian" is one syllable; write "xi'an" for two).
+pub fn split_word(word: &str) -> Option<Vec<Syllable>> {
+    let word = word.replace("u:", "ü").replace("U:", "Ü");
+    let mut syllables = Vec::new();
+    // (letter, tone mark, uppercase)
+    let mut chunk: Vec<(char, Option<u8>, bool)> = Vec::new();
+
+    for c in word.chars() {
+        match c {
+            '1'..='5' => {
+                if chunk.is_empty() {
+                    return None;
+                }
+                push_chunk(&mut syllables, &std::mem::take(&mut chunk), c.to_digit(10).map(|d| d as u8))?;
+            }
+            '\'' | '’' | '-' => {
+                if !chunk.is_empty() {
+                    push_chunk(&mut syllables, &std::mem::take(&mut chunk), None)?;
+                }
+            }
+            _ => {
+                let (letter, tone) = unmark(c.to_lowercase().next()?);
+                if !letter.is_ascii_lowercase() && letter != 'ü' {
+                    return None;
+                }
+                chunk.push((if letter == 'v' { 'ü' } else { letter }, tone, c.is_uppercase()));
+            }
+        }
+    }
+    if !chunk.is_empty() {
+        push_chunk(&mut syllables, &chunk, None)?;
+    }
+
+    if syllables.is_empty() {
+        None
+    } else {
+        Some(syllables)
+    }
+}
+
+/// Every syllable in a pinyin string; pieces that aren't pinyin (punctuation, "xx5") are skipped
+pub fn syllables(pinyin: &str) -> Vec<Syllable> {
+    pieces(pinyin)
+        .into_iter()
+        .flat_map(|piece| match piece {
+            Piece::Word(syllables) => syllables,
+            Piece::Other(_) => Vec::new(),
+        })
+        .collect()
+}
+
+/// Tone numbers to tone marks: "han4 zi4" → "hàn zì", "nu:3" → "nǚ", "yi1 dian3 r5" → "yī diǎnr"
+pub fn to_marked(pinyin: &str) -> String {
+    render(&pieces(pinyin), Syllable::marked)
+}
+
+/// Tone marks to CEDICT-style tone numbers: "hànzì" → "han4 zi4", "nǚ" → "nu:3".
+/// Syllables without a tone get no number.
+pub fn to_numbered(pinyin: &str) -> String {
+    pieces(pinyin)
+        .iter()
+        .map(|piece| match piece {
+            Piece::Word(syllables) => syllables.iter().map(Syllable::numbered).collect::<Vec<_>>().join(" "),
+            Piece::Other(text) => text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lowercase pinyin without tones or spaces ("Hàn zì", "han4 zi4" → "hanzi"), used for exact matches
+pub fn toneless(pinyin: &str) -> String {
+    pinyin
+        .to_lowercase()
+        .replace("u:", "u")
+        .chars()
+        .map(|c| match unmark(c).0 {
+            'ü' => 'u',
+            letter => letter,
+        })
+        .filter(|c| c.is_ascii_alphabetic())
+        .collect()
+}
+
+/// Tone marks as the word is spoken, after tone sandhi: consecutive third tones
+/// (nǐ hǎo → ní hǎo), 不 before a fourth tone (bú shì) and 一 before other tones
+/// (yí gè, yì tiān). 一 keeps its first tone at the end of a word, after 第 and next to numerals.
+/// `hanzi` is the written form; without it (or if it doesn't line up) only third tones change.
+pub fn to_sandhi_marked(hanzi: &str, pinyin: &str) -> String {
+    let mut pieces = pieces(pinyin);
+    let all = syllables(pinyin);
+    let lexical: Vec<Option<u8>> = all.iter().map(|s| s.tone).collect();
+    let characters = align_hanzi(hanzi, &all);
+
+    let mut spoken = lexical.clone();
+    for i in 0..lexical.len() {
+        let next = lexical.get(i + 1).copied().flatten();
+        match (characters.as_ref().map(|c| c[i]), lexical[i], next) {
+            (Some('不'), Some(4), Some(4)) => spoken[i] = Some(2),
+            (Some('一'), Some(1), Some(next)) if next != 5 => {
+                let characters = characters.as_ref().unwrap();
+                let numeral = |c: Option<&char>| c.is_some_and(|c| "第零〇一二三四五六七八九十百千万萬".contains(*c));
+                if !numeral(i.checked_sub(1).and_then(|p| characters.get(p))) && !numeral(characters.get(i + 1)) {
+                    spoken[i] = Some(if next == 4 { 2 } else { 4 });
+                }
+            }
+            (_, Some(3), Some(3)) => spoken[i] = Some(2),
+            _ => {}
+        }
+    }
+
+    let mut tones = spoken.into_iter();
+    for piece in &mut pieces {
+        if let Piece::Word(syllables) = piece {
+            for syllable in syllables {
+                syllable.tone = tones.next().flatten();
+            }
+        }
+    }
+    render(&pieces, Syllable::marked)
+}
+
+/// Fill the normalized pinyin columns of characters (marked, toneless, sandhi display forms)
+pub fn store_pinyin_forms(conn: &Connection) -> Result<usize> {
+    let rows: Vec<(i32, String, String)> = {
+        let mut stmt = conn.prepare("SELECT id, simplified, mandarin_pinyin FROM characters")?;
+        let result = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>>>()?;
+        result
+    };
+
+    let mut update = conn.prepare(
+        "UPDATE characters SET pinyin_marked = ?2, pinyin_toneless = ?3, pinyin_sandhi = ?4 WHERE id = ?1"
+    )?;
+    for (id, simplified, pinyin) in &rows {
+        update.execute(rusqlite::params![
+            id,
+            to_marked(pinyin),
+            toneless(pinyin),
+            to_sandhi_marked(simplified, pinyin),
+        ])?;
+    }
+
+    Ok(rows.len())
+}
+
+fn pieces(pinyin: &str) -> Vec<Piece> {
+    let mut pieces: Vec<Piece> = Vec::new();
+    for word in pinyin.split_whitespace() {
+        // CEDICT writes erhua as its own "r5" syllable
+        if word.trim_end_matches(|c: char| c.is_ascii_digit()).eq_ignore_ascii_case("r") {
+            if let Some(Piece::Word(previous)) = pieces.last_mut() {
+                previous.last_mut().unwrap().erhua = true;
+                continue;
+            }
+        }
+        pieces.push(match split_word(word) {
+            Some(syllables) => Piece::Word(syllables),
+            None => Piece::Other(word.to_string()),
+        });
+    }
+    pieces
+}
+
+fn render(pieces: &[Piece], syllable: fn(&Syllable) -> String) -> String {
+    pieces
+        .iter()
+        .map(|piece| match piece {
+            Piece::Word(syllables) => syllables.iter().map(syllable).collect::<String>(),
+            Piece::Other(text) => text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Add a chunk's syllables; an "r" on its own is the erhua suffix of the syllable before ("wan2r")
+fn push_chunk(syllables: &mut Vec<Syllable>, chunk: &[(char, Option<u8>, bool)], tone: Option<u8>) -> Option<()> {
+    if chunk.len() == 1 && chunk[0].0 == 'r' {
+        syllables.last_mut()?.erhua = true;
+    } else {
+        syllables.extend(split_chunk(chunk, tone)?);
+    }
+    Some(())
+}
+
+/// Segment a run of letters without separators; `tone` is a tone number typed after it
+fn split_chunk(chunk: &[(char, Option<u8>, bool)], tone: Option<u8>) -> Option<Vec<Syllable>> {
+    let mut letters: Vec<char> = chunk.iter().map(|(letter, _, _)| *letter).collect();
+    // j, q, x and y are never followed by u, so their ü is written u
+    for i in 1..letters.len() {
+        if letters[i] == 'ü' && matches!(letters[i - 1], 'j' | 'q' | 'x' | 'y') {
+            letters[i] = 'u';
+        }
+    }
+
+    let (bounds, erhua) = match segment(&letters) {
+        Some(bounds) => (bounds, false),
+        None if letters.len() > 1 && letters.last() == Some(&'r') => (segment(&letters[..letters.len() - 1])?, true),
+        None => return None,
+    };
+
+    let count = bounds.len();
+    let syllables = bounds
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, end))| {
+            let marked = chunk[start..end].iter().find_map(|(_, tone, _)| *tone);
+            Syllable {
+                letters: letters[start..end].iter().collect(),
+                tone: marked.or(if i == count - 1 { tone } else { None }),
+                erhua: erhua && i == count - 1,
+                capitalized: chunk[start].2,
+            }
+        })
+        .collect();
+    Some(syllables)
+}
+
+/// Syllable boundaries covering all of `letters` with the fewest syllables
+fn segment(letters: &[char]) -> Option<Vec<(usize, usize)>> {
+    let n = letters.len();
+    // best[i] = (syllables needed for letters[i..], length of the first one)
+    let mut best: Vec<Option<(usize, usize)>> = vec![None; n + 1];
+    best[n] = Some((0, 0));
+    for start in (0..n).rev() {
+        for len in (1..=LONGEST_SYLLABLE.min(n - start)).rev() {
+            let Some((rest, _)) = best[start + len] else { continue };
+            let candidate: String = letters[start..start + len].iter().collect();
+            let fewer = match best[start] {
+                Some((count, _)) => rest + 1 < count,
+                None => true,
+            };
+            if fewer && is_syllable(&candidate) {
+                best[start] = Some((rest + 1, len));
+            }
+        }
+    }
+
+    best[0]?;
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    while start < n {
+        let (_, len) = best[start]?;
+        bounds.push((start, start + len));
+        start += len;
+    }
+    Some(bounds)
+}
+
+fn is_syllable(letters: &str) -> bool {
+    SYLLABLES.split_whitespace().any(|syllable| syllable == letters)
+}
+
+/// Where the tone mark goes: a or e if present, o in ou, otherwise the last vowel
+fn mark_position(letters: &[char]) -> Option<usize> {
+    if let Some(position) = letters.iter().position(|c| *c == 'a' || *c == 'e') {
+        return Some(position);
+    }
+    if let Some(position) = letters.windows(2).position(|pair| pair == ['o', 'u']) {
+        return Some(position);
+    }
+    letters.iter().rposition(|c| matches!(c, 'i' | 'o' | 'u' | 'ü'))
+}
+
+/// Split a tone-marked vowel into its letter and tone ('ǚ' → ('ü', Some(3)))
+fn unmark(c: char) -> (char, Option<u8>) {
+    for (vowel, marks) in TONE_MARKS {
+        if let Some(index) = marks.iter().position(|mark| *mark == c) {
+            return (vowel, Some(index as u8 + 1));
+        }
+    }
+    (c, None)
+}
+
+/// The character written for each syllable, if `hanzi` has exactly one per syllable
+/// (erhua syllables may be followed by 儿)
+fn align_hanzi(hanzi: &str, syllables: &[Syllable]) -> Option<Vec<char>> {
+    let mut characters = hanzi.chars().filter(|c| crate::search::is_hanzi(*c)).peekable();
+    let mut aligned = Vec::new();
+    for syllable in syllables {
+        aligned.push(characters.next()?);
+        if syllable.erhua {
+            characters.next_if(|c| *c == '儿' || *c == '兒');
+        }
+    }
+    if characters.next().is_some() {
+        return None;
+    }
+    Some(aligned)
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letters(pinyin: &str) -> Vec<String> {
+        syllables(pinyin).into_iter().map(|s| s.letters).collect()
+    }
+
+    #[test]
+    fn test_segmentation() {
+        assert_eq!(letters("han4zi4"), vec!["han", "zi"]);
+        assert_eq!(letters("hanzi"), vec!["han", "zi"]);
+        assert_eq!(letters("hànzì"), vec!["han", "zi"]);
+        assert_eq!(letters("xian"), vec!["xian"]);
+        assert_eq!(letters("xi'an"), vec!["xi", "an"]);
+        assert_eq!(letters("zhongguoren"), vec!["zhong", "guo", "ren"]);
+        assert!(split_word("hello").is_none());
+        assert!(split_word("xx5").is_none());
+    }
+
+    #[test]
+    fn test_tones_from_numbers_and_marks() {
+        let tones = |pinyin: &str| syllables(pinyin).into_iter().map(|s| s.tone).collect::<Vec<_>>();
+        assert_eq!(tones("ni3hao3"), vec![Some(3), Some(3)]);
+        assert_eq!(tones("nǐ hǎo"), vec![Some(3), Some(3)]);
+        assert_eq!(tones("ma5"), vec![Some(5)]);
+        assert_eq!(tones("nihao3"), vec![None, Some(3)]);
+    }
+
+    #[test]
+    fn test_numbers_to_marks() {
+        assert_eq!(to_marked("han4 zi4"), "hàn zì");
+        assert_eq!(to_marked("ni3hao3"), "nǐhǎo");
+        assert_eq!(to_marked("liu2 xue2"), "liú xué");
+        assert_eq!(to_marked("dui4 gou3"), "duì gǒu");
+        assert_eq!(to_marked("ma5"), "ma");
+        assert_eq!(to_marked("Zhong1 guo2"), "Zhōng guó");
+        assert_eq!(to_marked("m2"), "m\u{301}");
+        assert_eq!(to_marked("xx5"), "xx5");
+    }
+
+    #[test]
+    fn test_u_umlaut() {
+        assert_eq!(to_marked("nu:3"), "nǚ");
+        assert_eq!(to_marked("lv4"), "lǜ");
+        assert_eq!(to_marked("lu:e4"), "lüè");
+        assert_eq!(to_numbered("nǚ"), "nu:3");
+        assert_eq!(to_numbered("nv3"), "nu:3");
+        // After j, q, x and y ü is written u
+        assert_eq!(to_marked("xu:e2"), "xué");
+        assert_eq!(toneless("nu:3"), "nu");
+    }
+
+    #[test]
+    fn test_marks_to_numbers() {
+        assert_eq!(to_numbered("hànzì"), "han4 zi4");
+        assert_eq!(to_numbered("Běijīng"), "Bei3 jing1");
+        assert_eq!(to_numbered("ma"), "ma");
+        assert_eq!(to_numbered("nǐ hǎo ma"), "ni3 hao3 ma");
+    }
+
+    #[test]
+    fn test_erhua() {
+        assert_eq!(to_marked("yi1 dian3 r5"), "yī diǎnr");
+        assert_eq!(to_marked("wan2r"), "wánr");
+        assert_eq!(to_numbered("yīdiǎnr"), "yi1 dian3 r5");
+        assert_eq!(letters("nar"), vec!["na"]);
+        assert_eq!(letters("er2"), vec!["er"]);
+    }
+
+    #[test]
+    fn test_tone_sandhi() {
+        assert_eq!(to_sandhi_marked("你好", "ni3 hao3"), "ní hǎo");
+        assert_eq!(to_sandhi_marked("不是", "bu4 shi4"), "bú shì");
+        assert_eq!(to_sandhi_marked("不好", "bu4 hao3"), "bù hǎo");
+        assert_eq!(to_sandhi_marked("一个", "yi1 ge4"), "yí gè");
+        assert_eq!(to_sandhi_marked("一天", "yi1 tian1"), "yì tiān");
+        assert_eq!(to_sandhi_marked("第一", "di4 yi1"), "dì yī");
+        assert_eq!(to_sandhi_marked("十一月", "shi2 yi1 yue4"), "shí yī yuè");
+        assert_eq!(to_sandhi_marked("一点儿", "yi1 dian3 r5"), "yì diǎnr");
+        // Without matching hanzi only third tones change
+        assert_eq!(to_sandhi_marked("", "yi1 ge4 ni3 hao3"), "yī gè ní hǎo");
+    }
+
+    #[test]
+    fn test_store_pinyin_forms() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../schema.sql")).unwrap();
+        conn.execute(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word)
+             VALUES ('你好', '你好', '你好', 'ni3 hao3', 'hello', 100, 1)",
+            []
+        ).unwrap();
+
+        assert_eq!(store_pinyin_forms(&conn).unwrap(), 1);
+        let forms: (String, String, String) = conn.query_row(
+            "SELECT pinyin_marked, pinyin_toneless, pinyin_sandhi FROM characters",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        ).unwrap();
+        assert_eq!(forms, ("nǐ hǎo".to_string(), "nihao".to_string(), "ní hǎo".to_string()));
+    }
+}
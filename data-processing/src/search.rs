@@ -0,0 +1,218 @@
+use rusqlite::{Connection, Result};
+use crate::pinyin::{self, toneless};
+
+/// Build (or rebuild) the full-text search index over every sense.
+/// Hanzi are indexed one character per token so multi-character queries become phrases.
+/// Pinyin is indexed tone-numbered, toneless and toneless-joined; queries are segmented
+/// with the pinyin module, so numbered, tone-marked and toneless input all match.
+pub fn build_search_index(conn: &Connection) -> Result<usize> {
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS dictionary_fts;
+         CREATE VIRTUAL TABLE dictionary_fts USING fts5(
+             character_id UNINDEXED,
+             toneless UNINDEXED,
+             hanzi,
+             pinyin,
+             definition,
+             tokenize = 'unicode61 remove_diacritics 2'
+         );"
+    )?;
+
+    let rows: Vec<(i32, String, Option<String>, String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT s.character_id, c.simplified, s.traditional, s.pinyin, s.definition
+             FROM senses s
+             JOIN characters c ON c.id = s.character_id"
+        )?;
+        let result = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        result
+    };
+
+    let mut insert = conn.prepare(
+        "INSERT INTO dictionary_fts (character_id, toneless, hanzi, pinyin, definition)
+         VALUES (?1, ?2, ?3, ?4, ?5)"
+    )?;
+
+    for (character_id, simplified, traditional, pinyin, definition) in &rows {
+        let mut hanzi = hanzi_tokens(simplified);
+        if let Some(traditional) = traditional.as_ref().filter(|t| *t != simplified) {
+            hanzi.push(' ');
+            hanzi.push_str(&hanzi_tokens(traditional));
+        }
+
+        insert.execute(rusqlite::params![
+            character_id,
+            toneless(pinyin),
+            hanzi,
+            pinyin_search_forms(pinyin),
+            definition,
+        ])?;
+    }
+
+    Ok(rows.len())
+}
+
+/// Turn user input into an FTS5 MATCH expression, or None if there is nothing to search for
+pub fn build_match_query(input: &str) -> Option<String> {
+    let input = input.trim();
+
+    if input.chars().any(is_hanzi) {
+        let hanzi: String = input.chars().filter(|c| is_hanzi(*c)).collect();
+        return Some(format!("hanzi : \"{}\"", hanzi_tokens(&hanzi)));
+    }
+
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|w| {
+            w.to_lowercase()
+                .chars()
+                .filter(|c| c.is_alphanumeric() || *c == ':')
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    if words.is_empty() {
+        return None;
+    }
+
+    // Pinyin: "han4zi4" and "hànzì" → "han4 zi4", "hanzi" → "han zi", and "hanzi" as a prefix
+    let joined = toneless(&words.concat());
+    let syllables: Option<Vec<pinyin::Syllable>> = words
+        .iter()
+        .map(|w| pinyin::split_word(w))
+        .collect::<Option<Vec<_>>>()
+        .map(|words| words.concat());
+    let pinyin = match syllables {
+        Some(syllables) => Some(format!("\"{}\" OR \"{}\"*", syllable_tokens(&syllables), joined)),
+        None if !joined.is_empty() => Some(format!("\"{}\"*", joined)),
+        None => None,
+    };
+
+    // English: every word must appear, the last one may be unfinished
+    let last = words.len() - 1;
+    let definition = words
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            let w = w.replace(':', "");
+            if i == last { format!("\"{}\"*", w) } else { format!("\"{}\"", w) }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(match pinyin {
+        Some(pinyin) => format!("pinyin : ({}) OR definition : ({})", pinyin, definition),
+        None => format!("definition : ({})", definition),
+    })
+}
+
+fn pinyin_search_forms(pinyin: &str) -> String {
+    let lower = pinyin::to_numbered(pinyin).to_lowercase();
+    let numbered = lower.replace("u:", "u");
+    let numbered_v = lower.replace("u:", "v");
+    let toneless_syllables: String = numbered.chars().filter(|c| !c.is_ascii_digit()).collect();
+
+    let mut forms = vec![numbered.clone()];
+    if numbered_v != numbered {
+        forms.push(numbered_v);
+    }
+    forms.push(toneless_syllables);
+    forms.push(toneless(pinyin));
+    forms.join(" ")
+}
+
+/// Query tokens as indexed: tone-numbered ("nv3") if every syllable has a tone, otherwise toneless
+fn syllable_tokens(syllables: &[pinyin::Syllable]) -> String {
+    let numbered = syllables.iter().all(|s| s.tone.is_some());
+    syllables
+        .iter()
+        .map(|s| {
+            let mut token = s.letters.replace('ü', "v");
+            if numbered {
+                token.push_str(&s.tone.unwrap().to_string());
+            }
+            if s.erhua {
+                token.push_str(if numbered { " r5" } else { " r" });
+            }
+            token
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn hanzi_tokens(text: &str) -> String {
+    text.chars().map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// CJK ideographs (unified, extensions and compatibility) plus 〇, which CEDICT lists as a character
+pub fn is_hanzi(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{2FA1F}' | '〇')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../schema.sql")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO characters (id, character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word)
+             VALUES (1, '汉字', '汉字', '漢字', 'han4 zi4', 'Chinese character', 900, 1),
+                    (2, '女', '女', '女', 'nu:3', 'female; woman', 300, 0);
+             INSERT INTO senses (character_id, traditional, pinyin, definition)
+             VALUES (1, '漢字', 'han4 zi4', 'Chinese character'),
+                    (2, '女', 'nu:3', 'female; woman');"
+        ).unwrap();
+        build_search_index(&conn).unwrap();
+        conn
+    }
+
+    fn search(conn: &Connection, input: &str) -> Vec<i32> {
+        let query = build_match_query(input).unwrap();
+        let mut stmt = conn
+            .prepare("SELECT DISTINCT character_id FROM dictionary_fts WHERE dictionary_fts MATCH ?1")
+            .unwrap();
+        stmt.query_map([query], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn test_matches_simplified_and_traditional_hanzi() {
+        let conn = index();
+        assert_eq!(search(&conn, "汉字"), vec![1]);
+        assert_eq!(search(&conn, "漢字"), vec![1]);
+        assert!(search(&conn, "字汉").is_empty());
+    }
+
+    #[test]
+    fn test_matches_every_pinyin_style() {
+        let conn = index();
+        assert_eq!(search(&conn, "han4 zi4"), vec![1]);
+        assert_eq!(search(&conn, "han4zi4"), vec![1]);
+        assert_eq!(search(&conn, "hanzi"), vec![1]);
+        assert_eq!(search(&conn, "han zi"), vec![1]);
+        assert_eq!(search(&conn, "hàn zì"), vec![1]);
+        assert_eq!(search(&conn, "nü3"), vec![2]);
+        assert_eq!(search(&conn, "nv3"), vec![2]);
+        assert_eq!(search(&conn, "nǚ"), vec![2]);
+    }
+
+    #[test]
+    fn test_matches_english_words() {
+        let conn = index();
+        assert_eq!(search(&conn, "woman"), vec![2]);
+        assert_eq!(search(&conn, "chinese char"), vec![1]);
+    }
+
+    #[test]
+    fn test_toneless() {
+        assert_eq!(toneless("Hàn zì"), "hanzi");
+        assert_eq!(toneless("nu:3"), "nu");
+        assert!(build_match_query("  ?! ").is_none());
+    }
+}
@@ -0,0 +1,409 @@
+// Review workload simulation
+// Replays the SRS scheduler over a copy of the user's cards, day by day, to forecast how many
+// reviews and new characters each day will bring under a given pacing policy and recall rate.
+// Answers are deterministic: a running total hands out exactly `recall_rate` correct answers,
+// so the same inputs always give the same forecast.
+
+use crate::srs::{scheduler_from_settings, Grade, MasteryPolicy, SrsCard};
+use rusqlite::{Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+
+/// Interval and due time of a newly introduced card (30 minutes after the introduction session)
+const NEW_CARD_INTERVAL_DAYS: f32 = 0.0417;
+const NEW_CARD_FIRST_REVIEW_DAYS: f32 = 30.0 / 1440.0;
+
+/// One card's scheduling state; times are in days relative to the start of the simulation
+#[derive(Debug, Clone)]
+pub struct SimCard {
+    pub card: SrsCard,
+    pub is_mastered: bool,
+    pub correct_streak: i32,
+    // times_correct when the card last lost mastery
+    pub times_correct_at_demotion: i32,
+    // Negative when the card is already overdue
+    pub due_in_days: f32,
+    pub last_reviewed_in_days: Option<f32>,
+}
+
+impl SimCard {
+    fn new_at(day: f32) -> Self {
+        SimCard {
+            card: SrsCard {
+                current_interval_days: NEW_CARD_INTERVAL_DAYS,
+                previous_interval_days: NEW_CARD_INTERVAL_DAYS,
+                ease_factor: 2.25,
+                ..Default::default()
+            },
+            is_mastered: false,
+            correct_streak: 0,
+            times_correct_at_demotion: 0,
+            due_in_days: day + NEW_CARD_FIRST_REVIEW_DAYS,
+            last_reviewed_in_days: None,
+        }
+    }
+}
+
+/// Where a track stands at the start of the simulation
+#[derive(Debug, Clone, Default)]
+pub struct StudyState {
+    // Introduced, unsuspended cards in the enabled directions
+    pub cards: Vec<SimCard>,
+    // Characters unlocked but not introduced yet
+    pub ready_to_learn: usize,
+    // Characters that later unlocks can still add
+    pub unlockable: usize,
+    // One card per enabled direction
+    pub cards_per_character: usize,
+    pub initial_unlock_completed: bool,
+    // None if nothing has been unlocked yet
+    pub hours_since_unlock: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationParams {
+    pub days: u32,
+    // Share of reviews answered correctly, 0.0 to 1.0
+    pub recall_rate: f32,
+    pub scheduler: String,
+    pub desired_retention: f32,
+    // Unlock pacing (see PacingPolicy in the app)
+    pub initial_batch_size: usize,
+    pub batch_size: usize,
+    pub cooldown_hours: f32,
+    pub workload_aware: bool,
+    pub max_due_reviews: usize,
+    // Daily limits
+    pub new_cards_per_day: usize,
+    pub review_limit: usize,
+    pub mastery: MasteryPolicy,
+}
+
+impl Default for SimulationParams {
+    fn default() -> Self {
+        SimulationParams {
+            days: 30,
+            recall_rate: 0.9,
+            scheduler: crate::srs::DEFAULT_SCHEDULER.to_string(),
+            desired_retention: crate::srs::DEFAULT_DESIRED_RETENTION,
+            initial_batch_size: 100,
+            batch_size: 10,
+            cooldown_hours: 48.0,
+            workload_aware: false,
+            max_due_reviews: 50,
+            new_cards_per_day: 10,
+            review_limit: 100,
+            mastery: MasteryPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayForecast {
+    // 0 = the next 24 hours
+    pub day: u32,
+    // Answers given, counting a card again each time it comes back the same day
+    pub reviews: usize,
+    // Distinct cards reviewed
+    pub cards_reviewed: usize,
+    // Due cards pushed to a later day by the review limit
+    pub held_back: usize,
+    // Characters introduced
+    pub new_cards: usize,
+    // Characters unlocked
+    pub unlocked: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Forecast {
+    pub days: Vec<DayForecast>,
+    pub total_reviews: usize,
+    pub total_new_cards: usize,
+    pub peak_reviews: usize,
+    pub mastered_cards: usize,
+}
+
+/// Hands out correct answers at exactly the recall rate
+struct Recall {
+    rate: f32,
+    credit: f32,
+}
+
+impl Recall {
+    // Starting halfway spreads the wrong answers evenly instead of making the first one wrong
+    fn new(rate: f32) -> Self {
+        Recall { rate: rate.clamp(0.0, 1.0), credit: 0.5 }
+    }
+
+    fn next(&mut self) -> bool {
+        self.credit += self.rate;
+        if self.credit >= 1.0 {
+            self.credit -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+pub fn simulate(state: &StudyState, params: &SimulationParams) -> Forecast {
+    let scheduler = scheduler_from_settings(&params.scheduler, params.desired_retention);
+    let mut recall = Recall::new(params.recall_rate);
+    let cards_per_character = state.cards_per_character.max(1);
+
+    let mut cards = state.cards.clone();
+    let mut ready = state.ready_to_learn;
+    let mut unlockable = state.unlockable;
+    let mut initial_unlock_completed = state.initial_unlock_completed;
+    let mut last_unlock = state.hours_since_unlock.map(|hours| -hours / 24.0);
+    let mut days = Vec::with_capacity(params.days as usize);
+
+    for day in 0..params.days {
+        let start = day as f32;
+        let end = start + 1.0;
+        let mut forecast = DayForecast { day, reviews: 0, cards_reviewed: 0, held_back: 0, new_cards: 0, unlocked: 0 };
+
+        // Unlock the same way check_and_unlock_characters does
+        let unlock = if !initial_unlock_completed {
+            initial_unlock_completed = true;
+            params.initial_batch_size
+        } else {
+            let cooled_down = match last_unlock {
+                Some(at) => (start - at) * 24.0 >= params.cooldown_hours,
+                None => true,
+            };
+            let due_now = cards.iter().filter(|c| c.due_in_days <= start).count();
+            let held_for_workload = params.workload_aware && due_now > params.max_due_reviews;
+            if ready == 0 && cooled_down && !held_for_workload { params.batch_size } else { 0 }
+        };
+        let unlock = unlock.min(unlockable);
+        if unlock > 0 {
+            ready += unlock;
+            unlockable -= unlock;
+            last_unlock = Some(start);
+            forecast.unlocked = unlock;
+        }
+
+        // Introduce up to the daily new card limit
+        let introduced = ready.min(params.new_cards_per_day);
+        ready -= introduced;
+        forecast.new_cards = introduced;
+        cards.extend((0..introduced * cards_per_character).map(|_| SimCard::new_at(start)));
+
+        // Most overdue first; cards held back by the review limit stay due for tomorrow
+        cards.sort_by(|a, b| a.due_in_days.total_cmp(&b.due_in_days));
+        let mut budget = params.review_limit;
+        for card in cards.iter_mut().take_while(|c| c.due_in_days < end) {
+            if budget == 0 {
+                forecast.held_back += 1;
+                continue;
+            }
+            budget -= 1;
+            forecast.cards_reviewed += 1;
+
+            // Failed and new cards come back within the day
+            while card.due_in_days < end {
+                let at = card.due_in_days.max(start);
+                review(card, at, recall.next(), scheduler.as_ref(), &params.mastery);
+                forecast.reviews += 1;
+            }
+        }
+
+        days.push(forecast);
+    }
+
+    Forecast {
+        total_reviews: days.iter().map(|d| d.reviews).sum(),
+        total_new_cards: days.iter().map(|d| d.new_cards).sum(),
+        peak_reviews: days.iter().map(|d| d.reviews).max().unwrap_or(0),
+        mastered_cards: cards.iter().filter(|c| c.is_mastered).count(),
+        days,
+    }
+}
+
+/// Answer one card at `at`, mirroring record_srs_answer in the app
+fn review(card: &mut SimCard, at: f32, correct: bool, scheduler: &dyn crate::srs::Scheduler, mastery: &MasteryPolicy) {
+    card.card.elapsed_days = card.last_reviewed_in_days.map(|last| at - last);
+    let update = scheduler.next_review(&card.card, Grade::from_correct(correct));
+
+    card.correct_streak = if correct { card.correct_streak + 1 } else { 0 };
+    if card.is_mastered && !correct {
+        card.times_correct_at_demotion = card.card.times_correct;
+    }
+    let times_correct = card.card.times_correct + 1 - card.times_correct_at_demotion;
+    card.is_mastered = correct
+        && (card.is_mastered || mastery.is_reached(times_correct, card.correct_streak, update.new_interval_days));
+    let interval = if card.is_mastered {
+        update.new_interval_days.max(mastery.maintenance_interval_days)
+    } else {
+        update.new_interval_days
+    };
+
+    let srs = &mut card.card;
+    srs.previous_interval_days = srs.current_interval_days;
+    srs.current_interval_days = interval;
+    srs.ease_factor = update.new_ease_factor;
+    srs.has_reached_week |= update.reached_week_for_first_time;
+    srs.stability = update.new_stability.or(srs.stability);
+    srs.difficulty = update.new_difficulty.or(srs.difficulty);
+    if correct {
+        srs.times_correct += 1;
+    } else {
+        srs.times_incorrect += 1;
+    }
+
+    card.due_in_days = at + interval;
+    card.last_reviewed_in_days = Some(at);
+}
+
+fn setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row("SELECT value FROM app_settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+}
+
+/// Load a track's cards and unlock state from a user database. The queries follow the app's
+/// rules for directions, suspended cards and unlock candidates; the app's database tests check
+/// them against a migrated database.
+pub fn load_state(conn: &Connection, track: &str) -> Result<StudyState> {
+    // Per-track settings keep the original key for Mandarin
+    let track_key = |key: &str| if track == "mandarin" { key.to_string() } else { format!("{}_{}", key, track) };
+
+    let directions = setting(conn, "card_directions")?.unwrap_or_default();
+    let mut enabled: Vec<&str> = directions.split(',').map(str::trim).filter(|d| !d.is_empty()).collect();
+    if enabled.is_empty() {
+        enabled.push("zh_to_en");
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT direction, current_interval_days, previous_interval_days, ease_factor, times_correct,
+                times_incorrect, has_reached_week, stability, difficulty, is_mastered, correct_streak,
+                times_correct_at_demotion, julianday(next_review_date) - julianday('now'),
+                julianday(last_reviewed) - julianday('now')
+         FROM user_progress
+         WHERE track = ?1 AND introduced = 1 AND is_suspended = 0"
+    )?;
+    let cards = stmt
+        .query_map([track], |row| {
+            let direction: String = row.get(0)?;
+            let card = SimCard {
+                card: SrsCard {
+                    character_id: 0,
+                    current_interval_days: row.get(1)?,
+                    previous_interval_days: row.get(2)?,
+                    ease_factor: row.get(3)?,
+                    times_correct: row.get(4)?,
+                    times_incorrect: row.get(5)?,
+                    has_reached_week: row.get(6)?,
+                    stability: row.get(7)?,
+                    difficulty: row.get(8)?,
+                    elapsed_days: None,
+                },
+                is_mastered: row.get(9)?,
+                correct_streak: row.get(10)?,
+                times_correct_at_demotion: row.get(11)?,
+                // Cards without a due date are never due
+                due_in_days: row.get::<_, Option<f64>>(12)?.map_or(f32::INFINITY, |days| days as f32),
+                last_reviewed_in_days: row.get::<_, Option<f64>>(13)?.map(|days| days as f32),
+            };
+            Ok((direction, card))
+        })?
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|(direction, _)| enabled.contains(&direction.as_str()))
+        .map(|(_, card)| card)
+        .collect();
+
+    let ready_to_learn: usize = conn.query_row(
+        "SELECT COUNT(DISTINCT character_id) FROM user_progress
+         WHERE track = ?1 AND introduced = 0 AND is_suspended = 0",
+        [track],
+        |row| row.get(0),
+    )?;
+    // Words are only unlocked when pulled forward
+    let unlockable: usize = conn.query_row(
+        "SELECT COUNT(*) FROM characters c
+         WHERE (c.is_word = 0
+                OR EXISTS (SELECT 1 FROM pull_forward_queue q WHERE q.character_id = c.id AND q.track = ?1))
+           AND (?1 <> 'cantonese' OR c.cantonese_jyutping IS NOT NULL)
+           AND NOT EXISTS (SELECT 1 FROM user_progress p WHERE p.character_id = c.id AND p.track = ?1)",
+        [track],
+        |row| row.get(0),
+    )?;
+    let hours_since_unlock: Option<f64> = match setting(conn, &track_key("last_unlock_date"))? {
+        Some(date) if !date.is_empty() => conn.query_row(
+            "SELECT (julianday('now') - julianday(?1)) * 24",
+            [date],
+            |row| row.get(0),
+        )?,
+        _ => None,
+    };
+
+    Ok(StudyState {
+        cards,
+        ready_to_learn,
+        unlockable,
+        cards_per_character: enabled.len().max(1),
+        initial_unlock_completed: setting(conn, &track_key("initial_unlock_completed"))?.as_deref() == Some("true"),
+        hours_since_unlock: hours_since_unlock.map(|hours| hours as f32),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_user(unlockable: usize) -> StudyState {
+        StudyState { unlockable, cards_per_character: 1, ..Default::default() }
+    }
+
+    #[test]
+    fn test_recall_rate_is_exact() {
+        let mut recall = Recall::new(0.75);
+        let correct = (0..100).filter(|_| recall.next()).count();
+        assert_eq!(correct, 75);
+
+        // The first answer isn't always the wrong one
+        assert!(Recall::new(0.9).next());
+    }
+
+    #[test]
+    fn test_pacing_and_daily_limits() {
+        let params = SimulationParams { days: 12, initial_batch_size: 20, batch_size: 10, cooldown_hours: 48.0, new_cards_per_day: 10, recall_rate: 1.0, ..Default::default() };
+        let forecast = simulate(&new_user(1000), &params);
+
+        let new_cards: Vec<usize> = forecast.days.iter().map(|d| d.new_cards).collect();
+        let unlocked: Vec<usize> = forecast.days.iter().map(|d| d.unlocked).collect();
+        // The initial batch takes two days to introduce, then 10 every 2 days
+        assert_eq!(new_cards, vec![10, 10, 10, 0, 10, 0, 10, 0, 10, 0, 10, 0]);
+        assert_eq!(unlocked, vec![20, 0, 10, 0, 10, 0, 10, 0, 10, 0, 10, 0]);
+        assert_eq!(forecast.total_new_cards, 70);
+
+        // New cards are reviewed the day they're introduced: 1h and 12h steps with perfect recall
+        assert_eq!(forecast.days[0].cards_reviewed, 10);
+        assert!(forecast.days[0].reviews >= 20);
+        assert!(forecast.days.iter().all(|d| d.held_back == 0));
+    }
+
+    #[test]
+    fn test_review_limit_and_workload_hold() {
+        let mut state = new_user(1000);
+        state.initial_unlock_completed = true;
+        state.cards = (0..50).map(|_| SimCard::new_at(-1.0)).collect();
+
+        let params = SimulationParams { days: 1, review_limit: 30, workload_aware: true, max_due_reviews: 40, ..Default::default() };
+        let forecast = simulate(&state, &params);
+        assert_eq!((forecast.days[0].cards_reviewed, forecast.days[0].held_back), (30, 20));
+        assert_eq!(forecast.days[0].unlocked, 0);
+
+        let params = SimulationParams { workload_aware: false, ..params };
+        assert_eq!(simulate(&state, &params).days[0].unlocked, 10);
+    }
+
+    #[test]
+    fn test_lower_recall_means_more_reviews() {
+        let params = SimulationParams { days: 30, ..Default::default() };
+        let good = simulate(&new_user(500), &SimulationParams { recall_rate: 0.95, ..params.clone() });
+        let poor = simulate(&new_user(500), &SimulationParams { recall_rate: 0.6, ..params });
+        assert!(poor.total_reviews > good.total_reviews);
+        assert_eq!(good.total_new_cards, poor.total_new_cards);
+    }
+}
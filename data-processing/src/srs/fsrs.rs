@@ -0,0 +1,234 @@
+// FSRS (Free Spaced Repetition Scheduler), version 5
+// Models each card with a stability (days until recall probability drops to 90%)
+// and a difficulty (1-10), and schedules the next review for a target retention.
+// Reference: https://github.com/open-spaced-repetition/fsrs4anki/wiki/The-Algorithm
+
+use super::{review_date_after, Grade, Scheduler, SrsCard, SrsUpdate};
+
+const DECAY: f32 = -0.5;
+const FACTOR: f32 = 19.0 / 81.0;
+
+/// Default FSRS-5 parameters (trained on the Anki review dataset)
+pub const DEFAULT_WEIGHTS: [f32; 19] = [
+    0.40255, 1.18385, 3.173, 15.69105, 7.1949, 0.5345, 1.4604, 0.0046, 1.54575, 0.1192,
+    1.01925, 1.9395, 0.11, 0.29605, 2.2698, 0.2315, 2.9898, 0.51655, 0.6621,
+];
+
+// Same bounds as the ladder: never less than 1 hour, never more than 100 years
+const MIN_INTERVAL_DAYS: f32 = 0.0417;
+const MAX_INTERVAL_DAYS: f32 = 36500.0;
+
+pub struct FsrsScheduler {
+    pub weights: [f32; 19],
+    pub desired_retention: f32,
+}
+
+impl FsrsScheduler {
+    pub fn new(desired_retention: f32) -> Self {
+        FsrsScheduler {
+            weights: DEFAULT_WEIGHTS,
+            desired_retention: desired_retention.clamp(0.7, 0.99),
+        }
+    }
+
+    /// Probability of recalling a card `elapsed_days` after its last review
+    pub fn retrievability(&self, elapsed_days: f32, stability: f32) -> f32 {
+        (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+    }
+
+    /// Interval (days) after which recall probability falls to the desired retention
+    pub fn interval_for(&self, stability: f32) -> f32 {
+        let interval = stability / FACTOR * (self.desired_retention.powf(1.0 / DECAY) - 1.0);
+        interval.clamp(MIN_INTERVAL_DAYS, MAX_INTERVAL_DAYS)
+    }
+
+    fn initial_stability(&self, rating: f32) -> f32 {
+        self.weights[rating as usize - 1].max(0.1)
+    }
+
+    fn initial_difficulty(&self, rating: f32) -> f32 {
+        let w = &self.weights;
+        (w[4] - (w[5] * (rating - 1.0)).exp() + 1.0).clamp(1.0, 10.0)
+    }
+
+    fn next_difficulty(&self, difficulty: f32, rating: f32) -> f32 {
+        let w = &self.weights;
+        let delta = -w[6] * (rating - 3.0);
+        // Linear damping: changes shrink as difficulty approaches 10
+        let damped = difficulty + delta * (10.0 - difficulty) / 9.0;
+        // Mean reversion towards the difficulty of an "Easy" first answer
+        let reverted = w[7] * self.initial_difficulty(4.0) + (1.0 - w[7]) * damped;
+        reverted.clamp(1.0, 10.0)
+    }
+
+    fn recall_stability(&self, difficulty: f32, stability: f32, retrievability: f32, grade: Grade) -> f32 {
+        let w = &self.weights;
+        let hard_penalty = if grade == Grade::Hard { w[15] } else { 1.0 };
+        let easy_bonus = if grade == Grade::Easy { w[16] } else { 1.0 };
+        stability
+            * (w[8].exp()
+                * (11.0 - difficulty)
+                * stability.powf(-w[9])
+                * ((w[10] * (1.0 - retrievability)).exp() - 1.0)
+                * hard_penalty
+                * easy_bonus
+                + 1.0)
+    }
+
+    fn forget_stability(&self, difficulty: f32, stability: f32, retrievability: f32) -> f32 {
+        let w = &self.weights;
+        let forgotten = w[11]
+            * difficulty.powf(-w[12])
+            * ((stability + 1.0).powf(w[13]) - 1.0)
+            * (w[14] * (1.0 - retrievability)).exp();
+        // Forgetting can never make a card more stable
+        forgotten.min(stability)
+    }
+
+    fn short_term_stability(&self, stability: f32, rating: f32) -> f32 {
+        let w = &self.weights;
+        stability * (w[17] * (rating - 3.0 + w[18])).exp()
+    }
+}
+
+impl Scheduler for FsrsScheduler {
+    fn name(&self) -> &'static str {
+        "fsrs"
+    }
+
+    fn next_review(&self, card: &SrsCard, grade: Grade) -> SrsUpdate {
+        let rating = grade.as_i32() as f32;
+
+        let (stability, difficulty) = match (card.stability, card.difficulty) {
+            (Some(stability), Some(difficulty)) => {
+                let elapsed = card.elapsed_days.unwrap_or(0.0).max(0.0);
+                let new_difficulty = self.next_difficulty(difficulty, rating);
+
+                let new_stability = if elapsed < 1.0 {
+                    // Same-day review: the long-term formulas assume at least a day has passed
+                    self.short_term_stability(stability, rating)
+                } else {
+                    let retrievability = self.retrievability(elapsed, stability);
+                    if grade.is_correct() {
+                        self.recall_stability(difficulty, stability, retrievability, grade)
+                    } else {
+                        self.forget_stability(difficulty, stability, retrievability)
+                    }
+                };
+
+                (new_stability.max(0.01), new_difficulty)
+            }
+            // First FSRS review of this card
+            _ => (self.initial_stability(rating), self.initial_difficulty(rating)),
+        };
+
+        let new_interval = self.interval_for(stability);
+
+        SrsUpdate {
+            new_interval_days: new_interval,
+            // Ease is not used by FSRS; keep it so switching back to the ladder is seamless
+            new_ease_factor: card.ease_factor,
+            next_review_date: review_date_after(new_interval),
+            reached_week_for_first_time: !card.has_reached_week && new_interval >= 7.0,
+            new_stability: Some(stability),
+            new_difficulty: Some(difficulty),
+        }
+    }
+}
+
+/// Estimate FSRS memory state for a card scheduled by the ladder
+/// Stability starts at the current interval (ladder intervals roughly target 90% recall)
+/// and difficulty is derived from ease: 2.25 (cap) → 5, 1.3 (floor) → 10
+pub fn seed_memory_state(current_interval_days: f32, ease_factor: f32) -> (f32, f32) {
+    let stability = current_interval_days.max(0.1);
+    let difficulty = (5.0 + (2.25 - ease_factor) * 5.0 / 0.95).clamp(1.0, 10.0);
+    (stability, difficulty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(stability: Option<f32>, difficulty: Option<f32>, elapsed_days: Option<f32>) -> SrsCard {
+        SrsCard {
+            character_id: 1,
+            current_interval_days: stability.unwrap_or(0.0417),
+            previous_interval_days: 0.0417,
+            ease_factor: 2.25,
+            stability,
+            difficulty,
+            elapsed_days,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_interval_equals_stability_at_90_percent() {
+        let scheduler = FsrsScheduler::new(0.9);
+        let interval = scheduler.interval_for(10.0);
+        assert!((interval - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_higher_retention_means_shorter_intervals() {
+        let relaxed = FsrsScheduler::new(0.8);
+        let strict = FsrsScheduler::new(0.95);
+        assert!(strict.interval_for(10.0) < relaxed.interval_for(10.0));
+    }
+
+    #[test]
+    fn test_first_review_uses_initial_state() {
+        let scheduler = FsrsScheduler::new(0.9);
+        let update = scheduler.next_review(&card(None, None, None), Grade::Good);
+
+        assert_eq!(update.new_stability, Some(DEFAULT_WEIGHTS[2]));
+        let difficulty = update.new_difficulty.unwrap();
+        assert!((1.0..=10.0).contains(&difficulty));
+    }
+
+    #[test]
+    fn test_correct_answer_on_time_grows_stability() {
+        let scheduler = FsrsScheduler::new(0.9);
+        let update = scheduler.next_review(&card(Some(5.0), Some(5.0), Some(5.0)), Grade::Good);
+
+        assert!(update.new_stability.unwrap() > 5.0);
+        assert!(update.new_interval_days > 5.0);
+        assert_eq!(update.new_ease_factor, 2.25); // Ease untouched by FSRS
+    }
+
+    #[test]
+    fn test_lapse_shrinks_stability_and_raises_difficulty() {
+        let scheduler = FsrsScheduler::new(0.9);
+        let update = scheduler.next_review(&card(Some(20.0), Some(5.0), Some(20.0)), Grade::Again);
+
+        assert!(update.new_stability.unwrap() < 20.0);
+        assert!(update.new_difficulty.unwrap() > 5.0);
+        assert!(update.new_interval_days >= MIN_INTERVAL_DAYS);
+    }
+
+    #[test]
+    fn test_reaching_one_week() {
+        let scheduler = FsrsScheduler::new(0.9);
+        let update = scheduler.next_review(&card(Some(4.0), Some(3.0), Some(4.0)), Grade::Good);
+
+        assert!(update.new_interval_days >= 7.0);
+        assert!(update.reached_week_for_first_time);
+    }
+
+    #[test]
+    fn test_grades_order_stability() {
+        let scheduler = FsrsScheduler::new(0.9);
+        let review = card(Some(10.0), Some(5.0), Some(10.0));
+        let stability = |grade| scheduler.next_review(&review, grade).new_stability.unwrap();
+
+        assert!(stability(Grade::Again) < stability(Grade::Hard));
+        assert!(stability(Grade::Hard) < stability(Grade::Good));
+        assert!(stability(Grade::Good) < stability(Grade::Easy));
+    }
+
+    #[test]
+    fn test_seed_memory_state_from_ladder() {
+        assert_eq!(seed_memory_state(7.0, 2.25), (7.0, 5.0));
+        assert_eq!(seed_memory_state(0.0417, 1.3), (0.1, 10.0));
+    }
+}
@@ -0,0 +1,485 @@
+mod fsrs;
+
+pub use fsrs::{seed_memory_state, FsrsScheduler};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How well the user recalled a card (same 1-4 scale Anki uses)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Grade {
+    Again = 1,
+    Hard = 2,
+    Good = 3,
+    Easy = 4,
+}
+
+impl Grade {
+    /// Pass/fail answers map to Good/Again
+    pub fn from_correct(correct: bool) -> Self {
+        if correct { Grade::Good } else { Grade::Again }
+    }
+
+    pub fn as_i32(self) -> i32 {
+        self as i32
+    }
+
+    /// Everything except Again counts as a successful recall
+    pub fn is_correct(self) -> bool {
+        self != Grade::Again
+    }
+}
+
+impl From<bool> for Grade {
+    fn from(correct: bool) -> Self {
+        Grade::from_correct(correct)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SrsCard {
+    pub character_id: i32,
+    pub current_interval_days: f32,
+    pub previous_interval_days: f32,
+    pub ease_factor: f32,
+    pub times_correct: i32,
+    pub times_incorrect: i32,
+    pub has_reached_week: bool,
+    // FSRS memory state (None until the card has been scheduled or seeded by FSRS)
+    pub stability: Option<f32>,
+    pub difficulty: Option<f32>,
+    // Days since the card was last answered (None if never reviewed)
+    pub elapsed_days: Option<f32>,
+}
+
+#[derive(Debug)]
+pub struct SrsUpdate {
+    pub new_interval_days: f32,
+    pub new_ease_factor: f32,
+    pub next_review_date: DateTime<Utc>,
+    pub reached_week_for_first_time: bool,
+    pub new_stability: Option<f32>,
+    pub new_difficulty: Option<f32>,
+}
+
+/// A spaced repetition algorithm that turns an answer into the card's next schedule
+pub trait Scheduler {
+    /// Name stored in the `srs_scheduler` app setting
+    fn name(&self) -> &'static str;
+
+    fn next_review(&self, card: &SrsCard, grade: Grade) -> SrsUpdate;
+}
+
+/// The original fixed ladder: 1 hour → 12 hours → 1 day → 3 days → 7 days → ease growth
+pub struct LadderScheduler;
+
+impl Scheduler for LadderScheduler {
+    fn name(&self) -> &'static str {
+        "ladder"
+    }
+
+    fn next_review(&self, card: &SrsCard, grade: Grade) -> SrsUpdate {
+        calculate_next_review(card, grade)
+    }
+}
+
+pub const DEFAULT_SCHEDULER: &str = "ladder";
+pub const DEFAULT_DESIRED_RETENTION: f32 = 0.9;
+
+/// Build the scheduler selected in app_settings
+/// Unknown names fall back to the ladder so a bad setting never blocks reviews
+pub fn scheduler_from_settings(name: &str, desired_retention: f32) -> Box<dyn Scheduler> {
+    match name {
+        "fsrs" => Box::new(FsrsScheduler::new(desired_retention)),
+        _ => Box::new(LadderScheduler),
+    }
+}
+
+pub fn is_known_scheduler(name: &str) -> bool {
+    matches!(name, "ladder" | "fsrs")
+}
+
+/// How a card becomes mastered: total correct answers, correct answers in a row, or an interval reached
+pub const MASTERY_RULES: [&str; 3] = ["total_correct", "consecutive_correct", "min_interval"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MasteryPolicy {
+    pub rule: String,
+    // Answers for the counting rules, days for min_interval
+    pub threshold: f32,
+    // Mastered cards are reviewed at least this many days apart
+    pub maintenance_interval_days: f32,
+}
+
+impl Default for MasteryPolicy {
+    fn default() -> Self {
+        MasteryPolicy {
+            rule: "total_correct".to_string(),
+            threshold: 9.0,
+            maintenance_interval_days: 180.0,
+        }
+    }
+}
+
+impl MasteryPolicy {
+    /// Whether a card that was just answered correctly is now mastered
+    pub fn is_reached(&self, times_correct: i32, correct_streak: i32, new_interval_days: f32) -> bool {
+        match self.rule.as_str() {
+            "consecutive_correct" => correct_streak as f32 >= self.threshold,
+            "min_interval" => new_interval_days >= self.threshold,
+            _ => times_correct as f32 >= self.threshold,
+        }
+    }
+}
+
+pub fn is_known_mastery_rule(rule: &str) -> bool {
+    MASTERY_RULES.contains(&rule)
+}
+
+/// Accepts a `Grade` or a plain pass/fail `bool` (mapped to Good/Again)
+pub fn calculate_next_review(
+    card: &SrsCard,
+    grade: impl Into<Grade>,
+) -> SrsUpdate {
+    let grade = grade.into();
+    let (new_interval, new_ease) = match grade {
+        Grade::Again => calculate_interval_incorrect(card),
+        Grade::Hard => calculate_interval_hard(card),
+        Grade::Good => calculate_interval_correct(card),
+        Grade::Easy => calculate_interval_easy(card),
+    };
+
+    let next_review_date = review_date_after(new_interval);
+
+    // Check if reaching 1 week for first time
+    let reached_week_for_first_time =
+        !card.has_reached_week && new_interval >= 7.0;
+
+    SrsUpdate {
+        new_interval_days: new_interval,
+        new_ease_factor: new_ease,
+        next_review_date,
+        reached_week_for_first_time,
+        new_stability: None,
+        new_difficulty: None,
+    }
+}
+
+/// Next review date for an interval given in days
+fn review_date_after(interval_days: f32) -> DateTime<Utc> {
+    // Convert interval from days to minutes for precise calculation
+    // This ensures sub-day intervals (like 1 hour = 0.0417 days) work correctly
+    let interval_minutes = (interval_days * 24.0 * 60.0) as i64;
+    Utc::now() + Duration::minutes(interval_minutes)
+}
+
+fn calculate_interval_correct(card: &SrsCard) -> (f32, f32) {
+    let new_interval = next_ladder_step(card.current_interval_days, card.ease_factor);
+
+    // Cap ease factor at 2.25 to prevent runaway intervals
+    let new_ease = card.ease_factor.min(2.25);
+
+    (new_interval, new_ease)
+}
+
+/// Interval a new card reaches after `count` correct answers in a row (used to seed imported cards)
+pub fn interval_after_correct_answers(count: u32) -> f32 {
+    (0..count).fold(0.0417, |interval, _| next_ladder_step(interval, 2.25))
+}
+
+fn next_ladder_step(current: f32, ease: f32) -> f32 {
+    // Progression: 1 hour → 12 hours → 1 day → 3 days → 7 days → exponential
+    if current < 0.0417 {
+        // Less than 1 hour → 1 hour
+        0.0417 // 1 hour = 1/24 days
+    } else if current <= 0.0417 {
+        // At 1 hour → 12 hours
+        0.5 // 12 hours = 0.5 days
+    } else if current < 0.5 {
+        // Less than 12 hours → 12 hours
+        0.5 // 12 hours = 0.5 days
+    } else if current <= 0.5 {
+        // At 12 hours → 1 day
+        1.0
+    } else if current < 1.0 {
+        // Less than 1 day → 1 day
+        1.0
+    } else if current <= 1.0 {
+        // At 1 day → 3 days
+        3.0
+    } else if current < 3.0 {
+        // Less than 3 days → 3 days
+        3.0
+    } else if current <= 3.0 {
+        // At 3 days → 7 days
+        7.0
+    } else if current < 7.0 {
+        // Less than 7 days → 7 days
+        7.0
+    } else {
+        // After 7 days, use exponential growth
+        current * ease
+    }
+}
+
+fn calculate_interval_hard(card: &SrsCard) -> (f32, f32) {
+    // Shaky recall: repeat the current step on the ladder, grow slowly after 7 days
+    let current = card.current_interval_days.max(0.0417); // 1 hour minimum
+    let new_interval = if current < 7.0 { current } else { current * 1.2 };
+    let new_ease = (card.ease_factor - 0.15).max(1.3);
+
+    (new_interval, new_ease)
+}
+
+fn calculate_interval_easy(card: &SrsCard) -> (f32, f32) {
+    // Instant recall: skip a step on the ladder, add a bonus after 7 days
+    let current = card.current_interval_days;
+    let new_interval = if current < 7.0 {
+        next_ladder_step(next_ladder_step(current, card.ease_factor), card.ease_factor)
+    } else {
+        current * card.ease_factor * 1.3
+    };
+    let new_ease = (card.ease_factor + 0.15).min(2.25);
+
+    (new_interval, new_ease)
+}
+
+fn calculate_interval_incorrect(card: &SrsCard) -> (f32, f32) {
+    // Return to previous interval, but minimum is 1 hour (not 1 day)
+    let new_interval = card.previous_interval_days.max(0.0417); // 1 hour minimum
+    let new_ease = (card.ease_factor - 0.2).max(1.3);
+
+    (new_interval, new_ease)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_correct_answer() {
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 0.0417, // Start at 1 hour
+            previous_interval_days: 0.0417,
+            ease_factor: 2.25,
+            times_correct: 0,
+            times_incorrect: 0,
+            has_reached_week: false,
+            ..Default::default()
+        };
+
+        let update = calculate_next_review(&card, true);
+        assert_eq!(update.new_interval_days, 0.5); // 1 hour (at 0.0417) → 12 hours
+        assert_eq!(update.new_ease_factor, 2.25); // Capped at 2.25
+        assert!(!update.reached_week_for_first_time);
+    }
+
+    #[test]
+    fn test_reaching_one_week() {
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 3.0,
+            previous_interval_days: 1.0,
+            ease_factor: 2.25,
+            times_correct: 1,
+            times_incorrect: 0,
+            has_reached_week: false,
+            ..Default::default()
+        };
+
+        let update = calculate_next_review(&card, true);
+        assert_eq!(update.new_interval_days, 7.0);
+        assert!(update.reached_week_for_first_time);
+    }
+
+    #[test]
+    fn test_incorrect_answer_backs_to_previous() {
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 7.0,
+            previous_interval_days: 3.0,
+            ease_factor: 2.25,
+            times_correct: 2,
+            times_incorrect: 0,
+            has_reached_week: true,
+            ..Default::default()
+        };
+
+        let update = calculate_next_review(&card, false);
+        assert_eq!(update.new_interval_days, 3.0); // Back to previous
+        assert_eq!(update.new_ease_factor, 2.05); // Decreased by 0.2 from 2.25
+        assert!(!update.reached_week_for_first_time); // Already reached
+    }
+
+    #[test]
+    fn test_incorrect_answer_minimum_interval() {
+        // Test that incorrect answers have a minimum of 1 hour, not 1 day
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 0.5, // 12 hours
+            previous_interval_days: 0.0417, // 1 hour
+            ease_factor: 2.25,
+            times_correct: 1,
+            times_incorrect: 0,
+            has_reached_week: false,
+            ..Default::default()
+        };
+
+        let update = calculate_next_review(&card, false);
+        assert_eq!(update.new_interval_days, 0.0417); // Back to 1 hour
+        assert_eq!(update.new_ease_factor, 2.05); // Decreased by 0.2 from 2.25
+    }
+
+    #[test]
+    fn test_ease_factor_floor() {
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 7.0,
+            previous_interval_days: 3.0,
+            ease_factor: 1.4,
+            times_correct: 0,
+            times_incorrect: 5,
+            has_reached_week: true,
+            ..Default::default()
+        };
+
+        let update = calculate_next_review(&card, false);
+        assert_eq!(update.new_ease_factor, 1.3); // Floor at 1.3
+    }
+
+    #[test]
+    fn test_progression_sequence() {
+        let mut card = SrsCard {
+            character_id: 1,
+            current_interval_days: 0.0417, // 1 hour
+            previous_interval_days: 0.0417,
+            ease_factor: 2.25,
+            times_correct: 0,
+            times_incorrect: 0,
+            has_reached_week: false,
+            ..Default::default()
+        };
+
+        // 1 hour -> 12 hours
+        let update = calculate_next_review(&card, true);
+        assert_eq!(update.new_interval_days, 0.5);
+
+        card.previous_interval_days = card.current_interval_days;
+        card.current_interval_days = update.new_interval_days;
+        card.ease_factor = update.new_ease_factor;
+
+        // 12 hours -> 1 day
+        let update = calculate_next_review(&card, true);
+        assert_eq!(update.new_interval_days, 1.0);
+
+        card.previous_interval_days = card.current_interval_days;
+        card.current_interval_days = update.new_interval_days;
+        card.ease_factor = update.new_ease_factor;
+
+        // 1 day -> 3 days
+        let update = calculate_next_review(&card, true);
+        assert_eq!(update.new_interval_days, 3.0);
+
+        card.previous_interval_days = card.current_interval_days;
+        card.current_interval_days = update.new_interval_days;
+        card.ease_factor = update.new_ease_factor;
+
+        // 3 days -> 7 days (5th correct - reaches week!)
+        let update = calculate_next_review(&card, true);
+        assert_eq!(update.new_interval_days, 7.0);
+        assert!(update.reached_week_for_first_time);
+
+        card.previous_interval_days = card.current_interval_days;
+        card.current_interval_days = update.new_interval_days;
+        card.has_reached_week = true;
+        card.ease_factor = update.new_ease_factor;
+
+        // 7 days -> 15.75 days (7 * 2.25)
+        let update = calculate_next_review(&card, true);
+        assert_eq!(update.new_interval_days, 15.75);
+    }
+
+    #[test]
+    fn test_boolean_answers_map_to_again_and_good() {
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 3.0,
+            previous_interval_days: 1.0,
+            ease_factor: 2.0,
+            ..Default::default()
+        };
+
+        let good = calculate_next_review(&card, Grade::Good);
+        let correct = calculate_next_review(&card, true);
+        assert_eq!(good.new_interval_days, correct.new_interval_days);
+        assert_eq!(good.new_ease_factor, correct.new_ease_factor);
+
+        let again = calculate_next_review(&card, Grade::Again);
+        let incorrect = calculate_next_review(&card, false);
+        assert_eq!(again.new_interval_days, incorrect.new_interval_days);
+        assert_eq!(again.new_ease_factor, incorrect.new_ease_factor);
+    }
+
+    #[test]
+    fn test_grades_are_ordered_on_the_ladder() {
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 1.0,
+            previous_interval_days: 0.5,
+            ease_factor: 2.0,
+            ..Default::default()
+        };
+
+        let again = calculate_next_review(&card, Grade::Again);
+        let hard = calculate_next_review(&card, Grade::Hard);
+        let good = calculate_next_review(&card, Grade::Good);
+        let easy = calculate_next_review(&card, Grade::Easy);
+
+        assert_eq!(again.new_interval_days, 0.5); // Back to previous
+        assert_eq!(hard.new_interval_days, 1.0); // Repeat current step
+        assert_eq!(good.new_interval_days, 3.0); // Next step
+        assert_eq!(easy.new_interval_days, 7.0); // Skip a step
+
+        assert_eq!(again.new_ease_factor, 1.8);
+        assert_eq!(hard.new_ease_factor, 1.85);
+        assert_eq!(good.new_ease_factor, 2.0);
+        assert_eq!(easy.new_ease_factor, 2.15);
+    }
+
+    #[test]
+    fn test_hard_and_easy_after_one_week() {
+        let card = SrsCard {
+            character_id: 1,
+            current_interval_days: 10.0,
+            previous_interval_days: 7.0,
+            ease_factor: 2.0,
+            has_reached_week: true,
+            ..Default::default()
+        };
+
+        let hard = calculate_next_review(&card, Grade::Hard);
+        let easy = calculate_next_review(&card, Grade::Easy);
+        assert_eq!(hard.new_interval_days, 12.0); // 10 * 1.2
+        assert_eq!(easy.new_interval_days, 26.0); // 10 * 2.0 * 1.3
+    }
+
+    #[test]
+    fn test_interval_after_correct_answers() {
+        assert_eq!(interval_after_correct_answers(0), 0.0417);
+        assert_eq!(interval_after_correct_answers(1), 0.5);
+        assert_eq!(interval_after_correct_answers(4), 7.0);
+        assert!((interval_after_correct_answers(5) - 15.75).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_grade_conversions() {
+        assert_eq!(Grade::from_correct(true), Grade::Good);
+        assert_eq!(Grade::from_correct(false), Grade::Again);
+        assert_eq!(Grade::from(true), Grade::Good);
+        assert_eq!(Grade::Easy.as_i32(), 4);
+        assert!(Grade::Hard.is_correct());
+        assert!(!Grade::Again.is_correct());
+    }
+}
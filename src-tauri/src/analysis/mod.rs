@@ -0,0 +1,360 @@
+// Reading analysis for pasted Chinese text
+// Each run of hanzi is segmented into dictionary words (fewest words, then the most frequent),
+// and every word gets the active track's study status, so a passage's known coverage and its
+// unseen words can be shown before reading it.
+
+use rusqlite::{Connection, OptionalExtension, Result};
+use std::collections::HashMap;
+
+use crate::database::ACTIVE_TRACK;
+use data_processing::search::is_hanzi;
+
+/// Longest dictionary word tried when segmenting (CEDICT idioms and set phrases fit well within it)
+const MAX_WORD_CHARS: usize = 12;
+
+/// Cost of a hanzi with no dictionary entry, worse than any ranked entry
+const UNKNOWN_RANK: i64 = 10_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenStatus {
+    Mastered,
+    /// Introduced and reviewed, not yet mastered
+    Learning,
+    /// Introduced but never reviewed
+    Introduced,
+    /// In the dictionary, never introduced on the active track
+    Unseen,
+    /// Hanzi with no dictionary entry
+    NotInDictionary,
+    /// Punctuation, Latin letters, digits and whitespace
+    Other,
+}
+
+impl TokenStatus {
+    fn is_known(self) -> bool {
+        matches!(self, TokenStatus::Mastered | TokenStatus::Learning | TokenStatus::Introduced)
+    }
+
+    fn is_chinese(self) -> bool {
+        self != TokenStatus::Other
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TextToken {
+    pub text: String,
+    pub status: TokenStatus,
+    pub character_id: Option<i32>,
+    // Reading for the active track (tone-marked pinyin, or Jyutping on the Cantonese track)
+    pub reading: Option<String>,
+    pub definition: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UnseenWord {
+    pub character_id: i32,
+    pub text: String,
+    pub occurrences: usize,
+    pub frequency_rank: i32,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct TextAnalysis {
+    pub tokens: Vec<TextToken>,
+    // Chinese words in the text, counting repeats, and distinct ones
+    pub total_words: usize,
+    pub unique_words: usize,
+    // Percent of word occurrences that are introduced, learning or mastered
+    pub known_coverage: f64,
+    // Percent of word occurrences that are mastered
+    pub mastered_coverage: f64,
+    // Percent of hanzi (counting repeats) that are known as single characters
+    pub character_coverage: f64,
+    // Percent of distinct words that are known
+    pub unique_known_coverage: f64,
+    // Dictionary words the user hasn't started, most repeated first: candidates to pull forward
+    pub unseen_words: Vec<UnseenWord>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    character_id: i32,
+    frequency_rank: i32,
+    reading: String,
+    definition: String,
+    status: TokenStatus,
+}
+
+/// Segment `text` and report the study status of every word and the overall coverage
+pub fn analyze_text(conn: &Connection, text: &str) -> Result<TextAnalysis> {
+    let mut dictionary = Dictionary { conn, cache: HashMap::new() };
+    let mut tokens = Vec::new();
+
+    for (run, is_chinese) in runs(text) {
+        if !is_chinese {
+            tokens.push(TextToken { text: run, status: TokenStatus::Other, character_id: None, reading: None, definition: None });
+            continue;
+        }
+        for word in dictionary.segment(&run)? {
+            let token = match dictionary.lookup(&word)? {
+                Some(entry) => TextToken {
+                    text: word,
+                    status: entry.status,
+                    character_id: Some(entry.character_id),
+                    reading: Some(entry.reading),
+                    definition: Some(entry.definition),
+                },
+                None => TextToken { text: word, status: TokenStatus::NotInDictionary, character_id: None, reading: None, definition: None },
+            };
+            tokens.push(token);
+        }
+    }
+
+    let words: Vec<&TextToken> = tokens.iter().filter(|t| t.status.is_chinese()).collect();
+    let known = words.iter().filter(|t| t.status.is_known()).count();
+    let mastered = words.iter().filter(|t| t.status == TokenStatus::Mastered).count();
+
+    // Simplified and traditional spellings of an entry are the same word
+    let mut distinct: HashMap<String, &TextToken> = HashMap::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for word in &words {
+        let key = match word.character_id {
+            Some(id) => id.to_string(),
+            None => word.text.clone(),
+        };
+        distinct.entry(key.clone()).or_insert(word);
+        *occurrences.entry(key).or_insert(0) += 1;
+    }
+    let unique_known = distinct.values().filter(|t| t.status.is_known()).count();
+
+    let mut hanzi = 0;
+    let mut known_hanzi = 0;
+    for c in words.iter().flat_map(|t| t.text.chars()) {
+        hanzi += 1;
+        if dictionary.lookup(&c.to_string())?.is_some_and(|e| e.status.is_known()) {
+            known_hanzi += 1;
+        }
+    }
+
+    let mut unseen_words = Vec::new();
+    for (key, token) in &distinct {
+        if token.status == TokenStatus::Unseen {
+            let entry = dictionary.lookup(&token.text)?.unwrap();
+            unseen_words.push(UnseenWord {
+                character_id: entry.character_id,
+                text: token.text.clone(),
+                occurrences: occurrences[key],
+                frequency_rank: entry.frequency_rank,
+            });
+        }
+    }
+    unseen_words.sort_by(|a, b| b.occurrences.cmp(&a.occurrences).then(a.frequency_rank.cmp(&b.frequency_rank)));
+
+    println!("[DB] analyze_text: {} words, {}/{} known", words.len(), known, words.len());
+
+    Ok(TextAnalysis {
+        total_words: words.len(),
+        unique_words: distinct.len(),
+        known_coverage: percent(known, words.len()),
+        mastered_coverage: percent(mastered, words.len()),
+        character_coverage: percent(known_hanzi, hanzi),
+        unique_known_coverage: percent(unique_known, distinct.len()),
+        unseen_words,
+        tokens,
+    })
+}
+
+/// Dictionary lookups for one analysis, cached by surface form
+struct Dictionary<'a> {
+    conn: &'a Connection,
+    cache: HashMap<String, Option<Entry>>,
+}
+
+impl Dictionary<'_> {
+    /// The entry written this way (simplified preferred over traditional, then the most frequent)
+    fn lookup(&mut self, word: &str) -> Result<Option<Entry>> {
+        if let Some(entry) = self.cache.get(word) {
+            return Ok(entry.clone());
+        }
+
+        let entry = self.conn.query_row(
+            &format!(
+                "SELECT c.id, c.frequency_rank,
+                        CASE WHEN {0} = 'cantonese' THEN COALESCE(c.cantonese_jyutping, c.pinyin_marked, c.mandarin_pinyin)
+                             ELSE COALESCE(c.pinyin_marked, c.mandarin_pinyin) END,
+                        c.definition, p.introduced, p.is_mastered, p.times_reviewed
+                 FROM characters c
+                 LEFT JOIN character_progress p ON p.character_id = c.id
+                 WHERE c.simplified = ?1 OR c.traditional = ?1
+                 ORDER BY c.simplified = ?1 DESC, c.frequency_rank ASC
+                 LIMIT 1",
+                ACTIVE_TRACK
+            ),
+            [word],
+            |row| {
+                let introduced: Option<bool> = row.get(4)?;
+                let mastered: Option<bool> = row.get(5)?;
+                let reviewed: Option<i32> = row.get(6)?;
+                let status = match (introduced, mastered, reviewed) {
+                    (_, Some(true), _) => TokenStatus::Mastered,
+                    (Some(true), _, Some(reviewed)) if reviewed > 0 => TokenStatus::Learning,
+                    (Some(true), _, _) => TokenStatus::Introduced,
+                    _ => TokenStatus::Unseen,
+                };
+                Ok(Entry {
+                    character_id: row.get(0)?,
+                    frequency_rank: row.get(1)?,
+                    reading: row.get(2)?,
+                    definition: row.get(3)?,
+                    status,
+                })
+            }
+        ).optional()?;
+
+        self.cache.insert(word.to_string(), entry.clone());
+        Ok(entry)
+    }
+
+    /// Split a run of hanzi into the fewest dictionary words, breaking ties by frequency.
+    /// Hanzi without an entry become one-character words.
+    fn segment(&mut self, run: &str) -> Result<Vec<String>> {
+        let chars: Vec<char> = run.chars().collect();
+        let n = chars.len();
+        // best[i] = (words, summed frequency rank, start of the last word) for chars[..i]
+        let mut best: Vec<Option<(usize, i64, usize)>> = vec![None; n + 1];
+        best[0] = Some((0, 0, 0));
+
+        for start in 0..n {
+            let Some((words, rank, _)) = best[start] else { continue };
+            for end in start + 1..=n.min(start + MAX_WORD_CHARS) {
+                let word: String = chars[start..end].iter().collect();
+                let cost = match self.lookup(&word)? {
+                    Some(entry) => entry.frequency_rank as i64,
+                    None if end == start + 1 => UNKNOWN_RANK,
+                    None => continue,
+                };
+                let candidate = (words + 1, rank + cost, start);
+                let better = match best[end] {
+                    Some((best_words, best_rank, _)) => (candidate.0, candidate.1) < (best_words, best_rank),
+                    None => true,
+                };
+                if better {
+                    best[end] = Some(candidate);
+                }
+            }
+        }
+
+        let mut words = Vec::new();
+        let mut end = n;
+        while end > 0 {
+            let (_, _, start) = best[end].unwrap();
+            words.push(chars[start..end].iter().collect());
+            end = start;
+        }
+        words.reverse();
+        Ok(words)
+    }
+}
+
+/// Split text into runs of hanzi and runs of everything else
+fn runs(text: &str) -> Vec<(String, bool)> {
+    let mut runs: Vec<(String, bool)> = Vec::new();
+    for c in text.chars() {
+        let chinese = is_hanzi(c);
+        match runs.last_mut() {
+            Some((run, is_chinese)) if *is_chinese == chinese => run.push(c),
+            _ => runs.push((c.to_string(), chinese)),
+        }
+    }
+    runs
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_db() -> Connection {
+        let conn = crate::database::test_user_db(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word)
+             VALUES ('我', '我', '我', 'wo3', 'I; me', 5, 0),
+                    ('们', '们', '們', 'men5', 'plural marker', 40, 0),
+                    ('我们', '我们', '我們', 'wo3 men5', 'we; us', 30, 1),
+                    ('学', '学', '學', 'xue2', 'to learn', 200, 0),
+                    ('习', '习', '習', 'xi2', 'to practice', 900, 0),
+                    ('学习', '学习', '學習', 'xue2 xi2', 'to study', 150, 1),
+                    ('中', '中', '中', 'zhong1', 'middle', 20, 0),
+                    ('文', '文', '文', 'wen2', 'language', 300, 0),
+                    ('中文', '中文', '中文', 'zhong1 wen2', 'Chinese language', 500, 1),
+                    ('研究', '研究', '研究', 'yan2 jiu1', 'research', 700, 1),
+                    ('研究生', '研究生', '研究生', 'yan2 jiu1 sheng1', 'graduate student', 5000, 1),
+                    ('生命', '生命', '生命', 'sheng1 ming4', 'life', 800, 1),
+                    ('命', '命', '命', 'ming4', 'fate', 1000, 0);"
+        );
+        conn.execute_batch(
+            "INSERT INTO user_progress (character_id, next_review_date, introduced, times_reviewed, is_mastered)
+             SELECT id, datetime('now'), 1, 9, 1 FROM characters WHERE character IN ('我', '我们');
+             INSERT INTO user_progress (character_id, next_review_date, introduced, times_reviewed)
+             SELECT id, datetime('now'), 1, 2 FROM characters WHERE character = '学习';
+             INSERT INTO user_progress (character_id, next_review_date, introduced, times_reviewed)
+             SELECT id, datetime('now'), 1, 0 FROM characters WHERE character = '中';"
+        ).unwrap();
+        conn
+    }
+
+    fn words(analysis: &TextAnalysis) -> Vec<(&str, TokenStatus)> {
+        analysis.tokens.iter().map(|t| (t.text.as_str(), t.status)).collect()
+    }
+
+    #[test]
+    fn test_segments_and_reports_status() {
+        let conn = user_db();
+        let analysis = analyze_text(&conn, "我们学习中文。我們學習！").unwrap();
+        assert_eq!(words(&analysis), vec![
+            ("我们", TokenStatus::Mastered),
+            ("学习", TokenStatus::Learning),
+            ("中文", TokenStatus::Unseen),
+            ("。", TokenStatus::Other),
+            ("我們", TokenStatus::Mastered),
+            ("學習", TokenStatus::Learning),
+            ("！", TokenStatus::Other),
+        ]);
+        assert_eq!(analysis.tokens[2].reading.as_deref(), Some("zhōng wén"));
+        assert_eq!(analysis.total_words, 5);
+        assert_eq!(analysis.unique_words, 3);
+        assert_eq!(analysis.known_coverage, 80.0);
+        assert_eq!(analysis.mastered_coverage, 40.0);
+        assert_eq!(analysis.unseen_words.len(), 1);
+        assert_eq!(analysis.unseen_words[0].text, "中文");
+    }
+
+    #[test]
+    fn test_prefers_fewest_then_most_frequent_words() {
+        let conn = user_db();
+        // 研究生|命 and 研究|生命 are both two words; 研究|生命 is more frequent
+        let analysis = analyze_text(&conn, "研究生命").unwrap();
+        assert_eq!(words(&analysis), vec![("研究", TokenStatus::Unseen), ("生命", TokenStatus::Unseen)]);
+    }
+
+    #[test]
+    fn test_character_coverage_and_unknown_hanzi() {
+        let conn = user_db();
+        let analysis = analyze_text(&conn, "我们中文龘").unwrap();
+        assert_eq!(analysis.tokens[2].status, TokenStatus::NotInDictionary);
+        // 我 (mastered) and 中 (introduced) of 我们中文龘
+        assert_eq!(analysis.character_coverage, 40.0);
+        assert_eq!(analysis.tokens[1].status, TokenStatus::Unseen);
+
+        let empty = analyze_text(&conn, "Hello, world").unwrap();
+        assert_eq!(empty.total_words, 0);
+        assert_eq!(empty.known_coverage, 0.0);
+    }
+}
@@ -1,6 +1,10 @@
-use crate::database::{DbConnection, Character, DueCard};
+use crate::database::{DbConnection, Character, CharacterDetails, DailyLimits, DueCard, PacingPolicy, PullForwardReport, QueuedEntry, ReviewLogEntry, ReviewQueue, SearchResult, Sense, SrsSettings, StudyClock, StudyDaySettings, TrackOrderReport, UndoneAnswer, Leech, LeechSettings};
 use tauri::State;
-use chrono::{Utc, Duration};
+use data_processing::srs::{Grade, MasteryPolicy};
+use data_processing::simulation::{Forecast, SimulationParams};
+use crate::grading::{Expected, GradedAnswer};
+use crate::analysis::TextAnalysis;
+use chrono::{Utc, Duration, NaiveDate};
 
 #[tauri::command]
 pub fn get_character(db: State<DbConnection>, id: i32) -> Result<Character, String> {
@@ -9,6 +13,14 @@ pub fn get_character(db: State<DbConnection>, id: i32) -> Result<Character, Stri
         .map_err(|e| e.to_string())
 }
 
+/// Stroke order, radical, decomposition and etymology for each character of an entry
+#[tauri::command]
+pub fn get_character_details(db: State<DbConnection>, id: i32) -> Result<Vec<CharacterDetails>, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_character_details(&conn, id)
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_top_characters(db: State<DbConnection>, limit: usize) -> Result<Vec<Character>, String> {
     let conn = db.0.lock().unwrap();
@@ -30,10 +42,60 @@ pub fn test_database_connection(db: State<DbConnection>) -> Result<String, Strin
 
 // === SRS Commands ===
 
+/// Due cards for a review session, capped by the daily review limit
+#[tauri::command]
+pub fn get_due_cards_for_review(db: State<DbConnection>) -> Result<ReviewQueue, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_review_queue(&conn)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_daily_limits(db: State<DbConnection>) -> Result<DailyLimits, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_daily_limits(&conn)
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
-pub fn get_due_cards_for_review(db: State<DbConnection>) -> Result<Vec<DueCard>, String> {
+pub fn set_daily_limits(
+    db: State<DbConnection>,
+    review_limit: usize,
+    new_card_limit: usize,
+) -> Result<(), String> {
+    if !(1..=9999).contains(&review_limit) {
+        return Err(format!("Daily review limit must be between 1 and 9999, got {}", review_limit));
+    }
+    if new_card_limit > 500 {
+        return Err(format!("Daily new card limit must be at most 500, got {}", new_card_limit));
+    }
+
     let conn = db.0.lock().unwrap();
-    crate::database::get_due_cards(&conn)
+    crate::database::set_daily_limits(&conn, &DailyLimits { review_limit, new_card_limit })
+        .map_err(|e| e.to_string())
+}
+
+/// Timezone and start hour of the study days used by streaks, the calendar and daily limits
+#[tauri::command]
+pub fn get_study_day_settings(db: State<DbConnection>) -> Result<StudyDaySettings, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_study_day_settings(&conn)
+        .map_err(|e| e.to_string())
+}
+
+/// `timezone` is an IANA name such as "Europe/Berlin"; empty follows the system timezone
+#[tauri::command]
+pub fn set_study_day_settings(db: State<DbConnection>, timezone: String, day_start_hour: u32) -> Result<(), String> {
+    let timezone = timezone.trim().to_string();
+    if !crate::database::is_valid_timezone(&timezone) {
+        return Err(format!("Unknown timezone '{}'", timezone));
+    }
+    if day_start_hour > 23 {
+        return Err(format!("Day start hour must be between 0 and 23, got {}", day_start_hour));
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::set_study_day_settings(&conn, &StudyDaySettings { timezone, day_start_hour })
         .map_err(|e| e.to_string())
 }
 
@@ -41,19 +103,384 @@ pub fn get_due_cards_for_review(db: State<DbConnection>) -> Result<Vec<DueCard>,
 pub fn submit_srs_answer(
     db: State<DbConnection>,
     character_id: i32,
-    correct: bool,
+    correct: Option<bool>,
+    grade: Option<Grade>,
+    direction: Option<String>,
+    response_time_ms: Option<i64>,
+    user_answer: Option<String>,
 ) -> Result<bool, String> {
-    println!("[RUST] submit_srs_answer called: char_id={}, correct={}", character_id, correct);
+    // A grade takes precedence; plain pass/fail answers map to Good/Again
+    let grade = match (grade, correct) {
+        (Some(grade), _) => grade,
+        (None, Some(correct)) => Grade::from_correct(correct),
+        (None, None) => return Err("Either 'grade' or 'correct' must be provided".to_string()),
+    };
+
+    println!("[RUST] submit_srs_answer called: char_id={}, grade={:?}", character_id, grade);
     let conn = db.0.lock().unwrap();
-    let result = crate::database::record_srs_answer(&conn, character_id, correct)
-        .map_err(|e| {
-            eprintln!("[RUST] ERROR in record_srs_answer: {}", e);
-            e.to_string()
-        });
+    let result = crate::database::record_srs_answer(
+        &conn,
+        character_id,
+        grade,
+        direction.as_deref(),
+        response_time_ms,
+    )
+    .map_err(|e| {
+        eprintln!("[RUST] ERROR in record_srs_answer: {}", e);
+        e.to_string()
+    });
     println!("[RUST] submit_srs_answer result: {:?}", result);
+
+    // The typed answer goes to practice_history, where leeches find what they're confused with
+    if let Some(answer) = user_answer.filter(|answer| !answer.trim().is_empty()) {
+        if result.is_ok() {
+            crate::database::record_practice_history(
+                &conn,
+                character_id,
+                "srs",
+                direction.as_deref().unwrap_or(crate::database::DEFAULT_DIRECTION),
+                answer.trim(),
+                grade.is_correct(),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
     result
 }
 
+/// Undo the most recent SRS answer, restoring the card exactly as it was. Can be called
+/// repeatedly to undo several answers; returns None when there is nothing left to undo.
+#[tauri::command]
+pub fn undo_last_answer(db: State<DbConnection>) -> Result<Option<UndoneAnswer>, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::undo_last_answer(&conn)
+        .map_err(|e| e.to_string())
+}
+
+// === Card State Commands ===
+
+#[tauri::command]
+pub fn suspend_characters(db: State<DbConnection>, character_ids: Vec<i32>) -> Result<usize, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::set_suspended(&conn, &character_ids, true)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unsuspend_characters(db: State<DbConnection>, character_ids: Vec<i32>) -> Result<usize, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::set_suspended(&conn, &character_ids, false)
+        .map_err(|e| e.to_string())
+}
+
+/// Skip the characters until the next study day
+#[tauri::command]
+pub fn bury_characters(db: State<DbConnection>, character_ids: Vec<i32>) -> Result<usize, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::bury_until_tomorrow(&conn, &character_ids)
+        .map_err(|e| e.to_string())
+}
+
+/// Forget the characters' progress and put them back in the ready-to-learn pool
+#[tauri::command]
+pub fn reset_characters(db: State<DbConnection>, character_ids: Vec<i32>) -> Result<usize, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::reset_to_new(&conn, &character_ids)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn suspend_character(db: State<DbConnection>, character_id: i32) -> Result<usize, String> {
+    suspend_characters(db, vec![character_id])
+}
+
+#[tauri::command]
+pub fn unsuspend_character(db: State<DbConnection>, character_id: i32) -> Result<usize, String> {
+    unsuspend_characters(db, vec![character_id])
+}
+
+#[tauri::command]
+pub fn bury_character(db: State<DbConnection>, character_id: i32) -> Result<usize, String> {
+    bury_characters(db, vec![character_id])
+}
+
+#[tauri::command]
+pub fn reset_character(db: State<DbConnection>, character_id: i32) -> Result<usize, String> {
+    reset_characters(db, vec![character_id])
+}
+
+// === Leech Commands ===
+
+/// Cards that keep lapsing, with their failed reviews and the answers they were confused with
+#[tauri::command]
+pub fn get_leeches(db: State<DbConnection>) -> Result<Vec<Leech>, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_leeches(&conn)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_leech_settings(db: State<DbConnection>) -> Result<LeechSettings, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_leech_settings(&conn)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_leech_settings(db: State<DbConnection>, threshold: i32, auto_suspend: bool) -> Result<(), String> {
+    if !(1..=100).contains(&threshold) {
+        return Err(format!("Leech threshold must be between 1 and 100, got {}", threshold));
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::set_leech_settings(&conn, &LeechSettings { threshold, auto_suspend })
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_srs_settings(db: State<DbConnection>) -> Result<SrsSettings, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_srs_settings(&conn)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_srs_settings(
+    db: State<DbConnection>,
+    scheduler: String,
+    desired_retention: f32,
+) -> Result<(), String> {
+    if !data_processing::srs::is_known_scheduler(&scheduler) {
+        return Err(format!("Unknown scheduler '{}', expected 'ladder' or 'fsrs'", scheduler));
+    }
+    if !(0.7..=0.99).contains(&desired_retention) {
+        return Err(format!("Desired retention must be between 0.70 and 0.99, got {}", desired_retention));
+    }
+
+    println!("[RUST] set_srs_settings: scheduler={}, retention={}", scheduler, desired_retention);
+    let conn = db.0.lock().unwrap();
+    crate::database::set_srs_settings(&conn, &SrsSettings { scheduler, desired_retention })
+        .map_err(|e| e.to_string())
+}
+
+// === Mastery Commands ===
+
+#[tauri::command]
+pub fn get_mastery_policy(db: State<DbConnection>) -> Result<MasteryPolicy, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_mastery_policy(&conn)
+        .map_err(|e| e.to_string())
+}
+
+/// `threshold` counts answers for 'total_correct' and 'consecutive_correct', and days for 'min_interval'
+#[tauri::command]
+pub fn set_mastery_policy(
+    db: State<DbConnection>,
+    rule: String,
+    threshold: f32,
+    maintenance_interval_days: f32,
+) -> Result<(), String> {
+    if !data_processing::srs::is_known_mastery_rule(&rule) {
+        return Err(format!(
+            "Unknown mastery rule '{}', expected one of {:?}",
+            rule, data_processing::srs::MASTERY_RULES
+        ));
+    }
+    let max_threshold = if rule == "min_interval" { 3650.0 } else { 100.0 };
+    if !(1.0..=max_threshold).contains(&threshold) {
+        return Err(format!("Mastery threshold must be between 1 and {}, got {}", max_threshold, threshold));
+    }
+    if !(1.0..=3650.0).contains(&maintenance_interval_days) {
+        return Err(format!("Maintenance interval must be between 1 and 3650 days, got {}", maintenance_interval_days));
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::set_mastery_policy(&conn, &MasteryPolicy { rule, threshold, maintenance_interval_days })
+        .map_err(|e| e.to_string())
+}
+
+// === Unlock Pacing Commands ===
+
+#[tauri::command]
+pub fn get_pacing_policy(db: State<DbConnection>) -> Result<PacingPolicy, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_pacing_policy(&conn)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_pacing_policy(
+    db: State<DbConnection>,
+    initial_batch_size: usize,
+    batch_size: usize,
+    cooldown_hours: i64,
+    workload_aware: bool,
+    max_due_reviews: usize,
+) -> Result<(), String> {
+    if !(1..=500).contains(&initial_batch_size) {
+        return Err(format!("Initial batch size must be between 1 and 500, got {}", initial_batch_size));
+    }
+    if !(1..=100).contains(&batch_size) {
+        return Err(format!("Batch size must be between 1 and 100, got {}", batch_size));
+    }
+    if !(0..=720).contains(&cooldown_hours) {
+        return Err(format!("Cooldown must be between 0 and 720 hours, got {}", cooldown_hours));
+    }
+    if workload_aware && max_due_reviews == 0 {
+        return Err("Due review threshold must be at least 1".to_string());
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::set_pacing_policy(&conn, &PacingPolicy {
+        initial_batch_size,
+        batch_size,
+        cooldown_hours,
+        workload_aware,
+        max_due_reviews,
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Forecast daily reviews and new characters for the current track over the next `days` days.
+/// Pacing, daily limits, scheduler and mastery come from the user's settings unless overridden.
+#[tauri::command]
+pub fn simulate_workload(
+    db: State<DbConnection>,
+    days: u32,
+    recall_rate: f32,
+    batch_size: Option<usize>,
+    cooldown_hours: Option<i64>,
+    new_cards_per_day: Option<usize>,
+) -> Result<Forecast, String> {
+    if !(1..=365).contains(&days) {
+        return Err(format!("Days must be between 1 and 365, got {}", days));
+    }
+    if !(recall_rate > 0.0 && recall_rate <= 1.0) {
+        return Err(format!("Recall rate must be between 0 and 1, got {}", recall_rate));
+    }
+
+    let conn = db.0.lock().unwrap();
+    let pacing = crate::database::get_pacing_policy(&conn).map_err(|e| e.to_string())?;
+    let limits = crate::database::get_daily_limits(&conn).map_err(|e| e.to_string())?;
+    let srs = crate::database::get_srs_settings(&conn).map_err(|e| e.to_string())?;
+    let params = SimulationParams {
+        days,
+        recall_rate,
+        scheduler: srs.scheduler,
+        desired_retention: srs.desired_retention,
+        initial_batch_size: pacing.initial_batch_size,
+        batch_size: batch_size.unwrap_or(pacing.batch_size),
+        cooldown_hours: cooldown_hours.unwrap_or(pacing.cooldown_hours) as f32,
+        workload_aware: pacing.workload_aware,
+        max_due_reviews: pacing.max_due_reviews,
+        new_cards_per_day: new_cards_per_day.unwrap_or(limits.new_card_limit),
+        review_limit: limits.review_limit,
+        mastery: crate::database::get_mastery_policy(&conn).map_err(|e| e.to_string())?,
+    };
+
+    let track = crate::database::get_study_track(&conn).map_err(|e| e.to_string())?;
+    let state = data_processing::simulation::load_state(&conn, &track)
+        .map_err(|e| e.to_string())?;
+    let forecast = data_processing::simulation::simulate(&state, &params);
+    println!("[SRS] Simulated {} days on {}: {} reviews, {} new characters, peak {} reviews/day",
+        days, track, forecast.total_reviews, forecast.total_new_cards, forecast.peak_reviews);
+    Ok(forecast)
+}
+
+// === Card Direction Commands ===
+
+#[tauri::command]
+pub fn get_card_directions(db: State<DbConnection>) -> Result<Vec<String>, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_card_directions(&conn)
+        .map_err(|e| e.to_string())
+}
+
+/// Enable a set of directions; returns how many new cards were created
+#[tauri::command]
+pub fn set_card_directions(
+    db: State<DbConnection>,
+    directions: Vec<String>,
+) -> Result<usize, String> {
+    if directions.is_empty() {
+        return Err("At least one card direction must be enabled".to_string());
+    }
+    if let Some(unknown) = directions.iter().find(|d| !crate::database::is_known_direction(d)) {
+        return Err(format!("Unknown card direction '{}'", unknown));
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::set_card_directions(&conn, &directions)
+        .map_err(|e| e.to_string())
+}
+
+// === Study Track Commands ===
+
+#[tauri::command]
+pub fn get_study_track(db: State<DbConnection>) -> Result<String, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_study_track(&conn)
+        .map_err(|e| e.to_string())
+}
+
+/// Switch between the Mandarin and Cantonese tracks; each keeps its own cards and schedule
+#[tauri::command]
+pub fn set_study_track(db: State<DbConnection>, track: String) -> Result<(), String> {
+    if !crate::database::is_known_track(&track) {
+        return Err(format!("Unknown study track '{}', expected 'mandarin' or 'cantonese'", track));
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::set_study_track(&conn, &track)
+        .map_err(|e| e.to_string())
+}
+
+/// Set the order a track introduces characters and words in (e.g. a Cantonese frequency list)
+#[tauri::command]
+pub fn set_track_introduction_order(
+    db: State<DbConnection>,
+    track: String,
+    entries: Vec<String>,
+) -> Result<TrackOrderReport, String> {
+    if !crate::database::is_known_track(&track) {
+        return Err(format!("Unknown study track '{}', expected 'mandarin' or 'cantonese'", track));
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::set_track_introduction_order(&conn, &track, &entries)
+        .map_err(|e| e.to_string())
+}
+
+// === Review Log Commands ===
+
+#[tauri::command]
+pub fn get_character_review_log(
+    db: State<DbConnection>,
+    character_id: i32,
+    limit: Option<usize>,
+) -> Result<Vec<ReviewLogEntry>, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_review_log_for_character(&conn, character_id, limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_review_log_by_date(
+    db: State<DbConnection>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<ReviewLogEntry>, String> {
+    // Dates are YYYY-MM-DD, both inclusive
+    for date in [&start_date, &end_date] {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date))?;
+    }
+
+    let conn = db.0.lock().unwrap();
+    crate::database::get_review_log_between(&conn, &start_date, &end_date)
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn unlock_new_character(db: State<DbConnection>) -> Result<Option<Character>, String> {
     println!("[RUST] unlock_new_character called");
@@ -89,13 +516,17 @@ pub fn introduce_character_immediately_reviewable(
     // Use '-1 second' to ensure the review date is definitely in the past
     // Explicitly set ease_factor to 2.25 (mastery system cap)
     conn.execute(
-        "UPDATE user_progress
-         SET introduced = 1,
-             current_interval_days = 0.04167,
-             ease_factor = 2.25,
-             next_review_date = datetime('now', '-1 second'),
-             updated_at = datetime('now')
-         WHERE character_id = ?1",
+        &format!(
+            "UPDATE user_progress
+             SET introduced = 1,
+                 introduced_at = COALESCE(introduced_at, datetime('now')),
+                 current_interval_days = 0.04167,
+                 ease_factor = 2.25,
+                 next_review_date = datetime('now', '-1 second'),
+                 updated_at = datetime('now')
+             WHERE character_id = ?1 AND track = {}",
+            crate::database::ACTIVE_TRACK
+        ),
         [character_id]
     ).map_err(|e| {
         eprintln!("[RUST] Error updating character {}: {}", character_id, e);
@@ -112,8 +543,11 @@ pub fn get_available_to_learn_count(db: State<DbConnection>) -> Result<i32, Stri
 
     // Count characters that have been unlocked but not yet introduced
     let count: i32 = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress p
-         WHERE p.introduced = 0",
+        &format!(
+            "SELECT COUNT(DISTINCT p.character_id) FROM user_progress p
+             WHERE p.introduced = 0 AND p.is_suspended = 0 AND {}",
+            crate::database::ACTIVE_TRACK_FILTER
+        ),
         [],
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
@@ -127,35 +561,8 @@ pub fn get_unlocked_characters_batch(
     batch_size: i32,
 ) -> Result<Vec<Character>, String> {
     let conn = db.0.lock().unwrap();
-
-    // Get characters that have been unlocked but not yet introduced
-    let mut stmt = conn.prepare(
-        "SELECT c.id, c.character, c.simplified, c.traditional,
-                c.mandarin_pinyin, c.definition, c.frequency_rank, c.is_word
-         FROM characters c
-         INNER JOIN user_progress p ON c.id = p.character_id
-         WHERE p.introduced = 0
-         ORDER BY c.frequency_rank ASC
-         LIMIT ?1"
-    ).map_err(|e| e.to_string())?;
-
-    let characters = stmt.query_map([batch_size], |row| {
-        Ok(Character {
-            id: row.get(0)?,
-            character: row.get(1)?,
-            simplified: row.get(2)?,
-            traditional: row.get(3)?,
-            mandarin_pinyin: row.get(4)?,
-            definition: row.get(5)?,
-            frequency_rank: row.get(6)?,
-            is_word: row.get(7)?,
-        })
-    })
-    .map_err(|e| e.to_string())?
-    .collect::<Result<Vec<_>, _>>()
-    .map_err(|e| e.to_string())?;
-
-    Ok(characters)
+    crate::database::get_ready_to_learn_batch(&conn, batch_size.max(0) as usize)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -184,13 +591,17 @@ pub fn complete_initial_srs_session(
         // Mark character as introduced and set next review to rounded half-hour
         // Explicitly set ease_factor to 2.25 (mastery system cap)
         conn.execute(
-            "UPDATE user_progress
-             SET introduced = 1,
-                 current_interval_days = 0.04167,
-                 ease_factor = 2.25,
-                 next_review_date = ?1,
-                 updated_at = datetime('now')
-             WHERE character_id = ?2",
+            &format!(
+                "UPDATE user_progress
+                 SET introduced = 1,
+                     introduced_at = COALESCE(introduced_at, datetime('now')),
+                     current_interval_days = 0.04167,
+                     ease_factor = 2.25,
+                     next_review_date = ?1,
+                     updated_at = datetime('now')
+                 WHERE character_id = ?2 AND track = {}",
+                crate::database::ACTIVE_TRACK
+            ),
             rusqlite::params![&next_review_sqlite, char_id]
         ).map_err(|e| {
             eprintln!("[RUST] Error updating character {}: {}", char_id, e);
@@ -218,13 +629,17 @@ pub fn mark_incomplete_characters_reviewable(
         // Use '-1 second' to ensure the review date is definitely in the past
         // Explicitly set ease_factor to 2.25 (mastery system cap)
         conn.execute(
-            "UPDATE user_progress
-             SET introduced = 1,
-                 current_interval_days = 0.04167,
-                 ease_factor = 2.25,
-                 next_review_date = datetime('now', '-1 second'),
-                 updated_at = datetime('now')
-             WHERE character_id = ?1",
+            &format!(
+                "UPDATE user_progress
+                 SET introduced = 1,
+                     introduced_at = COALESCE(introduced_at, datetime('now')),
+                     current_interval_days = 0.04167,
+                     ease_factor = 2.25,
+                     next_review_date = datetime('now', '-1 second'),
+                     updated_at = datetime('now')
+                 WHERE character_id = ?1 AND track = {}",
+                crate::database::ACTIVE_TRACK
+            ),
             [char_id]
         ).map_err(|e| {
             eprintln!("[RUST] Error updating character {}: {}", char_id, e);
@@ -247,50 +662,11 @@ pub fn introduce_multiple_characters(
     println!("[RUST] introduce_multiple_characters called with count={}", count);
     let conn = db.0.lock().unwrap();
 
-    // Get 'count' most frequent characters that are NOT yet in user_progress
-    let mut stmt = conn.prepare(
-        "SELECT c.id FROM characters c
-         WHERE c.is_word = 0
-           AND NOT EXISTS (
-               SELECT 1 FROM user_progress p
-               WHERE p.character_id = c.id
-           )
-         ORDER BY c.frequency_rank ASC
-         LIMIT ?1"
-    ).map_err(|e| {
-        eprintln!("[RUST] Error preparing query: {}", e);
-        e.to_string()
-    })?;
-
-    let char_ids: Vec<i32> = stmt.query_map([count], |row| row.get(0))
-        .map_err(|e| {
-            eprintln!("[RUST] Error executing query: {}", e);
-            e.to_string()
-        })?
-        .collect::<Result<Vec<_>, _>>()
+    let char_ids = crate::database::introduce_next_characters(&conn, count.max(0) as usize)
         .map_err(|e| {
-            eprintln!("[RUST] Error collecting results: {}", e);
-            e.to_string()
-        })?;
-
-    println!("[RUST] Found {} characters to introduce: {:?}", char_ids.len(), char_ids);
-
-    // For each character, add to user_progress and mark as introduced
-    for char_id in &char_ids {
-        println!("[RUST] Adding character {} to user_progress", char_id);
-
-        // Insert into user_progress with initial SRS values
-        conn.execute(
-            "INSERT INTO user_progress
-             (character_id, current_interval_days, previous_interval_days,
-              next_review_date, introduced)
-             VALUES (?1, 0.0417, 0.0417, datetime('now'), 1)",
-            [char_id]
-        ).map_err(|e| {
-            eprintln!("[RUST] Error inserting character {}: {}", char_id, e);
+            eprintln!("[RUST] Error introducing characters: {}", e);
             e.to_string()
         })?;
-    }
 
     let result = format!("Introduced {} new characters (IDs: {:?})", char_ids.len(), char_ids);
     println!("[RUST] {}", result);
@@ -304,23 +680,33 @@ pub fn get_characters_for_initial_study(
 ) -> Result<Vec<DueCard>, String> {
     let conn = db.0.lock().unwrap();
 
+    // Introduction covers every direction at once, so study the first enabled one
+    let direction = crate::database::get_card_directions(&conn)
+        .map_err(|e| e.to_string())?
+        .remove(0);
+
     let mut cards = Vec::new();
     for char_id in character_ids {
         let card: Result<DueCard, rusqlite::Error> = conn.query_row(
-            "SELECT c.id, c.character, c.mandarin_pinyin, c.definition,
-                    p.current_interval_days, p.times_reviewed
-             FROM characters c
-             JOIN user_progress p ON c.id = p.character_id
-             WHERE c.id = ?1",
-            [char_id],
+            &format!(
+                "SELECT c.id, p.direction, c.character, {}, c.definition,
+                        p.current_interval_days, p.times_reviewed
+                 FROM characters c
+                 JOIN user_progress p ON c.id = p.character_id
+                 WHERE c.id = ?1 AND p.direction = ?2 AND {}",
+                crate::database::TRACK_READING,
+                crate::database::ACTIVE_TRACK_FILTER
+            ),
+            rusqlite::params![char_id, direction],
             |row| {
                 Ok(DueCard {
                     character_id: row.get(0)?,
-                    character: row.get(1)?,
-                    pinyin: row.get(2)?,
-                    definition: row.get(3)?,
-                    current_interval: row.get(4)?,
-                    times_reviewed: row.get(5)?,
+                    direction: row.get(1)?,
+                    character: row.get(2)?,
+                    pinyin: row.get(3)?,
+                    definition: row.get(4)?,
+                    current_interval: row.get(5)?,
+                    times_reviewed: row.get(6)?,
                 })
             }
         );
@@ -340,6 +726,8 @@ pub struct UnlockStatus {
     pub unlocked_count: usize,
     pub ready_to_learn_count: usize,
     pub hours_until_next_unlock: Option<i64>,
+    // Workload-aware pacing is waiting for due reviews to drop below the threshold
+    pub held_for_workload: bool,
 }
 
 #[tauri::command]
@@ -359,6 +747,8 @@ pub fn check_and_unlock_characters(db: State<DbConnection>) -> Result<UnlockStat
     let hours_until_next_unlock = crate::database::get_hours_until_next_unlock(&conn)
         .map_err(|e| e.to_string())?;
 
+    let held_for_workload = held_for_workload(&conn)?;
+
     println!("[RUST] Unlock status: unlocked={}, ready={}, hours_until={:?}",
         unlocked_count, ready_to_learn_count, hours_until_next_unlock);
 
@@ -366,6 +756,7 @@ pub fn check_and_unlock_characters(db: State<DbConnection>) -> Result<UnlockStat
         unlocked_count,
         ready_to_learn_count,
         hours_until_next_unlock,
+        held_for_workload,
     })
 }
 
@@ -383,15 +774,22 @@ pub fn get_unlock_status(db: State<DbConnection>) -> Result<UnlockStatus, String
         unlocked_count: 0,
         ready_to_learn_count,
         hours_until_next_unlock,
+        held_for_workload: held_for_workload(&conn)?,
     })
 }
 
+fn held_for_workload(conn: &rusqlite::Connection) -> Result<bool, String> {
+    crate::database::get_pacing_policy(conn)
+        .and_then(|policy| crate::database::is_unlock_held_for_workload(conn, &policy))
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn mark_all_ready_characters_introduced(db: State<DbConnection>) -> Result<String, String> {
     println!("[RUST] mark_all_ready_characters_introduced called");
     let conn = db.0.lock().unwrap();
 
-    // This triggers the 2-day timer to start
+    // This starts the unlock cooldown
     // We update last_unlock_date when all ready-to-learn characters are introduced
     let ready_count = crate::database::get_ready_to_learn_count(&conn)
         .map_err(|e| e.to_string())?;
@@ -402,11 +800,17 @@ pub fn mark_all_ready_characters_introduced(db: State<DbConnection>) -> Result<S
         let now = Utc::now();
         // Use SQLite datetime format
         let now_sqlite = now.format("%Y-%m-%d %H:%M:%S").to_string();
-        crate::database::set_setting(&conn, "last_unlock_date", &now_sqlite)
+        let track = crate::database::get_study_track(&conn)
             .map_err(|e| e.to_string())?;
+        crate::database::set_setting(&conn, &crate::database::track_setting_key(&track, "last_unlock_date"), &now_sqlite)
+            .map_err(|e| e.to_string())?;
+
+        let cooldown_hours = crate::database::get_pacing_policy(&conn)
+            .map_err(|e| e.to_string())?
+            .cooldown_hours;
 
         println!("[RUST] All characters introduced. Timer set to: {}", now_sqlite);
-        Ok(format!("Timer set. Next unlock in 48 hours."))
+        Ok(format!("Timer set. Next unlock in {} hours.", cooldown_hours))
     } else {
         Ok(format!("Still {} characters to introduce", ready_count))
     }
@@ -425,6 +829,26 @@ pub fn get_self_study_cards(db: State<DbConnection>, limit: usize) -> Result<Vec
         })
 }
 
+/// Grade a typed answer for a card against all of its senses and readings.
+/// The frontend records the verdict with submit_srs_answer or record_practice.
+#[tauri::command]
+pub fn grade_answer(
+    db: State<DbConnection>,
+    character_id: i32,
+    direction: String,
+    input: String,
+) -> Result<GradedAnswer, String> {
+    let conn = db.0.lock().unwrap();
+    let track = crate::database::get_study_track(&conn).map_err(|e| e.to_string())?;
+    let expected = Expected::for_direction(&direction, &track)
+        .ok_or_else(|| format!("Unknown card direction '{}'", direction))?;
+    let key = crate::database::get_answer_key(&conn, character_id).map_err(|e| e.to_string())?;
+
+    let graded = crate::grading::grade(&key, expected, &input);
+    println!("[RUST] grade_answer: char_id={}, {} {:?} -> {:?}", character_id, direction, input, graded.verdict);
+    Ok(graded)
+}
+
 #[tauri::command]
 pub fn record_practice(
     db: State<DbConnection>,
@@ -446,6 +870,39 @@ pub fn record_practice(
     .map_err(|e| e.to_string())
 }
 
+/// Segment pasted Chinese text and report each word's study status and the overall coverage
+#[tauri::command]
+pub fn analyze_text(db: State<DbConnection>, text: String) -> Result<TextAnalysis, String> {
+    let conn = db.0.lock().unwrap();
+    crate::analysis::analyze_text(&conn, &text)
+        .map_err(|e| e.to_string())
+}
+
+/// Unlock these entries (and the characters they're made of) next, ahead of frequency order
+#[tauri::command]
+pub fn pull_forward_characters(
+    db: State<DbConnection>,
+    character_ids: Vec<i32>,
+) -> Result<PullForwardReport, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::pull_forward(&conn, &character_ids)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_pull_forward_queue(db: State<DbConnection>) -> Result<Vec<QueuedEntry>, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::get_pull_forward_queue(&conn)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn clear_pull_forward_queue(db: State<DbConnection>) -> Result<usize, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::clear_pull_forward_queue(&conn)
+        .map_err(|e| e.to_string())
+}
+
 // === Dashboard Statistics Commands ===
 
 #[derive(serde::Serialize)]
@@ -454,6 +911,7 @@ pub struct DashboardStats {
     pub characters_in_srs: usize,
     pub cards_due_today: usize,
     pub mastered_characters: usize,
+    pub suspended_characters: usize,
     pub study_streak_days: i32,
 }
 
@@ -476,23 +934,25 @@ pub fn get_dashboard_stats(db: State<DbConnection>) -> Result<DashboardStats, St
     let total_characters_learned = crate::database::get_introduced_count(&conn)
         .map_err(|e| e.to_string())?;
 
+    // Suspended characters are counted separately
     let characters_in_srs: usize = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress",
+        "SELECT COUNT(*) FROM character_progress WHERE is_suspended = 0",
         [],
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
 
-    let cards_due_today: usize = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress
-         WHERE introduced = 1
-           AND is_mastered = 0
-           AND next_review_date <= datetime('now')",
+    let suspended_characters: usize = conn.query_row(
+        "SELECT COUNT(*) FROM character_progress WHERE is_suspended = 1",
         [],
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
 
+    let cards_due_today = crate::database::get_due_today_count(&conn)
+        .map_err(|e| e.to_string())?;
+
+    // A character counts as mastered once every direction is mastered
     let mastered_characters: usize = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress WHERE is_mastered = 1",
+        "SELECT COUNT(*) FROM character_progress WHERE is_mastered = 1 AND is_suspended = 0",
         [],
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
@@ -506,6 +966,7 @@ pub fn get_dashboard_stats(db: State<DbConnection>) -> Result<DashboardStats, St
         characters_in_srs,
         cards_due_today,
         mastered_characters,
+        suspended_characters,
         study_streak_days,
     })
 }
@@ -569,6 +1030,7 @@ pub struct CharacterWithProgress {
     pub simplified: String,
     pub traditional: Option<String>,
     pub mandarin_pinyin: String,
+    pub pinyin_marked: String,
     pub definition: String,
     pub frequency_rank: i32,
     pub is_word: bool,
@@ -579,6 +1041,8 @@ pub struct CharacterWithProgress {
     pub times_incorrect: Option<i32>,
     pub current_interval_days: Option<f32>,
     pub next_review_date: Option<String>,
+    // All dictionary entries (readings) for this item
+    pub senses: Vec<Sense>,
 }
 
 #[tauri::command]
@@ -593,15 +1057,16 @@ pub fn browse_characters(
         "SELECT c.id, c.character, c.simplified, c.traditional, c.mandarin_pinyin,
                 c.definition, c.frequency_rank, c.is_word,
                 p.introduced, p.times_reviewed, p.times_correct, p.times_incorrect,
-                p.current_interval_days, p.next_review_date
+                p.current_interval_days, p.next_review_date,
+                COALESCE(c.pinyin_marked, c.mandarin_pinyin)
          FROM characters c
-         LEFT JOIN user_progress p ON c.id = p.character_id
+         LEFT JOIN character_progress p ON c.id = p.character_id
          WHERE c.is_word = 0
          ORDER BY c.frequency_rank ASC
          LIMIT ?1 OFFSET ?2"
     ).map_err(|e| e.to_string())?;
 
-    let characters = stmt.query_map([limit, offset], |row| {
+    let mut characters = stmt.query_map([limit, offset], |row| {
         Ok(CharacterWithProgress {
             id: row.get(0)?,
             character: row.get(1)?,
@@ -617,15 +1082,56 @@ pub fn browse_characters(
             times_incorrect: row.get(11)?,
             current_interval_days: row.get(12)?,
             next_review_date: row.get(13)?,
+            pinyin_marked: row.get(14)?,
+            senses: Vec::new(),
         })
     })
     .map_err(|e| e.to_string())?
     .collect::<Result<Vec<_>, _>>()
     .map_err(|e| e.to_string())?;
 
+    for character in &mut characters {
+        character.senses = crate::database::get_senses(&conn, character.id)
+            .map_err(|e| e.to_string())?;
+    }
+
     Ok(characters)
 }
 
+#[tauri::command]
+pub fn search_dictionary(
+    db: State<DbConnection>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, String> {
+    let conn = db.0.lock().unwrap();
+    crate::database::search_dictionary(&conn, &query, limit.unwrap_or(50))
+        .map_err(|e| e.to_string())
+}
+
+/// Export introduced cards, their schedule and review history as an Anki .apkg file
+#[tauri::command]
+pub fn export_anki_deck(
+    db: State<DbConnection>,
+    path: String,
+) -> Result<crate::export::ExportSummary, String> {
+    let conn = db.0.lock().unwrap();
+    crate::export::export_apkg(&conn, std::path::Path::new(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// Seed progress from an Anki, Pleco or CSV export; format is guessed from the extension if omitted
+#[tauri::command]
+pub fn import_progress(
+    db: State<DbConnection>,
+    path: String,
+    format: Option<crate::import::ImportFormat>,
+) -> Result<crate::import::ImportReport, String> {
+    let conn = db.0.lock().unwrap();
+    crate::import::import_progress(&conn, std::path::Path::new(&path), format)
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_total_characters_count(db: State<DbConnection>) -> Result<i32, String> {
     let conn = db.0.lock().unwrap();
@@ -644,6 +1150,7 @@ pub struct CharacterWithProgressAndScore {
     pub simplified: String,
     pub traditional: Option<String>,
     pub mandarin_pinyin: String,
+    pub pinyin_marked: String,
     pub definition: String,
     pub frequency_rank: i32,
     pub is_word: bool,
@@ -656,6 +1163,8 @@ pub struct CharacterWithProgressAndScore {
     pub times_incorrect: Option<i32>,
     pub current_interval_days: Option<f32>,
     pub next_review_date: Option<String>,
+    // All dictionary entries (readings) for this item
+    pub senses: Vec<Sense>,
 }
 
 #[tauri::command]
@@ -672,14 +1181,15 @@ pub fn browse_introduction_order(
                 c.definition, c.frequency_rank, c.is_word, c.component_characters,
                 c.introduction_rank,
                 p.introduced, p.times_reviewed, p.times_correct, p.times_incorrect,
-                p.current_interval_days, p.next_review_date
+                p.current_interval_days, p.next_review_date,
+                COALESCE(c.pinyin_marked, c.mandarin_pinyin)
          FROM characters c
-         LEFT JOIN user_progress p ON c.id = p.character_id
+         LEFT JOIN character_progress p ON c.id = p.character_id
          ORDER BY c.introduction_rank ASC
          LIMIT ?1 OFFSET ?2"
     ).map_err(|e| e.to_string())?;
 
-    let results = stmt.query_map([limit, offset], |row| {
+    let mut results = stmt.query_map([limit, offset], |row| {
         Ok(CharacterWithProgressAndScore {
             id: row.get(0)?,
             character: row.get(1)?,
@@ -697,12 +1207,19 @@ pub fn browse_introduction_order(
             times_incorrect: row.get(13)?,
             current_interval_days: row.get(14)?,
             next_review_date: row.get(15)?,
+            pinyin_marked: row.get(16)?,
+            senses: Vec::new(),
         })
     })
     .map_err(|e| e.to_string())?
     .collect::<Result<Vec<_>, _>>()
     .map_err(|e| e.to_string())?;
 
+    for item in &mut results {
+        item.senses = crate::database::get_senses(&conn, item.id)
+            .map_err(|e| e.to_string())?;
+    }
+
     Ok(results)
 }
 
@@ -739,29 +1256,39 @@ pub fn get_database_debug_info(db: State<DbConnection>) -> Result<DatabaseDebugI
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
 
+    // Counts and unlock settings are for the active track
     let characters_in_progress: i32 = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress",
+        "SELECT COUNT(*) FROM character_progress",
         [],
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
 
     let ready_to_learn: i32 = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress WHERE introduced = 0",
+        "SELECT COUNT(*) FROM character_progress WHERE introduced = 0",
         [],
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
 
     let introduced: i32 = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress WHERE introduced = 1",
+        "SELECT COUNT(*) FROM character_progress WHERE introduced = 1",
         [],
         |row| row.get(0)
     ).map_err(|e| e.to_string())?;
 
-    let initial_unlock_completed = crate::database::get_setting(&conn, "initial_unlock_completed")
-        .unwrap_or_else(|_| "ERROR".to_string());
+    let track = crate::database::get_study_track(&conn)
+        .map_err(|e| e.to_string())?;
+
+    let initial_unlock_completed = crate::database::get_setting(
+        &conn,
+        &crate::database::track_setting_key(&track, "initial_unlock_completed"),
+    )
+    .unwrap_or_else(|_| "ERROR".to_string());
 
-    let last_unlock_date = crate::database::get_setting(&conn, "last_unlock_date")
-        .unwrap_or_else(|_| "ERROR".to_string());
+    let last_unlock_date = crate::database::get_setting(
+        &conn,
+        &crate::database::track_setting_key(&track, "last_unlock_date"),
+    )
+    .unwrap_or_else(|_| "ERROR".to_string());
 
     Ok(DatabaseDebugInfo {
         total_characters,
@@ -779,29 +1306,43 @@ pub fn get_database_debug_info(db: State<DbConnection>) -> Result<DatabaseDebugI
 pub struct ReviewCalendarEntry {
     pub review_time: String,  // Full datetime in half-hour blocks (YYYY-MM-DD HH:MM:SS)
     pub cards_due: i32,
+    pub study_day: String,    // Local study day the review falls on (YYYY-MM-DD)
+    pub is_today: bool,
 }
 
 #[tauri::command]
 pub fn get_review_calendar(db: State<DbConnection>, days: i32) -> Result<Vec<ReviewCalendarEntry>, String> {
     let conn = db.0.lock().unwrap();
+    let clock = StudyClock::from_settings(&conn).map_err(|e| e.to_string())?;
+    let today = clock.today();
+    // Through the end of the local study day `days` from today
+    let until = clock.day_start(today + Duration::days(days.max(0) as i64 + 1));
 
-    let mut stmt = conn.prepare(
-        "SELECT next_review_date,
+    let mut stmt = conn.prepare(&format!(
+        "SELECT p.next_review_date,
                 COUNT(*) as cards_due
-         FROM user_progress
-         WHERE introduced = 1
-           AND is_mastered = 0
-           AND next_review_date IS NOT NULL
-           AND next_review_date > datetime('now')
-           AND DATE(next_review_date) <= DATE('now', '+' || ?1 || ' days')
-         GROUP BY next_review_date
-         ORDER BY next_review_date ASC"
-    ).map_err(|e| e.to_string())?;
-
-    let entries = stmt.query_map([days], |row| {
+         FROM user_progress p
+         WHERE p.introduced = 1
+           AND p.next_review_date IS NOT NULL
+           AND p.next_review_date > datetime('now')
+           AND p.next_review_date < ?1
+           AND p.is_suspended = 0
+           AND {}
+           AND {}
+         GROUP BY p.next_review_date
+         ORDER BY p.next_review_date ASC",
+        crate::database::ENABLED_DIRECTION_FILTER,
+        crate::database::ACTIVE_TRACK_FILTER
+    )).map_err(|e| e.to_string())?;
+
+    let entries = stmt.query_map([until.format("%Y-%m-%d %H:%M:%S").to_string()], |row| {
+        let review_time: String = row.get(0)?;
+        let study_day = clock.day_of_sqlite(&review_time).unwrap_or(today);
         Ok(ReviewCalendarEntry {
-            review_time: row.get(0)?,
+            review_time,
             cards_due: row.get(1)?,
+            study_day: study_day.to_string(),
+            is_today: study_day == today,
         })
     })
     .map_err(|e| e.to_string())?
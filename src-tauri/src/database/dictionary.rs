@@ -0,0 +1,329 @@
+// Dictionary refresh
+// The user's chinese.db holds both reference data (characters, senses, strokes, search index) and user
+// data (user_progress, practice_history, review_log, track_introduction_order). When the bundled
+// master database ships a different dictionary_version, the reference tables are replaced from it
+// and user rows are re-pointed through characters.character, the stable key. Row ids are local to
+// each build.
+
+use rusqlite::{Connection, OptionalExtension, Result};
+use std::path::Path;
+
+use super::{get_setting, set_setting};
+
+/// Reference tables keyed by characters.id, replaced along with characters
+const REFERENCE_TABLES: [&str; 2] = ["senses", "character_strokes"];
+
+/// User tables whose rows refer to characters.id
+const USER_TABLES: [&str; 5] = [
+    "user_progress", "practice_history", "review_log", "track_introduction_order", "pull_forward_queue",
+];
+
+#[derive(Debug, PartialEq)]
+pub struct DictionaryRefresh {
+    pub version: String,
+    /// Characters and words in the new dictionary
+    pub characters: usize,
+    /// Studied characters the new dictionary no longer has, kept so their progress survives
+    pub retained: usize,
+}
+
+/// The master database's dictionary version, if it differs from the one this database holds
+pub fn outdated_dictionary_version(conn: &Connection, master_path: &Path) -> Result<Option<String>> {
+    let master = Connection::open_with_flags(master_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let master_version: Option<String> = master
+        .query_row("SELECT value FROM app_settings WHERE key = 'dictionary_version'", [], |row| row.get(0))
+        .optional()?;
+
+    // Databases built before versioning can't be compared
+    let Some(master_version) = master_version else {
+        return Ok(None);
+    };
+
+    let user_version = get_setting(conn, "dictionary_version").ok();
+    if user_version.as_deref() == Some(master_version.as_str()) {
+        return Ok(None);
+    }
+
+    println!("[DB] Dictionary {:?} is outdated, bundled version is {}", user_version, master_version);
+    Ok(Some(master_version))
+}
+
+/// Replace characters and senses from the master database, keeping all user data
+pub fn refresh_dictionary(conn: &Connection, master_path: &Path, version: &str) -> Result<DictionaryRefresh> {
+    // Replacing characters would cascade-delete user rows; the pragma can't change inside a transaction
+    let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+    conn.execute_batch("PRAGMA foreign_keys = OFF")?;
+    conn.execute("ATTACH DATABASE ?1 AS master", [master_path.to_string_lossy()])?;
+
+    let result = replace_reference_data(conn, version);
+
+    conn.execute("DETACH DATABASE master", [])?;
+    conn.execute_batch(&format!("PRAGMA foreign_keys = {}", if foreign_keys { "ON" } else { "OFF" }))?;
+
+    let refresh = result?;
+    println!("[DB] Dictionary refreshed to {}: {} characters, {} retained from the old dictionary",
+             refresh.version, refresh.characters, refresh.retained);
+    Ok(refresh)
+}
+
+fn replace_reference_data(conn: &Connection, version: &str) -> Result<DictionaryRefresh> {
+    let tx = conn.unchecked_transaction()?;
+
+    // old id → new id by stable key; NULL when the new dictionary dropped the entry
+    tx.execute_batch(
+        "DROP TABLE IF EXISTS temp.id_map;
+         CREATE TEMP TABLE id_map (old_id INTEGER PRIMARY KEY, new_id INTEGER);
+         INSERT INTO temp.id_map (old_id, new_id)
+             SELECT c.id, m.id FROM main.characters c
+             LEFT JOIN master.characters m ON m.character = c.character;"
+    )?;
+
+    // Dropped entries that someone has studied are carried over with their senses and strokes
+    let referenced = USER_TABLES
+        .iter()
+        .map(|table| format!("SELECT character_id FROM main.{}", table))
+        .collect::<Vec<_>>()
+        .join(" UNION ");
+    tx.execute_batch(&format!(
+        "DROP TABLE IF EXISTS temp.retained_characters;
+         CREATE TEMP TABLE retained_characters AS
+             SELECT * FROM main.characters
+             WHERE id IN (SELECT old_id FROM temp.id_map WHERE new_id IS NULL)
+               AND id IN ({});",
+        referenced
+    ))?;
+    for table in REFERENCE_TABLES {
+        tx.execute_batch(&format!(
+            "DROP TABLE IF EXISTS temp.retained_{0};
+             CREATE TEMP TABLE retained_{0} AS
+                 SELECT * FROM main.{0} WHERE character_id IN (SELECT id FROM temp.retained_characters);",
+            table
+        ))?;
+    }
+
+    // === characters ===
+    let character_columns = shared_columns(&tx, "characters")?;
+    tx.execute("DELETE FROM main.characters", [])?;
+    let characters = tx.execute(
+        &format!(
+            "INSERT INTO main.characters ({0}) SELECT {0} FROM master.characters",
+            character_columns
+        ),
+        []
+    )?;
+
+    // Retained rows get ids after the new dictionary's; component ids belonged to the old build
+    let retained_columns: Vec<&str> = character_columns.split(", ").filter(|c| *c != "id").collect();
+    let retained_values: Vec<&str> = retained_columns
+        .iter()
+        .map(|c| if *c == "component_characters" { "NULL" } else { c })
+        .collect();
+    let retained = tx.execute(
+        &format!(
+            "INSERT INTO main.characters ({}) SELECT {} FROM temp.retained_characters ORDER BY id",
+            retained_columns.join(", "),
+            retained_values.join(", ")
+        ),
+        []
+    )?;
+    tx.execute(
+        "UPDATE temp.id_map
+         SET new_id = (SELECT c.id FROM main.characters c
+                       JOIN temp.retained_characters r ON r.character = c.character
+                       WHERE r.id = temp.id_map.old_id)
+         WHERE new_id IS NULL",
+        []
+    )?;
+
+    // === senses, strokes ===
+    for table in REFERENCE_TABLES {
+        let columns = shared_columns(&tx, table)?;
+        if columns.is_empty() {
+            continue; // Bundled database predates this table; keep what we have
+        }
+        tx.execute(&format!("DELETE FROM main.{}", table), [])?;
+        tx.execute(&format!("INSERT INTO main.{0} ({1}) SELECT {1} FROM master.{0}", table, columns), [])?;
+
+        let retained_columns: Vec<&str> = columns.split(", ").filter(|c| *c != "id").collect();
+        let retained_values: Vec<String> = retained_columns
+            .iter()
+            .map(|c| if *c == "character_id" { "m.new_id".to_string() } else { format!("r.{}", c) })
+            .collect();
+        tx.execute(
+            &format!(
+                "INSERT INTO main.{0} ({1})
+                 SELECT {2} FROM temp.retained_{0} r
+                 JOIN temp.id_map m ON m.old_id = r.character_id",
+                table,
+                retained_columns.join(", "),
+                retained_values.join(", ")
+            ),
+            []
+        )?;
+        tx.execute(&format!("DROP TABLE temp.retained_{}", table), [])?;
+    }
+
+    // === user data ===
+    // Negate first so re-pointing never collides with the unique (track, character_id, direction) index
+    for table in USER_TABLES {
+        tx.execute_batch(&format!(
+            "UPDATE main.{0} SET character_id = -character_id;
+             UPDATE main.{0}
+             SET character_id = (SELECT new_id FROM temp.id_map WHERE old_id = -main.{0}.character_id)
+             WHERE character_id < 0
+               AND (SELECT new_id FROM temp.id_map WHERE old_id = -main.{0}.character_id) IS NOT NULL;
+             UPDATE main.{0} SET character_id = -character_id WHERE character_id < 0;",
+            table
+        ))?;
+    }
+
+    data_processing::search::build_search_index(&tx)?;
+    set_setting(&tx, "dictionary_version", version)?;
+
+    tx.execute_batch(
+        "DROP TABLE temp.id_map;
+         DROP TABLE temp.retained_characters;"
+    )?;
+    tx.commit()?;
+
+    Ok(DictionaryRefresh { version: version.to_string(), characters, retained })
+}
+
+/// Columns present in both main.<table> and master.<table>, comma-separated
+fn shared_columns(conn: &Connection, table: &str) -> Result<String> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT name FROM pragma_table_info('{0}', 'main')
+         WHERE name IN (SELECT name FROM pragma_table_info('{0}', 'master'))
+         ORDER BY cid",
+        table
+    ))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(columns.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = include_str!("../../../data-processing/schema.sql");
+
+    fn master_db(dir: &Path) -> std::path::PathBuf {
+        let path = dir.join("master.db");
+        let master = Connection::open(&path).unwrap();
+        master.execute_batch(SCHEMA).unwrap();
+        // Rebuilt dictionary: different ids, a fixed definition, 是 added and 乂 removed
+        master.execute_batch(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word, introduction_rank)
+             VALUES ('是', '是', '是', 'shi4', 'to be', 3, 0, 1),
+                    ('一', '一', '一', 'yi1', 'one; a; single', 2, 0, 2),
+                    ('的', '的', '的', 'de5', 'of; possessive particle', 1, 0, 3);
+             INSERT INTO senses (character_id, traditional, pinyin, definition)
+             SELECT id, traditional, mandarin_pinyin, definition FROM characters;
+             INSERT INTO senses (character_id, traditional, pinyin, definition)
+             SELECT id, traditional, 'di2', 'really and truly' FROM characters WHERE character = '的';
+             INSERT INTO character_strokes (character_id, strokes, medians)
+             SELECT id, '[\"M 518 382 Z\"]', '[[[121,393],[890,393]]]' FROM characters WHERE character = '一';"
+        ).unwrap();
+        data_processing::database::stamp_dictionary_version(&master).unwrap();
+        path
+    }
+
+    fn user_db() -> Connection {
+        let conn = super::super::test_user_db(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word)
+             VALUES ('的', '的', '的', 'de5', 'possessive particle', 1, 0),
+                    ('一', '一', '一', 'yi1', 'one', 2, 0),
+                    ('乂', '乂', '乂', 'yi4', 'to govern', 9000, 0),
+                    ('乃', '乃', '乃', 'nai3', 'to be', 3000, 0);
+             INSERT INTO senses (character_id, traditional, pinyin, definition)
+             SELECT id, traditional, mandarin_pinyin, definition FROM characters;
+             INSERT INTO character_strokes (character_id, strokes, medians) VALUES (3, '[\"M 1 2 Z\"]', '[]');"
+        );
+        for id in 1..=3 {
+            super::super::insert_progress_rows(&conn, id, true).unwrap();
+        }
+        for _ in 0..3 {
+            super::super::record_srs_answer(&conn, 1, true, None, None).unwrap();
+        }
+        super::super::record_srs_answer(&conn, 3, true, None, None).unwrap();
+        conn.execute(
+            "INSERT INTO practice_history (character_id, practice_mode, is_correct) VALUES (2, 'lookup', 1)",
+            []
+        ).unwrap();
+        conn
+    }
+
+    fn character_of(conn: &Connection, table: &str, id_column: &str, row_id: i64) -> String {
+        conn.query_row(
+            &format!("SELECT c.character FROM {} t JOIN characters c ON c.id = t.{} WHERE t.id = ?1", table, id_column),
+            [row_id],
+            |row| row.get(0)
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_refresh_keeps_progress_on_stable_keys() {
+        let dir = std::env::temp_dir().join(format!("chinese-flashcards-dictionary-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let master_path = master_db(&dir);
+        let conn = user_db();
+
+        let version = outdated_dictionary_version(&conn, &master_path).unwrap().unwrap();
+        let refresh = refresh_dictionary(&conn, &master_path, &version).unwrap();
+        assert_eq!(refresh.characters, 3);
+        assert_eq!(refresh.retained, 1); // 乂 was studied, 乃 was not
+
+        // Progress follows the character, not the old id
+        let (interval, reviews): (f64, i32) = conn.query_row(
+            "SELECT p.current_interval_days, p.times_reviewed FROM user_progress p
+             JOIN characters c ON c.id = p.character_id WHERE c.character = '的'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).unwrap();
+        assert_eq!((interval, reviews), (3.0, 3));
+        assert_eq!(character_of(&conn, "practice_history", "character_id", 1), "一");
+        assert_eq!(character_of(&conn, "review_log", "character_id", 4), "乂");
+
+        // Reference data comes from the new build
+        let definition: String = conn.query_row(
+            "SELECT definition FROM characters WHERE character = '一'", [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(definition, "one; a; single");
+        let rank: Option<i32> = conn.query_row(
+            "SELECT introduction_rank FROM characters WHERE character = '是'", [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(rank, Some(1));
+        let readings: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM senses s JOIN characters c ON c.id = s.character_id WHERE c.character IN ('的', '乂')",
+            [],
+            |row| row.get(0)
+        ).unwrap();
+        assert_eq!(readings, 3);
+        let unstudied: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM characters WHERE character = '乃'", [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(unstudied, 0);
+        let strokes: Vec<String> = conn
+            .prepare("SELECT c.character FROM character_strokes s JOIN characters c ON c.id = s.character_id ORDER BY c.character")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(strokes, vec!["一", "乂"]);
+        let yi: i32 = conn.query_row("SELECT id FROM characters WHERE character = '一'", [], |row| row.get(0)).unwrap();
+        let details = super::super::get_character_details(&conn, yi).unwrap();
+        assert_eq!(details[0].strokes, vec!["M 518 382 Z"]);
+        assert_eq!(details[0].medians, vec![vec![[121, 393], [890, 393]]]);
+
+        let hits = super::super::search_dictionary(&conn, "really", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+
+        // Up to date now
+        assert_eq!(outdated_dictionary_version(&conn, &master_path).unwrap(), None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
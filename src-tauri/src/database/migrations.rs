@@ -0,0 +1,622 @@
+// Schema migrations
+// Every schema change is a numbered, named step in MIGRATIONS. Each step runs in its own
+// transaction together with its schema_version row, so a failure leaves the database at
+// the last good version and nothing after it runs.
+// Steps only use SQL written into the step, never live app code, so upgrading from an old
+// version runs exactly what shipped. Data derived from the dictionary with app code (search
+// index, normalized pinyin) is the exception: it has to match the code that reads it, so it
+// is rebuilt once with the current code, together with the last step (see refresh_derived_data).
+
+use rusqlite::{Connection, Result};
+use std::path::{Path, PathBuf};
+
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// All migrations in order. Version 1 is the baseline schema.sql; never edit a shipped step,
+/// add a new one instead, and never call app code from a step.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 2, name: "Time-based character introduction", apply: time_based_introduction },
+    Migration { version: 3, name: "Add mastery tracking", apply: mastery_tracking },
+    Migration { version: 4, name: "Add review log", apply: review_log },
+    Migration { version: 5, name: "Add FSRS scheduler support", apply: fsrs_scheduler },
+    Migration { version: 6, name: "Per-direction cards", apply: per_direction_cards },
+    Migration { version: 7, name: "Add senses table", apply: senses_table },
+    Migration { version: 8, name: "Dictionary search index", apply: search_index },
+    Migration { version: 9, name: "Traditional form index", apply: traditional_index },
+    Migration { version: 10, name: "Add character strokes table", apply: character_strokes },
+    Migration { version: 11, name: "Study tracks", apply: study_tracks },
+    Migration { version: 12, name: "Normalized pinyin columns", apply: normalized_pinyin },
+    Migration { version: 13, name: "Pull-forward queue", apply: pull_forward_queue },
+    Migration { version: 14, name: "Unlock pacing settings", apply: unlock_pacing },
+    Migration { version: 15, name: "Daily limit rollover hour", apply: day_rollover },
+    Migration { version: 16, name: "Study day timezone", apply: study_day_timezone },
+    Migration { version: 17, name: "Undo snapshots in review log", apply: review_undo },
+    Migration { version: 18, name: "Suspended and buried cards", apply: suspend_and_bury },
+    Migration { version: 19, name: "Leech tracking", apply: leech_tracking },
+    Migration { version: 20, name: "Mastery rules and maintenance reviews", apply: maintenance_reviews },
+    Migration { version: 21, name: "Introduction timestamps", apply: introduction_timestamps },
+    Migration { version: 22, name: "Mastery after demotion", apply: mastery_after_demotion },
+];
+
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(1)
+}
+
+pub fn current_version(conn: &Connection) -> Result<i32> {
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))
+}
+
+pub fn pending_migrations(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let version = current_version(conn)?;
+    Ok(MIGRATIONS.iter().filter(|m| m.version > version).collect())
+}
+
+/// Apply every pending migration. Returns the number applied.
+pub fn run_migrations(conn: &Connection) -> Result<usize> {
+    apply_migrations(conn, MIGRATIONS)
+}
+
+/// Run every pending migration inside a transaction that is rolled back, to find out whether
+/// the upgrade would succeed without touching the database. Returns the versions it would apply.
+pub fn dry_run(conn: &Connection) -> Result<Vec<i32>> {
+    let pending = pending_migrations(conn)?;
+
+    let tx = conn.unchecked_transaction()?;
+    for migration in &pending {
+        println!("[DB] Dry run: migration {} ({})", migration.version, migration.name);
+        (migration.apply)(&tx).map_err(|e| {
+            eprintln!("[DB] Dry run: migration {} would fail: {}", migration.version, e);
+            e
+        })?;
+    }
+    if !pending.is_empty() {
+        refresh_derived_data(&tx)?;
+    }
+    tx.rollback()?;
+
+    Ok(pending.iter().map(|m| m.version).collect())
+}
+
+/// Copy the database to `<name>.v<version>.bak` next to it before migrating
+pub fn backup_before_migrating(conn: &Connection, db_path: &Path) -> Result<PathBuf> {
+    let version = current_version(conn)?;
+    super::backup_database(conn, db_path, &format!("v{}", version))
+}
+
+fn apply_migrations(conn: &Connection, migrations: &[Migration]) -> Result<usize> {
+    let version = current_version(conn)?;
+    println!("[DB] Current schema version: {} (latest {})", version, latest_version());
+
+    let mut applied = 0;
+    for migration in migrations.iter().filter(|m| m.version > version) {
+        println!("[DB] Running migration {}: {}", migration.version, migration.name);
+
+        let tx = conn.unchecked_transaction()?;
+        (migration.apply)(&tx)
+            .and_then(|_| {
+                if migration.version == latest_version() {
+                    refresh_derived_data(&tx)?;
+                }
+                Ok(())
+            })
+            .and_then(|_| {
+                tx.execute(
+                    "INSERT INTO schema_version (version, description) VALUES (?1, ?2)",
+                    rusqlite::params![migration.version, migration.name]
+                )
+            })
+            .map_err(|e| {
+                eprintln!("[DB] ERROR: Migration {} failed, rolled back: {}", migration.version, e);
+                e
+            })?;
+        tx.commit()?;
+
+        println!("[DB] Migration {} completed", migration.version);
+        applied += 1;
+    }
+
+    Ok(applied)
+}
+
+/// Rebuild the data the app derives from the dictionary with its own code, once the schema is
+/// up to date. Unlike the steps this deliberately runs today's code: an index built by older
+/// code wouldn't match how today's code queries it. A dictionary refresh rebuilds it as well.
+fn refresh_derived_data(conn: &Connection) -> Result<()> {
+    let normalized = data_processing::pinyin::store_pinyin_forms(conn)?;
+    let indexed = data_processing::search::build_search_index(conn)?;
+    println!("[DB] Normalized pinyin for {} characters, indexed {} senses", normalized, indexed);
+    Ok(())
+}
+
+/// ALTER TABLE ADD COLUMN, skipped if the column is already there
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1", table),
+        [column],
+        |row| row.get(0)
+    )?;
+
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+// === Migration steps ===
+
+fn time_based_introduction(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO app_settings (key, value) VALUES
+         ('last_unlock_date', ''),
+         ('initial_unlock_completed', 'false')",
+        []
+    )?;
+    Ok(())
+}
+
+fn mastery_tracking(conn: &Connection) -> Result<()> {
+    add_column(conn, "user_progress", "is_mastered", "BOOLEAN DEFAULT 0")
+}
+
+fn review_log(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS review_log (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             character_id INTEGER NOT NULL,
+             direction TEXT,
+             grade INTEGER NOT NULL,
+             is_correct BOOLEAN NOT NULL,
+             interval_before REAL,
+             interval_after REAL,
+             ease_before REAL,
+             ease_after REAL,
+             scheduled_at TIMESTAMP,
+             reviewed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+             response_time_ms INTEGER,
+             FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+         );
+         CREATE INDEX IF NOT EXISTS idx_review_log_character ON review_log(character_id, reviewed_at);
+         CREATE INDEX IF NOT EXISTS idx_review_log_time ON review_log(reviewed_at);"
+    )
+}
+
+fn fsrs_scheduler(conn: &Connection) -> Result<()> {
+    add_column(conn, "user_progress", "stability", "REAL")?;
+    add_column(conn, "user_progress", "difficulty", "REAL")?;
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO app_settings (key, value) VALUES
+             ('srs_scheduler', 'ladder'),
+             ('desired_retention', '0.9');"
+    )?;
+
+    // Existing cards get an FSRS memory state estimated from their ladder state: stability is
+    // the interval, difficulty follows ease (2.25 → 5, 1.3 → 10)
+    let seeded = conn.execute(
+        "UPDATE user_progress
+         SET stability = MAX(current_interval_days, 0.1),
+             difficulty = MIN(MAX(5.0 + (2.25 - ease_factor) * 5.0 / 0.95, 1.0), 10.0)
+         WHERE introduced = 1",
+        []
+    )?;
+    println!("[DB] Migration 5: Seeded FSRS state for {} cards", seeded);
+    Ok(())
+}
+
+fn per_direction_cards(conn: &Connection) -> Result<()> {
+    // Existing progress becomes the character → meaning direction
+    add_column(conn, "user_progress", "direction", "TEXT NOT NULL DEFAULT 'zh_to_en'")?;
+    conn.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_progress_character_direction
+             ON user_progress(character_id, direction);
+         CREATE VIEW IF NOT EXISTS character_progress AS
+             SELECT character_id,
+                    MAX(introduced) AS introduced,
+                    MIN(is_mastered) AS is_mastered,
+                    SUM(times_reviewed) AS times_reviewed,
+                    SUM(times_correct) AS times_correct,
+                    SUM(times_incorrect) AS times_incorrect,
+                    MIN(current_interval_days) AS current_interval_days,
+                    MIN(next_review_date) AS next_review_date
+             FROM user_progress
+             GROUP BY character_id;
+         INSERT OR IGNORE INTO app_settings (key, value) VALUES ('card_directions', 'zh_to_en');"
+    )
+}
+
+fn senses_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS senses (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             character_id INTEGER NOT NULL,
+             traditional TEXT,
+             pinyin TEXT NOT NULL,
+             definition TEXT NOT NULL,
+             source TEXT NOT NULL DEFAULT 'cedict',
+             source_line INTEGER,
+             FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+         );
+         CREATE INDEX IF NOT EXISTS idx_senses_character ON senses(character_id);"
+    )?;
+
+    // Databases built before senses existed only kept the first entry per character.
+    // Keep that as the single sense until the dictionary is rebuilt.
+    let backfilled = conn.execute(
+        "INSERT INTO senses (character_id, traditional, pinyin, definition, source)
+         SELECT c.id, c.traditional, c.mandarin_pinyin, c.definition, 'cedict'
+         FROM characters c
+         WHERE NOT EXISTS (SELECT 1 FROM senses s WHERE s.character_id = c.id)",
+        []
+    )?;
+    println!("[DB] Migration 7: Backfilled {} senses from characters", backfilled);
+    Ok(())
+}
+
+fn search_index(conn: &Connection) -> Result<()> {
+    // Filled by refresh_derived_data once the schema is up to date
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS dictionary_fts USING fts5(
+             character_id UNINDEXED,
+             toneless UNINDEXED,
+             hanzi,
+             pinyin,
+             definition,
+             tokenize = 'unicode61 remove_diacritics 2'
+         );"
+    )
+}
+
+fn traditional_index(conn: &Connection) -> Result<()> {
+    // Used to match imported traditional characters
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_traditional ON characters(traditional)", [])?;
+    Ok(())
+}
+
+fn character_strokes(conn: &Connection) -> Result<()> {
+    // Filled by the next dictionary refresh from a bundled database built with Make Me a Hanzi
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS character_strokes (
+             character_id INTEGER PRIMARY KEY,
+             strokes TEXT NOT NULL,
+             medians TEXT NOT NULL,
+             FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+         );"
+    )
+}
+
+fn study_tracks(conn: &Connection) -> Result<()> {
+    // Existing progress and history are Mandarin; each track gets its own cards
+    add_column(conn, "user_progress", "track", "TEXT NOT NULL DEFAULT 'mandarin'")?;
+    add_column(conn, "review_log", "track", "TEXT NOT NULL DEFAULT 'mandarin'")?;
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS idx_progress_character_direction;
+         CREATE UNIQUE INDEX IF NOT EXISTS idx_progress_track_character_direction
+             ON user_progress(track, character_id, direction);
+         DROP VIEW IF EXISTS character_progress;
+         CREATE VIEW character_progress AS
+             SELECT character_id,
+                    MAX(introduced) AS introduced,
+                    MIN(is_mastered) AS is_mastered,
+                    SUM(times_reviewed) AS times_reviewed,
+                    SUM(times_correct) AS times_correct,
+                    SUM(times_incorrect) AS times_incorrect,
+                    MIN(current_interval_days) AS current_interval_days,
+                    MIN(next_review_date) AS next_review_date
+             FROM user_progress
+             WHERE track = COALESCE((SELECT value FROM app_settings WHERE key = 'study_track'), 'mandarin')
+             GROUP BY character_id;
+         CREATE TABLE IF NOT EXISTS track_introduction_order (
+             track TEXT NOT NULL,
+             character_id INTEGER NOT NULL,
+             position INTEGER NOT NULL,
+             PRIMARY KEY (track, character_id),
+             FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+         );
+         INSERT OR IGNORE INTO app_settings (key, value) VALUES ('study_track', 'mandarin');"
+    )
+}
+
+fn normalized_pinyin(conn: &Connection) -> Result<()> {
+    add_column(conn, "characters", "pinyin_marked", "TEXT")?;
+    add_column(conn, "characters", "pinyin_toneless", "TEXT")?;
+    add_column(conn, "characters", "pinyin_sandhi", "TEXT")?;
+    // Filled by refresh_derived_data once the schema is up to date
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_pinyin_toneless ON characters(pinyin_toneless)", [])?;
+    Ok(())
+}
+
+fn pull_forward_queue(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS pull_forward_queue (
+             track TEXT NOT NULL,
+             character_id INTEGER NOT NULL,
+             position INTEGER NOT NULL,
+             added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+             PRIMARY KEY (track, character_id),
+             FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+         );"
+    )
+}
+
+fn unlock_pacing(conn: &Connection) -> Result<()> {
+    // The values that used to be hard-coded, so existing users see no change
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO app_settings (key, value) VALUES
+             ('initial_unlock_size', '100'),
+             ('unlock_batch_size', '10'),
+             ('unlock_cooldown_hours', '48'),
+             ('workload_aware_unlock', 'false'),
+             ('unlock_max_due_reviews', '50');"
+    )
+}
+
+fn day_rollover(conn: &Connection) -> Result<()> {
+    // daily_review_limit and daily_new_cards were seeded by the first schema but never read
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO app_settings (key, value) VALUES
+             ('daily_review_limit', '100'),
+             ('daily_new_cards', '10'),
+             ('day_rollover_hour', '4');"
+    )
+}
+
+fn study_day_timezone(conn: &Connection) -> Result<()> {
+    // Empty means the system timezone, which is what study days used before
+    conn.execute_batch("INSERT OR IGNORE INTO app_settings (key, value) VALUES ('timezone', '');")
+}
+
+fn review_undo(conn: &Connection) -> Result<()> {
+    // Earlier answers have no saved state and can't be undone
+    add_column(conn, "review_log", "prior_state", "TEXT")
+}
+
+fn suspend_and_bury(conn: &Connection) -> Result<()> {
+    add_column(conn, "user_progress", "is_suspended", "BOOLEAN NOT NULL DEFAULT 0")?;
+    add_column(conn, "user_progress", "buried_until", "TIMESTAMP")?;
+    conn.execute_batch(
+        "DROP VIEW IF EXISTS character_progress;
+         CREATE VIEW character_progress AS
+             SELECT character_id,
+                    MAX(introduced) AS introduced,
+                    MIN(is_mastered) AS is_mastered,
+                    MIN(is_suspended) AS is_suspended,
+                    SUM(times_reviewed) AS times_reviewed,
+                    SUM(times_correct) AS times_correct,
+                    SUM(times_incorrect) AS times_incorrect,
+                    MIN(current_interval_days) AS current_interval_days,
+                    MIN(next_review_date) AS next_review_date
+             FROM user_progress
+             WHERE track = COALESCE((SELECT value FROM app_settings WHERE key = 'study_track'), 'mandarin')
+             GROUP BY character_id;"
+    )
+}
+
+fn leech_tracking(conn: &Connection) -> Result<()> {
+    add_column(conn, "user_progress", "lapses", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column(conn, "user_progress", "is_leech", "BOOLEAN NOT NULL DEFAULT 0")?;
+    // Count past lapses from the review log: failures of cards scheduled at least a day out
+    conn.execute_batch(
+        "UPDATE user_progress SET lapses = (
+             SELECT COUNT(*) FROM review_log r
+             WHERE r.character_id = user_progress.character_id
+               AND r.track = user_progress.track
+               AND COALESCE(r.direction, 'zh_to_en') = user_progress.direction
+               AND r.is_correct = 0
+               AND r.interval_before >= 1.0);
+         UPDATE user_progress SET is_leech = 1 WHERE lapses >= 8;
+         INSERT OR IGNORE INTO app_settings (key, value) VALUES
+             ('leech_threshold', '8'),
+             ('leech_auto_suspend', 'false');"
+    )
+}
+
+fn maintenance_reviews(conn: &Connection) -> Result<()> {
+    add_column(conn, "user_progress", "correct_streak", "INTEGER NOT NULL DEFAULT 0")?;
+    // Correct answers since the card's last failure
+    conn.execute_batch(
+        "UPDATE user_progress SET correct_streak = (
+             SELECT COUNT(*) FROM review_log r
+             WHERE r.character_id = user_progress.character_id
+               AND r.track = user_progress.track
+               AND COALESCE(r.direction, 'zh_to_en') = user_progress.direction
+               AND r.is_correct = 1
+               AND r.id > COALESCE((SELECT MAX(e.id) FROM review_log e
+                                    WHERE e.character_id = r.character_id AND e.track = r.track
+                                      AND COALESCE(e.direction, 'zh_to_en') = COALESCE(r.direction, 'zh_to_en')
+                                      AND e.is_correct = 0), 0));
+         INSERT OR IGNORE INTO app_settings (key, value) VALUES
+             ('mastery_rule', 'total_correct'),
+             ('mastery_threshold', '9'),
+             ('maintenance_interval_days', '180');"
+    )?;
+    // Mastered cards used to drop out of reviews for good; schedule their first maintenance review
+    conn.execute(
+        "UPDATE user_progress
+         SET next_review_date = datetime(COALESCE(last_reviewed, 'now'), '+180 days'),
+             current_interval_days = MAX(current_interval_days, 180)
+         WHERE is_mastered = 1 AND next_review_date IS NULL",
+        []
+    )?;
+    Ok(())
+}
+
+fn introduction_timestamps(conn: &Connection) -> Result<()> {
+    // Cards introduced before this have none and don't count against today's new card limit
+    add_column(conn, "user_progress", "introduced_at", "TIMESTAMP")
+}
+
+fn mastery_after_demotion(conn: &Connection) -> Result<()> {
+    // times_correct when the card last failed a maintenance review; total_correct counts from there
+    add_column(conn, "user_progress", "times_correct_at_demotion", "INTEGER NOT NULL DEFAULT 0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database as the app's first release built it, with some study progress
+    fn fixture_v1() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("fixtures/schema_v1.sql")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word)
+             VALUES ('的', '的', '的', 'de5', 'possessive particle', 1, 0),
+                    ('漢', '汉', '漢', 'han4', 'Chinese', 400, 0);
+             INSERT INTO user_progress (character_id, current_interval_days, next_review_date, times_reviewed, introduced)
+             VALUES (1, 7.0, datetime('now', '+7 days'), 5, 1),
+                    (2, 0.0417, datetime('now'), 0, 0);"
+        ).unwrap();
+        conn
+    }
+
+    /// A database left at `version` by an older release
+    fn fixture_at(version: i32) -> Connection {
+        let conn = fixture_v1();
+        let steps: Vec<&Migration> = MIGRATIONS.iter().filter(|m| m.version <= version).collect();
+        for migration in steps {
+            (migration.apply)(&conn).unwrap();
+            conn.execute(
+                "INSERT INTO schema_version (version, description) VALUES (?1, ?2)",
+                rusqlite::params![migration.version, migration.name]
+            ).unwrap();
+        }
+        if version == latest_version() {
+            refresh_derived_data(&conn).unwrap();
+        }
+        assert_eq!(current_version(&conn).unwrap(), version);
+        conn
+    }
+
+    #[test]
+    fn test_versions_are_ordered_and_unique() {
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[1].version, pair[0].version + 1);
+        }
+        assert_eq!(MIGRATIONS[0].version, 2);
+    }
+
+    #[test]
+    fn test_upgrade_from_every_historical_version() {
+        for version in 1..=latest_version() {
+            let conn = fixture_at(version);
+            let applied = run_migrations(&conn).unwrap();
+
+            assert_eq!(applied as i32, latest_version() - version, "from version {}", version);
+            assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+            // Progress survives and the newest schema is usable
+            let (interval, introduced, mastered, direction, track): (f64, bool, bool, String, String) = conn.query_row(
+                "SELECT current_interval_days, introduced, is_mastered, direction, track
+                 FROM user_progress WHERE character_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            ).unwrap();
+            assert_eq!(
+                (interval, introduced, mastered, direction.as_str(), track.as_str()),
+                (7.0, true, false, "zh_to_en", "mandarin")
+            );
+            let (stability, difficulty): (f64, f64) = conn.query_row(
+                "SELECT stability, difficulty FROM user_progress WHERE character_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?))
+            ).unwrap();
+            assert_eq!(stability, 7.0);
+            assert!((difficulty - 3.684).abs() < 0.001, "from version {}: {}", version, difficulty);
+
+            let senses: i32 = conn.query_row("SELECT COUNT(*) FROM senses", [], |row| row.get(0)).unwrap();
+            assert_eq!(senses, 2, "from version {}", version);
+            let hits: i32 = conn.query_row(
+                "SELECT COUNT(*) FROM dictionary_fts WHERE dictionary_fts MATCH 'hanzi : 漢'",
+                [],
+                |row| row.get(0)
+            ).unwrap();
+            assert_eq!(hits, 1);
+            let marked: String = conn.query_row(
+                "SELECT pinyin_marked FROM characters WHERE character = '漢'",
+                [],
+                |row| row.get(0)
+            ).unwrap();
+            assert_eq!(marked, "hàn");
+
+            // Running again is a no-op
+            assert_eq!(run_migrations(&conn).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn test_current_build_schema_upgrades() {
+        // Freshly built databases already contain some later tables
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../../../data-processing/schema.sql")).unwrap();
+        run_migrations(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back_and_stops() {
+        fn broken(conn: &Connection) -> Result<()> {
+            conn.execute("CREATE TABLE half_done (id INTEGER)", [])?;
+            conn.execute("INSERT INTO no_such_table VALUES (1)", [])?;
+            Ok(())
+        }
+        fn never_reached(conn: &Connection) -> Result<()> {
+            conn.execute("CREATE TABLE after_failure (id INTEGER)", [])?;
+            Ok(())
+        }
+        let migrations = [
+            Migration { version: 2, name: "Works", apply: time_based_introduction },
+            Migration { version: 3, name: "Broken", apply: broken },
+            Migration { version: 4, name: "Never reached", apply: never_reached },
+        ];
+
+        let conn = fixture_v1();
+        assert!(apply_migrations(&conn, &migrations).is_err());
+        assert_eq!(current_version(&conn).unwrap(), 2);
+
+        let leftovers: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name IN ('half_done', 'after_failure')",
+            [],
+            |row| row.get(0)
+        ).unwrap();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_dry_run_leaves_database_untouched() {
+        let conn = fixture_at(4);
+        let versions = dry_run(&conn).unwrap();
+
+        assert_eq!(versions, (5..=latest_version()).collect::<Vec<_>>());
+        assert_eq!(current_version(&conn).unwrap(), 4);
+        let senses_table: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = 'senses'", [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(senses_table, 0);
+    }
+
+    #[test]
+    fn test_backup_is_a_copy_of_the_old_version() {
+        let dir = std::env::temp_dir().join(format!("chinese-flashcards-backup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("chinese.db");
+
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(include_str!("fixtures/schema_v1.sql")).unwrap();
+        let backup_path = backup_before_migrating(&conn, &db_path).unwrap();
+        run_migrations(&conn).unwrap();
+
+        assert_eq!(backup_path, dir.join("chinese.db.v1.bak"));
+        let backup = Connection::open(&backup_path).unwrap();
+        assert_eq!(current_version(&backup).unwrap(), 1);
+
+        // A backup path that can't be replaced is reported as a file error
+        std::fs::create_dir_all(dir.join(format!("chinese.db.v{}.bak", latest_version()))).unwrap();
+        let error = backup_before_migrating(&conn, &db_path).unwrap_err();
+        assert!(error.to_string().contains("Can't replace the old backup"), "{}", error);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
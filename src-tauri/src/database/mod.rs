@@ -1,11 +1,17 @@
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, OptionalExtension, Result};
 use std::sync::Mutex;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
-use crate::srs::{SrsCard, calculate_next_review};
+use data_processing::srs::{is_known_mastery_rule, Grade, MasteryPolicy, SrsCard, Scheduler};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc, Timelike};
 
+mod dictionary;
+mod migrations;
+mod study_day;
+pub use migrations::run_migrations;
+pub use study_day::{get_study_day_settings, is_valid_timezone, set_study_day_settings, StudyClock, StudyDaySettings};
+
 pub struct DbConnection(pub Mutex<Connection>);
 
 pub fn initialize_database() -> Result<DbConnection> {
@@ -80,12 +86,27 @@ pub fn initialize_database() -> Result<DbConnection> {
     let conn = Connection::open(&user_db_path)?;
     println!("[DB] Database opened successfully");
 
-    // Run migrations
-    run_migrations(&conn)?;
+    // Run migrations: back up first, and only migrate if a dry run succeeds
+    if !migrations::pending_migrations(&conn)?.is_empty() {
+        migrations::backup_before_migrating(&conn, &user_db_path)?;
+        migrations::dry_run(&conn)?;
+        run_migrations(&conn)?;
+    }
+
+    // Bring in dictionary fixes from a newer bundled database, keeping the user's progress.
+    // (In development the master and user database can be the same file.)
+    let same_file = fs::canonicalize(&master_db_path).ok() == fs::canonicalize(&user_db_path).ok();
+    if master_db_path.exists() && !same_file {
+        if let Some(version) = dictionary::outdated_dictionary_version(&conn, &master_db_path)? {
+            backup_database(&conn, &user_db_path, "dictionary")?;
+            dictionary::refresh_dictionary(&conn, &master_db_path, &version)?;
+        }
+    }
 
     // Initialize new user with first 30 characters if this is a new database
     println!("[DB] Checking if initial unlock completed...");
-    let initial_unlock_completed = get_setting(&conn, "initial_unlock_completed")
+    let track = get_study_track(&conn)?;
+    let initial_unlock_completed = get_setting(&conn, &track_setting_key(&track, "initial_unlock_completed"))
         .unwrap_or_else(|e| {
             println!("[DB] Error getting initial_unlock_completed setting: {}", e);
             "false".to_string()
@@ -109,77 +130,161 @@ pub fn initialize_database() -> Result<DbConnection> {
     Ok(DbConnection(Mutex::new(conn)))
 }
 
-fn run_migrations(conn: &Connection) -> Result<()> {
-    // Get current schema version
-    let version: i32 = conn
-        .query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))
-        .unwrap_or_else(|e| {
-            println!("[DB] Warning: Could not read schema_version, assuming version 0: {}", e);
-            0
-        });
+/// Copy the database to `<name>.<tag>.bak` next to it, replacing an older copy.
+/// Uses VACUUM INTO so the copy is consistent even though the connection is open.
+pub fn backup_database(conn: &Connection, db_path: &Path, tag: &str) -> Result<PathBuf> {
+    let file_name = db_path.file_name().and_then(|n| n.to_str()).unwrap_or("chinese.db");
+    let backup_path = db_path.with_file_name(format!("{}.{}.bak", file_name, tag));
+
+    if backup_path.exists() {
+        fs::remove_file(&backup_path).map_err(|e| {
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
+                Some(format!("Can't replace the old backup {:?}: {}", backup_path, e)),
+            )
+        })?;
+    }
+    conn.execute("VACUUM INTO ?1", [backup_path.to_string_lossy()])?;
 
-    println!("[DB] Current schema version: {}", version);
+    println!("[DB] Backed up database to {:?}", backup_path);
+    Ok(backup_path)
+}
 
-    // Migration 2: Add time-based character introduction settings
-    if version < 2 {
-        println!("[DB] Running migration 2: Time-based character introduction");
+#[derive(serde::Serialize)]
+pub struct Character {
+    pub id: i32,
+    pub character: String,
+    pub simplified: String,
+    pub traditional: Option<String>,
+    pub mandarin_pinyin: String,
+    pub definition: String,
+    pub frequency_rank: i32,
+    pub is_word: bool,
+    // Every dictionary entry for this character (only loaded by get_character_by_id)
+    pub senses: Vec<Sense>,
+}
 
-        let result = conn.execute(
-            "INSERT OR IGNORE INTO app_settings (key, value) VALUES
-             ('last_unlock_date', ''),
-             ('initial_unlock_completed', 'false')",
-            []
-        );
+/// One dictionary entry (reading + definitions) for a character or word
+#[derive(serde::Serialize)]
+pub struct Sense {
+    pub id: i32,
+    pub traditional: Option<String>,
+    pub pinyin: String,
+    // Reading with tone marks ("hàn zì")
+    pub pinyin_marked: String,
+    pub definition: String,
+    pub source: String,
+    pub source_line: Option<i64>,
+}
 
-        match result {
-            Ok(rows) => println!("[DB] Migration 2: Inserted {} app_settings rows", rows),
-            Err(e) => println!("[DB] Migration 2 warning: Error inserting app_settings: {}", e),
-        }
+/// Get all senses of a character, in dictionary order
+pub fn get_senses(conn: &Connection, character_id: i32) -> Result<Vec<Sense>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, traditional, pinyin, definition, source, source_line
+         FROM senses
+         WHERE character_id = ?1
+         ORDER BY source, source_line, id"
+    )?;
 
-        conn.execute(
-            "INSERT INTO schema_version (version, description) VALUES (2, 'Time-based character introduction')",
-            []
+    let senses = stmt.query_map([character_id], |row| {
+        Ok(Sense {
+            id: row.get(0)?,
+            traditional: row.get(1)?,
+            pinyin: row.get(2)?,
+            pinyin_marked: data_processing::pinyin::to_marked(&row.get::<_, String>(2)?),
+            definition: row.get(3)?,
+            source: row.get(4)?,
+            source_line: row.get(5)?,
+        })
+    })?;
+
+    senses.collect()
+}
+
+/// Everything a typed answer for a character may be compared against: its forms and every sense
+pub fn get_answer_key(conn: &Connection, character_id: i32) -> Result<crate::grading::AnswerKey> {
+    let (simplified, traditional, primary_pinyin, jyutping, definition): (String, Option<String>, String, Option<String>, String) =
+        conn.query_row(
+            "SELECT simplified, traditional, mandarin_pinyin, cantonese_jyutping, definition
+             FROM characters WHERE id = ?1",
+            [character_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
         )?;
 
-        println!("[DB] Migration 2 completed");
+    let senses = get_senses(conn, character_id)?;
+    let mut readings = vec![primary_pinyin];
+    for sense in &senses {
+        if !readings.contains(&sense.pinyin) {
+            readings.push(sense.pinyin.clone());
+        }
     }
+    let definitions = if senses.is_empty() {
+        vec![definition]
+    } else {
+        senses.into_iter().map(|s| s.definition).collect()
+    };
 
-    // Migration 3: Add mastery tracking
-    if version < 3 {
-        println!("[DB] Running migration 3: Add mastery tracking");
+    Ok(crate::grading::AnswerKey { simplified, traditional, readings, jyutping, definitions })
+}
 
-        conn.execute(
-            "ALTER TABLE user_progress ADD COLUMN is_mastered BOOLEAN DEFAULT 0",
-            []
-        )?;
+/// Stroke order and structure of one character (Make Me a Hanzi)
+#[derive(Debug, serde::Serialize)]
+pub struct CharacterDetails {
+    pub character_id: i32,
+    pub character: String,
+    pub stroke_count: Option<i32>,
+    pub radical: Option<String>,
+    pub decomposition: Option<String>,
+    // { type, hint, phonetic, semantic }
+    pub etymology: Option<serde_json::Value>,
+    // SVG path per stroke, in stroke order, on a 1024×1024 grid (y axis flipped)
+    pub strokes: Vec<String>,
+    // Points along each stroke, for animating it
+    pub medians: Vec<Vec<[i32; 2]>>,
+}
 
-        println!("[DB] Migration 3: Added is_mastered column");
+/// Details for every character of an entry: one item for a character, one per character for a word
+pub fn get_character_details(conn: &Connection, id: i32) -> Result<Vec<CharacterDetails>> {
+    let text: String = conn.query_row("SELECT character FROM characters WHERE id = ?1", [id], |row| row.get(0))?;
 
-        conn.execute(
-            "INSERT INTO schema_version (version, description) VALUES (3, 'Add mastery tracking')",
-            []
-        )?;
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.character, c.stroke_count, c.radical, c.decomposition, c.etymology,
+                s.strokes, s.medians
+         FROM characters c
+         LEFT JOIN character_strokes s ON s.character_id = c.id
+         WHERE c.character = ?1 AND c.is_word = 0"
+    )?;
 
-        println!("[DB] Migration 3 completed");
-    }
+    let mut details = Vec::new();
+    for hanzi in text.chars() {
+        let found = stmt.query_row([hanzi.to_string()], |row| {
+            let etymology: Option<String> = row.get(5)?;
+            let strokes: Option<String> = row.get(6)?;
+            let medians: Option<String> = row.get(7)?;
+            Ok(CharacterDetails {
+                character_id: row.get(0)?,
+                character: row.get(1)?,
+                stroke_count: row.get(2)?,
+                radical: row.get(3)?,
+                decomposition: row.get(4)?,
+                etymology: etymology.and_then(|e| serde_json::from_str(&e).ok()),
+                strokes: strokes.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default(),
+                medians: medians.and_then(|m| serde_json::from_str(&m).ok()).unwrap_or_default(),
+            })
+        });
 
-    Ok(())
-}
+        match found {
+            Ok(character) => details.push(character),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {} // Not in the dictionary (e.g. punctuation)
+            Err(e) => return Err(e),
+        }
+    }
 
-#[derive(serde::Serialize)]
-pub struct Character {
-    pub id: i32,
-    pub character: String,
-    pub simplified: String,
-    pub traditional: Option<String>,
-    pub mandarin_pinyin: String,
-    pub definition: String,
-    pub frequency_rank: i32,
-    pub is_word: bool,
+    Ok(details)
 }
 
 pub fn get_character_by_id(conn: &Connection, id: i32) -> Result<Character> {
-    conn.query_row(
+    let mut character = conn.query_row(
         "SELECT id, character, simplified, traditional, mandarin_pinyin,
                 definition, frequency_rank, is_word
          FROM characters WHERE id = ?1",
@@ -194,9 +299,13 @@ pub fn get_character_by_id(conn: &Connection, id: i32) -> Result<Character> {
                 definition: row.get(5)?,
                 frequency_rank: row.get(6)?,
                 is_word: row.get(7)?,
+                senses: Vec::new(),
             })
         }
-    )
+    )?;
+
+    character.senses = get_senses(conn, id)?;
+    Ok(character)
 }
 
 pub fn get_characters_by_frequency(conn: &Connection, limit: usize) -> Result<Vec<Character>> {
@@ -219,6 +328,7 @@ pub fn get_characters_by_frequency(conn: &Connection, limit: usize) -> Result<Ve
             definition: row.get(5)?,
             frequency_rank: row.get(6)?,
             is_word: row.get(7)?,
+            senses: Vec::new(),
         })
     })?;
 
@@ -230,168 +340,1487 @@ pub fn get_characters_by_frequency(conn: &Connection, limit: usize) -> Result<Ve
 #[derive(serde::Serialize)]
 pub struct DueCard {
     pub character_id: i32,
+    pub direction: String,
+    pub character: String,
+    pub pinyin: String,
+    pub definition: String,
+    pub current_interval: f32,
+    pub times_reviewed: i32,
+}
+
+// === Card Directions ===
+
+/// Direction used when the caller doesn't name one (the original single-card behaviour)
+pub const DEFAULT_DIRECTION: &str = "zh_to_en";
+
+/// All directions a character can be tested in (same names as practice_history.arrow_tested)
+pub const CARD_DIRECTIONS: [&str; 4] = ["zh_to_en", "zh_to_pinyin", "en_to_zh", "pinyin_to_zh"];
+
+/// SQL condition limiting `user_progress p` to the directions enabled in settings
+pub const ENABLED_DIRECTION_FILTER: &str =
+    "instr(',' || COALESCE((SELECT value FROM app_settings WHERE key = 'card_directions'), 'zh_to_en') || ',',
+           ',' || p.direction || ',') > 0";
+
+/// SQL condition leaving out suspended cards and cards buried until a later study day
+pub const CARD_AVAILABLE_FILTER: &str =
+    "p.is_suspended = 0 AND (p.buried_until IS NULL OR p.buried_until <= datetime('now'))";
+
+pub fn is_known_direction(direction: &str) -> bool {
+    CARD_DIRECTIONS.contains(&direction)
+}
+
+/// Directions enabled in app_settings (stored comma-separated), in a stable order
+pub fn get_card_directions(conn: &Connection) -> Result<Vec<String>> {
+    let value = get_setting(conn, "card_directions")
+        .unwrap_or_else(|_| DEFAULT_DIRECTION.to_string());
+
+    let directions: Vec<String> = CARD_DIRECTIONS
+        .iter()
+        .filter(|d| value.split(',').any(|v| v.trim() == **d))
+        .map(|d| d.to_string())
+        .collect();
+
+    if directions.is_empty() {
+        Ok(vec![DEFAULT_DIRECTION.to_string()])
+    } else {
+        Ok(directions)
+    }
+}
+
+/// Save the enabled directions and create cards for any newly enabled direction.
+/// Disabled directions keep their progress, they just stop coming up for review.
+/// New cards are suspended or buried along with the character's other cards. The new cards of
+/// introduced characters come due a daily new-card limit at a time, in the order the characters
+/// were introduced, so enabling a direction doesn't make every known character due at once.
+/// Returns the number of cards created.
+pub fn set_card_directions(conn: &Connection, directions: &[String]) -> Result<usize> {
+    set_setting(conn, "card_directions", &directions.join(","))?;
+    let per_day = get_daily_limits(conn)?.new_card_limit.max(1);
+
+    let mut created = 0;
+    for direction in get_card_directions(conn)? {
+        created += conn.execute(
+            "INSERT OR IGNORE INTO user_progress
+             (character_id, track, direction, current_interval_days, previous_interval_days,
+              next_review_date, introduced, introduced_at, is_suspended, buried_until)
+             SELECT character_id, track, ?1, 0.0417, 0.0417,
+                    CASE WHEN MAX(introduced) THEN datetime('now', '+' || (
+                        (ROW_NUMBER() OVER (PARTITION BY track, MAX(introduced)
+                                            ORDER BY MIN(introduced_at), character_id) - 1) / ?2
+                    ) || ' days') ELSE datetime('now') END,
+                    MAX(introduced), MIN(introduced_at), MAX(is_suspended), MAX(buried_until)
+             FROM user_progress
+             GROUP BY track, character_id",
+            rusqlite::params![direction, per_day]
+        )?;
+    }
+
+    println!("[DB] Card directions set to {:?}, created {} cards", directions, created);
+    Ok(created)
+}
+
+/// Add a character to the active track's user_progress with one card per enabled direction
+pub fn insert_progress_rows(conn: &Connection, character_id: i32, introduced: bool) -> Result<()> {
+    let track = get_study_track(conn)?;
+
+    // Start with 1 hour interval (0.0417 days)
+    for direction in get_card_directions(conn)? {
+        conn.execute(
+            "INSERT OR IGNORE INTO user_progress
+             (character_id, track, direction, current_interval_days, previous_interval_days,
+              next_review_date, introduced, introduced_at)
+             VALUES (?1, ?2, ?3, 0.0417, 0.0417, datetime('now'), ?4, CASE WHEN ?4 THEN datetime('now') END)",
+            rusqlite::params![character_id, track, direction, introduced]
+        )?;
+    }
+
+    // Unlocked now, so it no longer needs pulling forward
+    conn.execute(
+        "DELETE FROM pull_forward_queue WHERE track = ?1 AND character_id = ?2",
+        rusqlite::params![track, character_id]
+    )?;
+    Ok(())
+}
+
+// === Study Tracks ===
+
+/// Track used when none is set (all progress from before tracks existed is Mandarin)
+pub const DEFAULT_TRACK: &str = "mandarin";
+
+/// Languages a user can study. Each track has its own cards, schedule and introduction order.
+pub const TRACKS: [&str; 2] = ["mandarin", "cantonese"];
+
+macro_rules! active_track_sql {
+    () => {
+        "COALESCE((SELECT value FROM app_settings WHERE key = 'study_track'), 'mandarin')"
+    };
+}
+
+/// SQL expression for the track selected in settings
+pub const ACTIVE_TRACK: &str = active_track_sql!();
+
+/// SQL condition limiting `user_progress p` to the active track
+pub const ACTIVE_TRACK_FILTER: &str = concat!("p.track = ", active_track_sql!());
+
+/// SQL condition for `characters c` the active track can still unlock: not in its progress yet,
+/// and for Cantonese only entries with a Jyutping reading
+pub const TRACK_CANDIDATE_FILTER: &str = concat!(
+    "NOT EXISTS (SELECT 1 FROM user_progress p WHERE p.character_id = c.id AND p.track = ",
+    active_track_sql!(),
+    ") AND (c.cantonese_jyutping IS NOT NULL OR ",
+    active_track_sql!(),
+    " != 'cantonese')"
+);
+
+/// SQL condition for `characters c` waiting in the active track's pull-forward queue
+pub const TRACK_PULLED_FORWARD: &str = concat!(
+    "EXISTS (SELECT 1 FROM pull_forward_queue q WHERE q.character_id = c.id AND q.track = ",
+    active_track_sql!(),
+    ")"
+);
+
+/// SQL ORDER BY terms for unlocking `characters c`: the pull-forward queue, the track's
+/// configured list, then frequency
+pub const TRACK_INTRODUCTION_ORDER: &str = concat!(
+    "COALESCE((SELECT q.position FROM pull_forward_queue q WHERE q.character_id = c.id AND q.track = ",
+    active_track_sql!(),
+    "), 2147483647) ASC, ",
+    "COALESCE((SELECT o.position FROM track_introduction_order o WHERE o.character_id = c.id AND o.track = ",
+    active_track_sql!(),
+    "), 2147483647) ASC, c.frequency_rank ASC"
+);
+
+/// SQL expression for the reading shown on a card of `user_progress p` for `characters c`
+pub const TRACK_READING: &str =
+    "CASE WHEN p.track = 'cantonese' THEN COALESCE(c.cantonese_jyutping, c.mandarin_pinyin) ELSE c.mandarin_pinyin END";
+
+pub fn is_known_track(track: &str) -> bool {
+    TRACKS.contains(&track)
+}
+
+pub fn get_study_track(conn: &Connection) -> Result<String> {
+    let track = get_setting(conn, "study_track").unwrap_or_else(|_| DEFAULT_TRACK.to_string());
+    if is_known_track(&track) {
+        Ok(track)
+    } else {
+        Ok(DEFAULT_TRACK.to_string())
+    }
+}
+
+/// Switch tracks. The other track's cards are untouched, they just stop coming up.
+pub fn set_study_track(conn: &Connection, track: &str) -> Result<()> {
+    set_setting(conn, "study_track", track)?;
+    println!("[DB] Study track set to {}", track);
+    Ok(())
+}
+
+/// Settings key for a per-track value such as last_unlock_date (Mandarin keeps the original keys)
+pub fn track_setting_key(track: &str, key: &str) -> String {
+    if track == DEFAULT_TRACK {
+        key.to_string()
+    } else {
+        format!("{}_{}", key, track)
+    }
+}
+
+/// Result of replacing a track's introduction order
+#[derive(Debug, serde::Serialize)]
+pub struct TrackOrderReport {
+    pub track: String,
+    pub matched: usize,
+    // Entries with no dictionary match (simplified or traditional), in list order
+    pub unmatched: Vec<String>,
+}
+
+/// Replace the order a track unlocks entries in, most important first (e.g. a Cantonese
+/// frequency list). Entries are matched by simplified or traditional form; anything not on the
+/// list is unlocked afterwards by frequency. An empty list restores frequency order.
+pub fn set_track_introduction_order(conn: &Connection, track: &str, entries: &[String]) -> Result<TrackOrderReport> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM track_introduction_order WHERE track = ?1", [track])?;
+
+    let mut matched = 0;
+    let mut unmatched = Vec::new();
+    {
+        let mut find = tx.prepare(
+            "SELECT id FROM characters
+             WHERE character = ?1 OR traditional = ?1
+             ORDER BY character = ?1 DESC, frequency_rank ASC
+             LIMIT 1"
+        )?;
+        let mut insert = tx.prepare(
+            "INSERT OR IGNORE INTO track_introduction_order (track, character_id, position)
+             VALUES (?1, ?2, ?3)"
+        )?;
+
+        for entry in entries.iter().map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            match find.query_row([entry], |row| row.get::<_, i32>(0)) {
+                Ok(character_id) => {
+                    matched += insert.execute(rusqlite::params![track, character_id, matched as i64])?;
+                }
+                Err(rusqlite::Error::QueryReturnedNoRows) => unmatched.push(entry.to_string()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    tx.commit()?;
+
+    println!("[DB] Introduction order for {}: {} entries, {} unmatched", track, matched, unmatched.len());
+    Ok(TrackOrderReport { track: track.to_string(), matched, unmatched })
+}
+
+// === Pull-Forward Queue ===
+
+/// Result of pulling entries forward in the active track
+#[derive(Debug, serde::Serialize)]
+pub struct PullForwardReport {
+    // Entries added to the queue, including component characters
+    pub queued: usize,
+    // Component characters added ahead of the words that need them
+    pub components: usize,
+    // Requested entries already unlocked, already queued, or not studyable in this track
+    pub skipped: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct QueuedEntry {
+    pub character_id: i32,
+    pub character: String,
+    pub mandarin_pinyin: String,
+    pub definition: String,
+    pub is_word: bool,
+    pub position: i64,
+}
+
+/// Queue entries (e.g. the unseen words of a text) to be unlocked before anything else in the
+/// active track, in the given order. Component characters a word needs that aren't unlocked yet
+/// are queued just ahead of it. Unlocking still waits for the unlock cooldown and an empty
+/// ready-to-learn queue; the queue only changes what comes next.
+pub fn pull_forward(conn: &Connection, character_ids: &[i32]) -> Result<PullForwardReport> {
+    let track = get_study_track(conn)?;
+    let tx = conn.unchecked_transaction()?;
+
+    let mut position: i64 = tx.query_row(
+        "SELECT COALESCE(MAX(position) + 1, 0) FROM pull_forward_queue WHERE track = ?1",
+        [&track],
+        |row| row.get(0)
+    )?;
+    let mut report = PullForwardReport { queued: 0, components: 0, skipped: 0 };
+    {
+        let mut components_of = tx.prepare("SELECT component_characters FROM characters WHERE id = ?1 AND is_word = 1")?;
+        let mut eligible = tx.prepare(&format!(
+            "SELECT COUNT(*) > 0 FROM characters c WHERE c.id = ?1 AND {}",
+            TRACK_CANDIDATE_FILTER
+        ))?;
+        let mut insert = tx.prepare(
+            "INSERT OR IGNORE INTO pull_forward_queue (track, character_id, position) VALUES (?1, ?2, ?3)"
+        )?;
+
+        let mut enqueue = |character_id: i32| -> Result<bool> {
+            if !eligible.query_row([character_id], |row| row.get::<_, bool>(0))? {
+                return Ok(false);
+            }
+            let added = insert.execute(rusqlite::params![track, character_id, position])? > 0;
+            if added {
+                position += 1;
+            }
+            Ok(added)
+        };
+
+        for &character_id in character_ids {
+            // component_characters is a comma-separated list of character ids
+            let components: Option<String> = components_of
+                .query_row([character_id], |row| row.get(0))
+                .optional()?
+                .flatten();
+            for component_id in components
+                .iter()
+                .flat_map(|list| list.split(','))
+                .filter_map(|id| id.trim().parse::<i32>().ok())
+                .filter(|id| *id != character_id)
+            {
+                if enqueue(component_id)? {
+                    report.components += 1;
+                    report.queued += 1;
+                }
+            }
+
+            if enqueue(character_id)? {
+                report.queued += 1;
+            } else {
+                report.skipped += 1;
+            }
+        }
+    }
+    tx.commit()?;
+
+    println!("[DB] Pulled forward {} entries ({} components, {} skipped) in {}",
+             report.queued, report.components, report.skipped, track);
+    Ok(report)
+}
+
+/// Entries of the active track still waiting in the pull-forward queue, next first
+pub fn get_pull_forward_queue(conn: &Connection) -> Result<Vec<QueuedEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id, c.character, c.mandarin_pinyin, c.definition, c.is_word, q.position
+         FROM pull_forward_queue q
+         JOIN characters c ON c.id = q.character_id
+         WHERE q.track = {}
+         ORDER BY q.position ASC",
+        ACTIVE_TRACK
+    ))?;
+
+    let entries = stmt.query_map([], |row| {
+        Ok(QueuedEntry {
+            character_id: row.get(0)?,
+            character: row.get(1)?,
+            mandarin_pinyin: row.get(2)?,
+            definition: row.get(3)?,
+            is_word: row.get(4)?,
+            position: row.get(5)?,
+        })
+    })?;
+
+    entries.collect()
+}
+
+/// Empty the active track's pull-forward queue; unlocking goes back to the normal order
+pub fn clear_pull_forward_queue(conn: &Connection) -> Result<usize> {
+    let cleared = conn.execute(
+        &format!("DELETE FROM pull_forward_queue WHERE track = {}", ACTIVE_TRACK),
+        []
+    )?;
+    println!("[DB] Cleared {} entries from the pull-forward queue", cleared);
+    Ok(cleared)
+}
+
+/// Number of cards in the active track due for review now
+pub fn get_due_count(conn: &Connection) -> Result<usize> {
+    let now: String = conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?;
+    count_due_before(conn, &now)
+}
+
+/// Cards that come due before the current local study day ends, including overdue ones
+pub fn get_due_today_count(conn: &Connection) -> Result<usize> {
+    count_due_before(conn, &StudyClock::from_settings(conn)?.today_end())
+}
+
+fn count_due_before(conn: &Connection, cutoff: &str) -> Result<usize> {
+    conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM user_progress p
+             WHERE p.introduced = 1
+               AND p.next_review_date <= ?1
+               AND {}
+               AND {}
+               AND {}",
+            ENABLED_DIRECTION_FILTER, ACTIVE_TRACK_FILTER, CARD_AVAILABLE_FILTER
+        ),
+        [cutoff],
+        |row| row.get(0)
+    )
+}
+
+pub fn get_due_cards(conn: &Connection) -> Result<Vec<DueCard>> {
+    // Debug: Log current time and due cards
+    let now: String = conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?;
+    println!("[DB] Current time (UTC): {}", now);
+
+    let due_count = get_due_count(conn)?;
+    println!("[DB] Cards due for review: {}", due_count);
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id, p.direction, c.character, {}, c.definition,
+                p.current_interval_days, p.times_reviewed
+         FROM characters c
+         JOIN user_progress p ON c.id = p.character_id
+         WHERE p.introduced = 1
+           AND p.next_review_date <= datetime('now')
+           AND {}
+           AND {}
+           AND {}
+         ORDER BY p.next_review_date ASC",
+        TRACK_READING, ENABLED_DIRECTION_FILTER, ACTIVE_TRACK_FILTER, CARD_AVAILABLE_FILTER
+    ))?;
+
+    let cards = stmt.query_map([], due_card_from_row)?;
+
+    cards.collect()
+}
+
+/// Map a row of (id, direction, character, pinyin, definition, interval, times_reviewed)
+fn due_card_from_row(row: &rusqlite::Row) -> Result<DueCard> {
+    Ok(DueCard {
+        character_id: row.get(0)?,
+        direction: row.get(1)?,
+        character: row.get(2)?,
+        pinyin: row.get(3)?,
+        definition: row.get(4)?,
+        current_interval: row.get(5)?,
+        times_reviewed: row.get(6)?,
+    })
+}
+
+pub fn get_srs_card_state(conn: &Connection, character_id: i32, direction: &str) -> Result<SrsCard> {
+    conn.query_row(
+        &format!(
+            "SELECT character_id, current_interval_days, previous_interval_days,
+                    ease_factor, times_correct, times_incorrect, has_reached_week,
+                    stability, difficulty,
+                    julianday('now') - julianday(last_reviewed)
+             FROM user_progress
+             WHERE character_id = ?1 AND direction = ?2 AND track = {}",
+            ACTIVE_TRACK
+        ),
+        rusqlite::params![character_id, direction],
+        |row| {
+            Ok(SrsCard {
+                character_id: row.get(0)?,
+                current_interval_days: row.get(1)?,
+                previous_interval_days: row.get(2)?,
+                ease_factor: row.get(3)?,
+                times_correct: row.get(4)?,
+                times_incorrect: row.get(5)?,
+                has_reached_week: row.get(6)?,
+                stability: row.get(7)?,
+                difficulty: row.get(8)?,
+                elapsed_days: row.get::<_, Option<f64>>(9)?.map(|days| days as f32),
+            })
+        }
+    )
+}
+
+// === Scheduler Settings ===
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SrsSettings {
+    pub scheduler: String,
+    pub desired_retention: f32,
+}
+
+pub fn get_srs_settings(conn: &Connection) -> Result<SrsSettings> {
+    let scheduler = get_setting(conn, "srs_scheduler")
+        .unwrap_or_else(|_| data_processing::srs::DEFAULT_SCHEDULER.to_string());
+
+    let desired_retention = get_setting(conn, "desired_retention")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(data_processing::srs::DEFAULT_DESIRED_RETENTION);
+
+    Ok(SrsSettings { scheduler, desired_retention })
+}
+
+/// Save scheduler settings. Switching to FSRS re-seeds memory state from the current
+/// intervals, since the ladder doesn't keep stability/difficulty up to date.
+pub fn set_srs_settings(conn: &Connection, settings: &SrsSettings) -> Result<()> {
+    let previous = get_srs_settings(conn)?;
+
+    set_setting(conn, "srs_scheduler", &settings.scheduler)?;
+    set_setting(conn, "desired_retention", &settings.desired_retention.to_string())?;
+
+    if settings.scheduler == "fsrs" && previous.scheduler != "fsrs" {
+        let seeded = seed_fsrs_memory_state(conn)?;
+        println!("[DB] Switched to FSRS, seeded memory state for {} cards", seeded);
+    }
+
+    Ok(())
+}
+
+/// The scheduler currently selected in app_settings
+pub fn load_scheduler(conn: &Connection) -> Result<Box<dyn Scheduler>> {
+    let settings = get_srs_settings(conn)?;
+    Ok(data_processing::srs::scheduler_from_settings(&settings.scheduler, settings.desired_retention))
+}
+
+// === Daily Limits ===
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DailyLimits {
+    // Cards reviewed per study day; cards already reviewed today don't count again
+    pub review_limit: usize,
+    // Cards seen for the first time per study day
+    pub new_card_limit: usize,
+}
+
+pub fn get_daily_limits(conn: &Connection) -> Result<DailyLimits> {
+    Ok(DailyLimits {
+        review_limit: parsed_setting(conn, "daily_review_limit", 100),
+        new_card_limit: parsed_setting(conn, "daily_new_cards", 10),
+    })
+}
+
+pub fn set_daily_limits(conn: &Connection, limits: &DailyLimits) -> Result<()> {
+    set_setting(conn, "daily_review_limit", &limits.review_limit.to_string())?;
+    set_setting(conn, "daily_new_cards", &limits.new_card_limit.to_string())?;
+    println!("[DB] Daily limits set to {:?}", limits);
+    Ok(())
+}
+
+/// Start of the current study day as a SQLite UTC timestamp, for comparing with reviewed_at
+pub fn study_day_start(conn: &Connection) -> Result<String> {
+    Ok(StudyClock::from_settings(conn)?.today_start())
+}
+
+/// Cards of the active track answered since `day_start`, as (character_id, direction)
+fn cards_reviewed_since(conn: &Connection, day_start: &str) -> Result<std::collections::HashSet<(i32, String)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT DISTINCT character_id, COALESCE(direction, '{}') FROM review_log
+         WHERE reviewed_at >= ?1 AND track = {}",
+        DEFAULT_DIRECTION, ACTIVE_TRACK
+    ))?;
+    let cards = stmt.query_map([day_start], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    cards.collect()
+}
+
+/// Characters of the active track introduced since the current study day started
+pub fn get_new_cards_today(conn: &Connection) -> Result<usize> {
+    let day_start = study_day_start(conn)?;
+    conn.query_row(
+        &format!(
+            "SELECT COUNT(DISTINCT p.character_id) FROM user_progress p
+             WHERE p.introduced = 1 AND p.introduced_at >= ?1 AND {}",
+            ACTIVE_TRACK_FILTER
+        ),
+        [&day_start],
+        |row| row.get(0)
+    )
+}
+
+/// How many more new characters can be introduced today
+pub fn get_new_card_budget(conn: &Connection) -> Result<usize> {
+    let limits = get_daily_limits(conn)?;
+    Ok(limits.new_card_limit.saturating_sub(get_new_cards_today(conn)?))
+}
+
+#[derive(serde::Serialize)]
+pub struct ReviewQueue {
+    pub cards: Vec<DueCard>,
+    // Due cards left out because today's review limit is used up
+    pub held_back: usize,
+    pub reviewed_today: usize,
+    pub review_limit: usize,
+}
+
+/// Due cards capped by the daily review limit, most overdue first. Cards already reviewed
+/// today (e.g. relearning after a lapse) always come back; they've been counted once.
+pub fn get_review_queue(conn: &Connection) -> Result<ReviewQueue> {
+    let limits = get_daily_limits(conn)?;
+    let day_start = study_day_start(conn)?;
+    let reviewed = cards_reviewed_since(conn, &day_start)?;
+    let mut budget = limits.review_limit.saturating_sub(reviewed.len());
+
+    let mut cards = Vec::new();
+    let mut held_back = 0;
+    for card in get_due_cards(conn)? {
+        if reviewed.contains(&(card.character_id, card.direction.clone())) {
+            cards.push(card);
+        } else if budget > 0 {
+            budget -= 1;
+            cards.push(card);
+        } else {
+            held_back += 1;
+        }
+    }
+
+    if held_back > 0 {
+        println!("[DB] Daily review limit {} reached, holding back {} cards", limits.review_limit, held_back);
+    }
+    Ok(ReviewQueue { cards, held_back, reviewed_today: reviewed.len(), review_limit: limits.review_limit })
+}
+
+// === Mastery ===
+
+pub fn get_mastery_policy(conn: &Connection) -> Result<MasteryPolicy> {
+    let defaults = MasteryPolicy::default();
+    let rule = get_setting(conn, "mastery_rule")
+        .ok()
+        .filter(|rule| is_known_mastery_rule(rule))
+        .unwrap_or(defaults.rule);
+    Ok(MasteryPolicy {
+        rule,
+        threshold: parsed_setting(conn, "mastery_threshold", defaults.threshold),
+        maintenance_interval_days: parsed_setting(conn, "maintenance_interval_days", defaults.maintenance_interval_days),
+    })
+}
+
+/// Save the policy. Cards already mastered stay mastered; the rule applies to answers from now on.
+pub fn set_mastery_policy(conn: &Connection, policy: &MasteryPolicy) -> Result<()> {
+    set_setting(conn, "mastery_rule", &policy.rule)?;
+    set_setting(conn, "mastery_threshold", &policy.threshold.to_string())?;
+    set_setting(conn, "maintenance_interval_days", &policy.maintenance_interval_days.to_string())?;
+    println!("[DB] Mastery policy set to {:?}", policy);
+    Ok(())
+}
+
+// === Unlock Pacing ===
+
+/// How new characters are unlocked, stored in app_settings
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PacingPolicy {
+    // Characters unlocked when a track is started
+    pub initial_batch_size: usize,
+    // Characters unlocked per batch after that
+    pub batch_size: usize,
+    // Hours to wait after a batch is introduced before the next one
+    pub cooldown_hours: i64,
+    // Hold off unlocking while more than max_due_reviews cards are due
+    pub workload_aware: bool,
+    pub max_due_reviews: usize,
+}
+
+impl Default for PacingPolicy {
+    fn default() -> Self {
+        PacingPolicy {
+            initial_batch_size: 100,
+            batch_size: 10,
+            cooldown_hours: 48,
+            workload_aware: false,
+            max_due_reviews: 50,
+        }
+    }
+}
+
+fn parsed_setting<T: std::str::FromStr>(conn: &Connection, key: &str, default: T) -> T {
+    get_setting(conn, key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+pub fn get_pacing_policy(conn: &Connection) -> Result<PacingPolicy> {
+    let defaults = PacingPolicy::default();
+    Ok(PacingPolicy {
+        initial_batch_size: parsed_setting(conn, "initial_unlock_size", defaults.initial_batch_size),
+        batch_size: parsed_setting(conn, "unlock_batch_size", defaults.batch_size),
+        cooldown_hours: parsed_setting(conn, "unlock_cooldown_hours", defaults.cooldown_hours),
+        workload_aware: parsed_setting(conn, "workload_aware_unlock", defaults.workload_aware),
+        max_due_reviews: parsed_setting(conn, "unlock_max_due_reviews", defaults.max_due_reviews),
+    })
+}
+
+pub fn set_pacing_policy(conn: &Connection, policy: &PacingPolicy) -> Result<()> {
+    set_setting(conn, "initial_unlock_size", &policy.initial_batch_size.to_string())?;
+    set_setting(conn, "unlock_batch_size", &policy.batch_size.to_string())?;
+    set_setting(conn, "unlock_cooldown_hours", &policy.cooldown_hours.to_string())?;
+    set_setting(conn, "workload_aware_unlock", &policy.workload_aware.to_string())?;
+    set_setting(conn, "unlock_max_due_reviews", &policy.max_due_reviews.to_string())?;
+    println!("[DB] Pacing policy set to {:?}", policy);
+    Ok(())
+}
+
+/// Whether the workload-aware mode is holding back the next batch because too many reviews are due
+pub fn is_unlock_held_for_workload(conn: &Connection, policy: &PacingPolicy) -> Result<bool> {
+    if !policy.workload_aware {
+        return Ok(false);
+    }
+    Ok(get_due_count(conn)? > policy.max_due_reviews)
+}
+
+/// Hours left before the active track's cooldown allows another batch (0 once it has passed)
+fn unlock_cooldown_remaining(conn: &Connection, policy: &PacingPolicy) -> Result<i64> {
+    use chrono::NaiveDateTime;
+
+    let track = get_study_track(conn)?;
+    let last_unlock_str = get_setting(conn, &track_setting_key(&track, "last_unlock_date"))
+        .unwrap_or_else(|_| "".to_string());
+
+    if last_unlock_str.is_empty() {
+        // First unlock after the initial batch
+        return Ok(0);
+    }
+
+    // Parse from SQLite datetime format (YYYY-MM-DD HH:MM:SS)
+    match NaiveDateTime::parse_from_str(&last_unlock_str, "%Y-%m-%d %H:%M:%S") {
+        Ok(naive_dt) => {
+            let last_unlock = DateTime::<Utc>::from_naive_utc_and_offset(naive_dt, Utc);
+            let elapsed = Utc::now().signed_duration_since(last_unlock);
+            println!("[DB] Last unlock was {} hours ago", elapsed.num_hours());
+
+            // Round up so a cooldown that has barely started shows its full length
+            let remaining_minutes = policy.cooldown_hours * 60 - elapsed.num_minutes();
+            Ok(((remaining_minutes + 59) / 60).max(0))
+        }
+        Err(_) => {
+            // Invalid date format, allow unlock
+            println!("[DB] Invalid last_unlock_date format: {}", last_unlock_str);
+            Ok(0)
+        }
+    }
+}
+
+/// Estimate FSRS stability/difficulty for every introduced card from its ladder state
+fn seed_fsrs_memory_state(conn: &Connection) -> Result<usize> {
+    let mut stmt = conn.prepare(
+        "SELECT id, current_interval_days, ease_factor
+         FROM user_progress
+         WHERE introduced = 1"
+    )?;
+
+    let cards: Vec<(i32, f32, f32)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>>>()?;
+
+    for (id, interval, ease) in &cards {
+        let (stability, difficulty) = data_processing::srs::seed_memory_state(*interval, *ease);
+        conn.execute(
+            "UPDATE user_progress SET stability = ?1, difficulty = ?2 WHERE id = ?3",
+            rusqlite::params![stability, difficulty, id]
+        )?;
+    }
+
+    Ok(cards.len())
+}
+
+/// Round a datetime down to the nearest half-hour (0 or 30 minutes)
+pub fn round_down_to_half_hour(dt: DateTime<Utc>) -> DateTime<Utc> {
+    let minute = dt.minute();
+    let rounded_minute = if minute < 30 { 0 } else { 30 };
+
+    dt.with_minute(rounded_minute).unwrap()
+      .with_second(0).unwrap()
+      .with_nanosecond(0).unwrap()
+}
+
+pub fn record_srs_answer(
+    conn: &Connection,
+    character_id: i32,
+    grade: impl Into<Grade>,
+    direction: Option<&str>,
+    response_time_ms: Option<i64>,
+) -> Result<bool> {
+    let grade = grade.into();
+    let correct = grade.is_correct();
+    let direction = direction.unwrap_or(DEFAULT_DIRECTION);
+
+    // Get current card state (only this direction is rescheduled)
+    let card = get_srs_card_state(conn, character_id, direction)?;
+
+    // Remember the card as it was (for undo) and when it was actually due, for the review log
+    let prior = get_progress_snapshot(conn, character_id, direction)?;
+    let scheduled_at = prior.next_review_date.clone();
+    let prior_state = serde_json::to_string(&prior)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+    println!("[DB] record_srs_answer: char_id={}, direction={}, grade={:?}", character_id, direction, grade);
+    println!("[DB] Before: current_interval={}, previous_interval={}",
+             card.current_interval_days, card.previous_interval_days);
+
+    // Calculate new values with the scheduler selected in settings
+    let scheduler = load_scheduler(conn)?;
+    println!("[DB] Scheduling with {}", scheduler.name());
+    let mut update = scheduler.next_review(&card, grade);
+
+    // Mastery is checked after every correct answer; a failed answer always demotes the card,
+    // and a demoted card only counts correct answers given since then
+    let mastery = get_mastery_policy(conn)?;
+    let correct_streak = if correct { prior.correct_streak.unwrap_or(0) + 1 } else { 0 };
+    let times_correct_at_demotion = if prior.is_mastered && !correct {
+        card.times_correct
+    } else {
+        prior.times_correct_at_demotion.unwrap_or(0)
+    };
+    let is_mastered = correct
+        && (prior.is_mastered
+            || mastery.is_reached(card.times_correct + 1 - times_correct_at_demotion, correct_streak, update.new_interval_days));
+    if is_mastered && update.new_interval_days < mastery.maintenance_interval_days {
+        // Mastered cards only come back for infrequent maintenance reviews
+        update.new_interval_days = mastery.maintenance_interval_days;
+        update.next_review_date = Utc::now() + chrono::Duration::minutes((mastery.maintenance_interval_days * 1440.0) as i64);
+    }
+
+    // Round next review date to nearest half hour for cleaner scheduling
+    let next_review_rounded = round_down_to_half_hour(update.next_review_date);
+
+    println!("[DB] After calculation: new_interval={}, next_review={}, rounded={}",
+             update.new_interval_days, update.next_review_date, next_review_rounded);
+
+    // Convert to SQLite datetime format (YYYY-MM-DD HH:MM:SS)
+    // SQLite's datetime() function uses this format, and we need to match it for comparisons
+    let next_review_sqlite = next_review_rounded.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    // Update database
+    conn.execute(
+        &format!(
+            "UPDATE user_progress
+             SET previous_interval_days = current_interval_days,
+                 current_interval_days = ?1,
+                 ease_factor = ?2,
+                 next_review_date = ?3,
+                 times_reviewed = times_reviewed + 1,
+                 times_correct = times_correct + ?4,
+                 times_incorrect = times_incorrect + ?5,
+                 has_reached_week = has_reached_week OR ?6,
+                 stability = COALESCE(?7, stability),
+                 difficulty = COALESCE(?8, difficulty),
+                 is_mastered = ?11,
+                 correct_streak = ?12,
+                 times_correct_at_demotion = ?13,
+                 last_reviewed = datetime('now'),
+                 updated_at = datetime('now')
+             WHERE character_id = ?9 AND direction = ?10 AND track = {}",
+            ACTIVE_TRACK
+        ),
+        rusqlite::params![
+            update.new_interval_days,
+            update.new_ease_factor,
+            next_review_sqlite,
+            if correct { 1 } else { 0 },
+            if correct { 0 } else { 1 },
+            update.reached_week_for_first_time,
+            update.new_stability,
+            update.new_difficulty,
+            character_id,
+            direction,
+            is_mastered,
+            correct_streak,
+            times_correct_at_demotion,
+        ]
+    )?;
+
+    // Failing a card that had graduated to intervals of a day or more is a lapse; too many make it a leech
+    if !correct && card.current_interval_days >= 1.0 {
+        record_lapse(conn, character_id, direction)?;
+    }
+
+    if is_mastered && !prior.is_mastered {
+        println!("[SRS] Character {} ({}) has reached MASTERY ({} rule), next maintenance review in {} days",
+                 character_id, direction, mastery.rule, update.new_interval_days);
+    } else if prior.is_mastered && !correct {
+        println!("[SRS] Character {} ({}) failed a maintenance review and is back in regular reviews",
+                 character_id, direction);
+    }
+
+    // Keep a permanent record of this answer (user_progress only holds the latest state)
+    conn.execute(
+        &format!(
+            "INSERT INTO review_log
+             (character_id, track, direction, grade, is_correct, interval_before, interval_after,
+              ease_before, ease_after, scheduled_at, reviewed_at, response_time_ms, prior_state)
+             VALUES (?1, {}, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'), ?10, ?11)",
+            ACTIVE_TRACK
+        ),
+        rusqlite::params![
+            character_id,
+            direction,
+            grade.as_i32(),
+            correct,
+            card.current_interval_days,
+            update.new_interval_days,
+            card.ease_factor,
+            update.new_ease_factor,
+            scheduled_at,
+            response_time_ms,
+            prior_state,
+        ]
+    )?;
+
+    Ok(update.reached_week_for_first_time)
+}
+
+// === Undo ===
+
+/// The scheduling state of one card, saved with each review_log entry so the answer can be undone
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProgressSnapshot {
+    pub current_interval_days: f64,
+    pub previous_interval_days: f64,
+    pub ease_factor: f64,
+    pub next_review_date: Option<String>,
+    pub times_reviewed: i32,
+    pub times_correct: i32,
+    pub times_incorrect: i32,
+    pub has_reached_week: bool,
+    pub is_mastered: bool,
+    pub stability: Option<f64>,
+    pub difficulty: Option<f64>,
+    pub last_reviewed: Option<String>,
+    // Missing from snapshots saved before leech tracking; undo leaves them as they are
+    #[serde(default)]
+    pub lapses: Option<i32>,
+    #[serde(default)]
+    pub is_leech: Option<bool>,
+    #[serde(default)]
+    pub is_suspended: Option<bool>,
+    #[serde(default)]
+    pub correct_streak: Option<i32>,
+    #[serde(default)]
+    pub times_correct_at_demotion: Option<i32>,
+}
+
+pub fn get_progress_snapshot(conn: &Connection, character_id: i32, direction: &str) -> Result<ProgressSnapshot> {
+    conn.query_row(
+        &format!(
+            "SELECT current_interval_days, previous_interval_days, ease_factor, next_review_date,
+                    times_reviewed, times_correct, times_incorrect, has_reached_week, is_mastered,
+                    stability, difficulty, last_reviewed, lapses, is_leech, is_suspended, correct_streak,
+                    times_correct_at_demotion
+             FROM user_progress
+             WHERE character_id = ?1 AND direction = ?2 AND track = {}",
+            ACTIVE_TRACK
+        ),
+        rusqlite::params![character_id, direction],
+        |row| {
+            Ok(ProgressSnapshot {
+                current_interval_days: row.get(0)?,
+                previous_interval_days: row.get(1)?,
+                ease_factor: row.get(2)?,
+                next_review_date: row.get(3)?,
+                times_reviewed: row.get(4)?,
+                times_correct: row.get(5)?,
+                times_incorrect: row.get(6)?,
+                has_reached_week: row.get(7)?,
+                is_mastered: row.get(8)?,
+                stability: row.get(9)?,
+                difficulty: row.get(10)?,
+                last_reviewed: row.get(11)?,
+                lapses: Some(row.get(12)?),
+                is_leech: Some(row.get(13)?),
+                is_suspended: Some(row.get(14)?),
+                correct_streak: Some(row.get(15)?),
+                times_correct_at_demotion: Some(row.get(16)?),
+            })
+        }
+    )
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct UndoneAnswer {
+    pub character_id: i32,
+    pub character: String,
+    pub direction: String,
+    pub grade: i32,
+}
+
+/// Undo the active track's most recent answer of today's study day: restore the card to its state
+/// before the answer and delete the review_log entry. Call again to step further back. Returns None
+/// when there is nothing to undo today, or the last answer has no saved state: it predates undo
+/// support, or the card was suspended, reset or imported since.
+pub fn undo_last_answer(conn: &Connection) -> Result<Option<UndoneAnswer>> {
+    let today_start = StudyClock::from_settings(conn)?.today_start();
+    let last = conn.query_row(
+        &format!(
+            "SELECT r.id, r.character_id, c.character, COALESCE(r.direction, '{}'), r.grade, r.prior_state
+             FROM review_log r
+             JOIN characters c ON r.character_id = c.id
+             WHERE r.track = {} AND r.reviewed_at >= ?1
+             ORDER BY r.reviewed_at DESC, r.id DESC
+             LIMIT 1",
+            DEFAULT_DIRECTION, ACTIVE_TRACK
+        ),
+        [today_start],
+        |row| Ok((
+            row.get::<_, i64>(0)?,
+            UndoneAnswer { character_id: row.get(1)?, character: row.get(2)?, direction: row.get(3)?, grade: row.get(4)? },
+            row.get::<_, Option<String>>(5)?,
+        ))
+    ).optional()?;
+
+    let Some((review_id, answer, Some(prior_state))) = last else {
+        println!("[DB] Nothing to undo");
+        return Ok(None);
+    };
+    let prior: ProgressSnapshot = serde_json::from_str(&prior_state)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e)))?;
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        &format!(
+            "UPDATE user_progress
+             SET current_interval_days = ?1,
+                 previous_interval_days = ?2,
+                 ease_factor = ?3,
+                 next_review_date = ?4,
+                 times_reviewed = ?5,
+                 times_correct = ?6,
+                 times_incorrect = ?7,
+                 has_reached_week = ?8,
+                 is_mastered = ?9,
+                 stability = ?10,
+                 difficulty = ?11,
+                 last_reviewed = ?12,
+                 lapses = COALESCE(?15, lapses),
+                 is_leech = COALESCE(?16, is_leech),
+                 is_suspended = COALESCE(?17, is_suspended),
+                 correct_streak = COALESCE(?18, correct_streak),
+                 times_correct_at_demotion = COALESCE(?19, times_correct_at_demotion),
+                 updated_at = datetime('now')
+             WHERE character_id = ?13 AND direction = ?14 AND track = {}",
+            ACTIVE_TRACK
+        ),
+        rusqlite::params![
+            prior.current_interval_days,
+            prior.previous_interval_days,
+            prior.ease_factor,
+            prior.next_review_date,
+            prior.times_reviewed,
+            prior.times_correct,
+            prior.times_incorrect,
+            prior.has_reached_week,
+            prior.is_mastered,
+            prior.stability,
+            prior.difficulty,
+            prior.last_reviewed,
+            answer.character_id,
+            answer.direction,
+            prior.lapses,
+            prior.is_leech,
+            prior.is_suspended,
+            prior.correct_streak,
+            prior.times_correct_at_demotion,
+        ]
+    )?;
+    tx.execute("DELETE FROM review_log WHERE id = ?1", [review_id])?;
+    tx.commit()?;
+
+    println!("[DB] Undid answer {} for {} ({})", review_id, answer.character, answer.direction);
+    Ok(Some(answer))
+}
+
+/// Drop the saved state of a character's logged answers in the active track, so undo stops there
+/// instead of overwriting a later change to the card (suspend, reset, import)
+pub fn end_undo_history(conn: &Connection, character_id: i32) -> Result<()> {
+    conn.execute(
+        &format!(
+            "UPDATE review_log SET prior_state = NULL
+             WHERE character_id = ?1 AND track = {} AND prior_state IS NOT NULL",
+            ACTIVE_TRACK
+        ),
+        [character_id],
+    )?;
+    Ok(())
+}
+
+// === Suspend, Bury and Reset ===
+// These act on every direction of a character in the active track. Each returns the number of
+// characters changed.
+
+fn update_characters(
+    conn: &Connection,
+    character_ids: &[i32],
+    assignments: &str,
+    params: &[&dyn rusqlite::ToSql],
+    ends_undo: bool,
+) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let sql = format!(
+        "UPDATE user_progress SET {}, updated_at = datetime('now')
+         WHERE character_id = ?1 AND track = {}",
+        assignments, ACTIVE_TRACK
+    );
+    let mut changed = 0;
+    for character_id in character_ids {
+        let mut bound: Vec<&dyn rusqlite::ToSql> = vec![character_id];
+        bound.extend_from_slice(params);
+        if tx.execute(&sql, bound.as_slice())? > 0 {
+            changed += 1;
+            if ends_undo {
+                end_undo_history(&tx, *character_id)?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(changed)
+}
+
+/// Suspended characters are never due, never offered for self-study and never introduced,
+/// but keep their progress for when they're unsuspended
+pub fn set_suspended(conn: &Connection, character_ids: &[i32], suspended: bool) -> Result<usize> {
+    let changed = update_characters(conn, character_ids, "is_suspended = ?2", &[&suspended], true)?;
+    println!("[DB] {} {} characters", if suspended { "Suspended" } else { "Unsuspended" }, changed);
+    Ok(changed)
+}
+
+/// Hide characters from reviews and self-study until the next study day starts
+pub fn bury_until_tomorrow(conn: &Connection, character_ids: &[i32]) -> Result<usize> {
+    let tomorrow = StudyClock::from_settings(conn)?.today_end();
+    let changed = update_characters(conn, character_ids, "buried_until = ?2", &[&tomorrow], false)?;
+    println!("[DB] Buried {} characters until {}", changed, tomorrow);
+    Ok(changed)
+}
+
+/// Forget all scheduling progress: the characters go back to ready-to-learn as if just unlocked.
+/// Their review history is kept.
+pub fn reset_to_new(conn: &Connection, character_ids: &[i32]) -> Result<usize> {
+    let changed = update_characters(
+        conn,
+        character_ids,
+        "introduced = 0,
+         introduced_at = NULL,
+         current_interval_days = 0.0417,
+         previous_interval_days = 0.0417,
+         ease_factor = 2.25,
+         next_review_date = datetime('now'),
+         times_reviewed = 0,
+         times_correct = 0,
+         times_incorrect = 0,
+         has_reached_week = 0,
+         is_mastered = 0,
+         stability = NULL,
+         difficulty = NULL,
+         last_reviewed = NULL,
+         is_suspended = 0,
+         buried_until = NULL,
+         lapses = 0,
+         is_leech = 0,
+         correct_streak = 0,
+         times_correct_at_demotion = 0",
+        &[],
+        true,
+    )?;
+    println!("[DB] Reset {} characters to new", changed);
+    Ok(changed)
+}
+
+// === Leeches ===
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LeechSettings {
+    // Lapses before a card is flagged
+    pub threshold: i32,
+    // Suspend the card when it's flagged
+    pub auto_suspend: bool,
+}
+
+pub fn get_leech_settings(conn: &Connection) -> Result<LeechSettings> {
+    Ok(LeechSettings {
+        threshold: parsed_setting(conn, "leech_threshold", 8),
+        auto_suspend: parsed_setting(conn, "leech_auto_suspend", false),
+    })
+}
+
+/// Save the leech settings and re-flag every card against the new threshold.
+/// Only cards that become leeches now are auto-suspended; cards already flagged are left as they are.
+pub fn set_leech_settings(conn: &Connection, settings: &LeechSettings) -> Result<()> {
+    set_setting(conn, "leech_threshold", &settings.threshold.to_string())?;
+    set_setting(conn, "leech_auto_suspend", &settings.auto_suspend.to_string())?;
+    let updated = conn.execute(
+        "UPDATE user_progress
+         SET is_suspended = is_suspended OR (?2 AND is_leech = 0 AND lapses >= ?1),
+             is_leech = lapses >= ?1
+         WHERE is_leech <> (lapses >= ?1)",
+        rusqlite::params![settings.threshold, settings.auto_suspend],
+    )?;
+    println!("[DB] Leech settings set to {:?}, {} cards re-flagged", settings, updated);
+    Ok(())
+}
+
+/// Count a lapse on one card and flag it as a leech once it reaches the threshold
+fn record_lapse(conn: &Connection, character_id: i32, direction: &str) -> Result<()> {
+    let (lapses, was_leech): (i32, bool) = conn.query_row(
+        &format!(
+            "SELECT lapses, is_leech FROM user_progress
+             WHERE character_id = ?1 AND direction = ?2 AND track = {}",
+            ACTIVE_TRACK
+        ),
+        rusqlite::params![character_id, direction],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    )?;
+
+    let settings = get_leech_settings(conn)?;
+    let lapses = lapses + 1;
+    let becomes_leech = !was_leech && lapses >= settings.threshold;
+    conn.execute(
+        &format!(
+            "UPDATE user_progress
+             SET lapses = ?1,
+                 is_leech = is_leech OR ?2,
+                 is_suspended = is_suspended OR ?3
+             WHERE character_id = ?4 AND direction = ?5 AND track = {}",
+            ACTIVE_TRACK
+        ),
+        rusqlite::params![lapses, becomes_leech, becomes_leech && settings.auto_suspend, character_id, direction],
+    )?;
+
+    if becomes_leech {
+        println!("[SRS] Character {} ({}) is a leech after {} lapses{}",
+                 character_id, direction, lapses, if settings.auto_suspend { ", suspended" } else { "" });
+    }
+    Ok(())
+}
+
+/// An answer given instead of the right one, from practice_history
+#[derive(Debug, serde::Serialize)]
+pub struct Confusion {
+    pub answer: String,
+    pub times: i32,
+    // The dictionary entry the answer matches, when it was a character or word
+    pub character_id: Option<i32>,
+}
+
+#[derive(serde::Serialize)]
+pub struct Leech {
+    pub character_id: i32,
+    pub character: String,
+    pub pinyin: String,
+    pub definition: String,
+    pub direction: String,
+    pub lapses: i32,
+    pub times_incorrect: i32,
+    pub is_suspended: bool,
+    // Failed answers from review_log, most recent first
+    pub failures: Vec<ReviewLogEntry>,
+    // Most frequent wrong answers first
+    pub confusions: Vec<Confusion>,
+}
+
+/// Leech cards of the active track, most lapses first.
+/// Confusions come from practice_history, which has no track, so they cover both tracks.
+pub fn get_leeches(conn: &Connection) -> Result<Vec<Leech>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id, c.character, {}, c.definition, p.direction, p.lapses, p.times_incorrect, p.is_suspended
+         FROM user_progress p
+         JOIN characters c ON c.id = p.character_id
+         WHERE p.is_leech = 1 AND {}
+         ORDER BY p.lapses DESC, c.frequency_rank ASC",
+        TRACK_READING, ACTIVE_TRACK_FILTER
+    ))?;
+    let mut leeches = stmt
+        .query_map([], |row| {
+            Ok(Leech {
+                character_id: row.get(0)?,
+                character: row.get(1)?,
+                pinyin: row.get(2)?,
+                definition: row.get(3)?,
+                direction: row.get(4)?,
+                lapses: row.get(5)?,
+                times_incorrect: row.get(6)?,
+                is_suspended: row.get(7)?,
+                failures: Vec::new(),
+                confusions: Vec::new(),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut failures = conn.prepare(&format!(
+        "SELECT r.id, r.character_id, c.character, r.direction, r.grade, r.is_correct,
+                r.interval_before, r.interval_after, r.ease_before, r.ease_after,
+                r.scheduled_at, r.reviewed_at, r.response_time_ms
+         FROM review_log r
+         JOIN characters c ON r.character_id = c.id
+         WHERE r.character_id = ?1 AND COALESCE(r.direction, '{}') = ?2 AND r.is_correct = 0 AND r.track = {}
+         ORDER BY r.reviewed_at DESC, r.id DESC
+         LIMIT 20",
+        DEFAULT_DIRECTION, ACTIVE_TRACK
+    ))?;
+    let mut confusions = conn.prepare(
+        "SELECT h.user_answer, COUNT(*) AS times,
+                (SELECT m.id FROM characters m
+                 WHERE m.character = h.user_answer OR m.traditional = h.user_answer
+                 ORDER BY m.frequency_rank LIMIT 1)
+         FROM practice_history h
+         WHERE h.character_id = ?1 AND h.arrow_tested = ?2 AND h.is_correct = 0
+           AND TRIM(COALESCE(h.user_answer, '')) <> ''
+         GROUP BY h.user_answer
+         ORDER BY times DESC, MAX(h.practiced_at) DESC
+         LIMIT 10"
+    )?;
+
+    for leech in &mut leeches {
+        leech.failures = failures
+            .query_map(rusqlite::params![leech.character_id, leech.direction], review_log_entry_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+        leech.confusions = confusions
+            .query_map(rusqlite::params![leech.character_id, leech.direction], |row| {
+                Ok(Confusion { answer: row.get(0)?, times: row.get(1)?, character_id: row.get(2)? })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+    }
+
+    Ok(leeches)
+}
+
+// === Dictionary Search ===
+
+#[derive(serde::Serialize)]
+pub struct SearchResult {
+    pub id: i32,
     pub character: String,
-    pub pinyin: String,
+    pub simplified: String,
+    pub traditional: Option<String>,
+    pub mandarin_pinyin: String,
+    // Tone marks, and tone marks after tone sandhi ("nǐ hǎo", "ní hǎo")
+    pub pinyin_marked: String,
+    pub pinyin_sandhi: String,
     pub definition: String,
-    pub current_interval: f32,
-    pub times_reviewed: i32,
+    pub frequency_rank: i32,
+    pub is_word: bool,
+    pub senses: Vec<Sense>,
+    // 0 = exact hanzi, 1 = exact pinyin, 2 = exact definition, 3 = partial match
+    pub match_quality: i32,
+    // Progress fields (null if not in user_progress)
+    pub introduced: Option<bool>,
+    pub is_mastered: Option<bool>,
+    pub times_reviewed: Option<i32>,
+    pub next_review_date: Option<String>,
 }
 
-pub fn get_due_cards(conn: &Connection) -> Result<Vec<DueCard>> {
-    // Debug: Log current time and due cards
-    let now: String = conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?;
-    println!("[DB] Current time (UTC): {}", now);
+/// Search hanzi (simplified or traditional), pinyin (any tone style) and English definitions.
+/// Results are ordered by match quality, then frequency, then FTS relevance.
+pub fn search_dictionary(conn: &Connection, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let match_query = match data_processing::search::build_match_query(query) {
+        Some(match_query) => match_query,
+        None => return Ok(Vec::new()),
+    };
+    let query = query.trim();
+    let toneless = data_processing::pinyin::toneless(query);
 
-    let due_count: i32 = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress
-         WHERE introduced = 1
-           AND is_mastered = 0
-           AND next_review_date <= datetime('now')",
-        [],
-        |row| row.get(0)
-    )?;
-    println!("[DB] Cards due for review: {}", due_count);
+    println!("[DB] search_dictionary: {:?} -> {}", query, match_query);
 
     let mut stmt = conn.prepare(
-        "SELECT c.id, c.character, c.mandarin_pinyin, c.definition,
-                p.current_interval_days, p.times_reviewed
-         FROM characters c
-         JOIN user_progress p ON c.id = p.character_id
-         WHERE p.introduced = 1
-           AND p.is_mastered = 0
-           AND p.next_review_date <= datetime('now')
-         ORDER BY p.next_review_date ASC"
+        "WITH hits AS MATERIALIZED (
+             SELECT CAST(character_id AS INTEGER) AS character_id,
+                    toneless,
+                    bm25(dictionary_fts) AS score
+             FROM dictionary_fts
+             WHERE dictionary_fts MATCH ?1
+         )
+         SELECT c.id, c.character, c.simplified, c.traditional, c.mandarin_pinyin,
+                c.definition, c.frequency_rank, c.is_word,
+                MIN(CASE
+                    WHEN c.simplified = ?2 OR c.traditional = ?2 THEN 0
+                    WHEN h.toneless = ?3 OR c.pinyin_toneless = ?3 THEN 1
+                    WHEN instr('; ' || lower(c.definition) || ';', '; ' || lower(?2) || ';') > 0 THEN 2
+                    ELSE 3
+                END) AS match_quality,
+                p.introduced, p.is_mastered, p.times_reviewed, p.next_review_date,
+                MIN(h.score) AS score,
+                COALESCE(c.pinyin_marked, c.mandarin_pinyin), COALESCE(c.pinyin_sandhi, c.mandarin_pinyin)
+         FROM hits h
+         JOIN characters c ON c.id = h.character_id
+         LEFT JOIN character_progress p ON p.character_id = c.id
+         GROUP BY c.id
+         ORDER BY match_quality ASC, c.frequency_rank ASC, score ASC
+         LIMIT ?4"
     )?;
 
-    let cards = stmt.query_map([], |row| {
-        Ok(DueCard {
-            character_id: row.get(0)?,
-            character: row.get(1)?,
-            pinyin: row.get(2)?,
-            definition: row.get(3)?,
-            current_interval: row.get(4)?,
-            times_reviewed: row.get(5)?,
-        })
-    })?;
-
-    cards.collect()
-}
-
-pub fn get_srs_card_state(conn: &Connection, character_id: i32) -> Result<SrsCard> {
-    conn.query_row(
-        "SELECT character_id, current_interval_days, previous_interval_days,
-                ease_factor, times_correct, times_incorrect, has_reached_week
-         FROM user_progress
-         WHERE character_id = ?1",
-        [character_id],
+    let mut results = stmt.query_map(
+        rusqlite::params![match_query, query, toneless, limit],
         |row| {
-            Ok(SrsCard {
-                character_id: row.get(0)?,
-                current_interval_days: row.get(1)?,
-                previous_interval_days: row.get(2)?,
-                ease_factor: row.get(3)?,
-                times_correct: row.get(4)?,
-                times_incorrect: row.get(5)?,
-                has_reached_week: row.get(6)?,
+            Ok(SearchResult {
+                id: row.get(0)?,
+                character: row.get(1)?,
+                simplified: row.get(2)?,
+                traditional: row.get(3)?,
+                mandarin_pinyin: row.get(4)?,
+                pinyin_marked: row.get(14)?,
+                pinyin_sandhi: row.get(15)?,
+                definition: row.get(5)?,
+                frequency_rank: row.get(6)?,
+                is_word: row.get(7)?,
+                senses: Vec::new(),
+                match_quality: row.get(8)?,
+                introduced: row.get(9)?,
+                is_mastered: row.get(10)?,
+                times_reviewed: row.get(11)?,
+                next_review_date: row.get(12)?,
             })
         }
-    )
-}
+    )?
+    .collect::<Result<Vec<_>>>()?;
 
-/// Round a datetime down to the nearest half-hour (0 or 30 minutes)
-pub fn round_down_to_half_hour(dt: DateTime<Utc>) -> DateTime<Utc> {
-    let minute = dt.minute();
-    let rounded_minute = if minute < 30 { 0 } else { 30 };
+    for result in &mut results {
+        result.senses = get_senses(conn, result.id)?;
+    }
 
-    dt.with_minute(rounded_minute).unwrap()
-      .with_second(0).unwrap()
-      .with_nanosecond(0).unwrap()
+    Ok(results)
 }
 
-pub fn record_srs_answer(
-    conn: &Connection,
-    character_id: i32,
-    correct: bool,
-) -> Result<bool> {
-    // Get current card state
-    let card = get_srs_card_state(conn, character_id)?;
-
-    println!("[DB] record_srs_answer: char_id={}, correct={}", character_id, correct);
-    println!("[DB] Before: current_interval={}, previous_interval={}",
-             card.current_interval_days, card.previous_interval_days);
-
-    // Calculate new values
-    let update = calculate_next_review(&card, correct);
-
-    // Round next review date to nearest half hour for cleaner scheduling
-    let next_review_rounded = round_down_to_half_hour(update.next_review_date);
+// === Review Log Functions ===
 
-    println!("[DB] After calculation: new_interval={}, next_review={}, rounded={}",
-             update.new_interval_days, update.next_review_date, next_review_rounded);
 
-    // Convert to SQLite datetime format (YYYY-MM-DD HH:MM:SS)
-    // SQLite's datetime() function uses this format, and we need to match it for comparisons
-    let next_review_sqlite = next_review_rounded.format("%Y-%m-%d %H:%M:%S").to_string();
+#[derive(serde::Serialize)]
+pub struct ReviewLogEntry {
+    pub id: i64,
+    pub character_id: i32,
+    pub character: String,
+    pub direction: Option<String>,
+    pub grade: i32,
+    pub is_correct: bool,
+    pub interval_before: Option<f32>,
+    pub interval_after: Option<f32>,
+    pub ease_before: Option<f32>,
+    pub ease_after: Option<f32>,
+    pub scheduled_at: Option<String>,
+    pub reviewed_at: String,
+    pub response_time_ms: Option<i64>,
+}
 
-    // Update database
-    conn.execute(
-        "UPDATE user_progress
-         SET previous_interval_days = current_interval_days,
-             current_interval_days = ?1,
-             ease_factor = ?2,
-             next_review_date = ?3,
-             times_reviewed = times_reviewed + 1,
-             times_correct = times_correct + ?4,
-             times_incorrect = times_incorrect + ?5,
-             has_reached_week = has_reached_week OR ?6,
-             last_reviewed = datetime('now'),
-             updated_at = datetime('now')
-         WHERE character_id = ?7",
-        rusqlite::params![
-            update.new_interval_days,
-            update.new_ease_factor,
-            next_review_sqlite,
-            if correct { 1 } else { 0 },
-            if correct { 0 } else { 1 },
-            update.reached_week_for_first_time,
-            character_id,
-        ]
-    )?;
+fn review_log_entry_from_row(row: &rusqlite::Row) -> Result<ReviewLogEntry> {
+    Ok(ReviewLogEntry {
+        id: row.get(0)?,
+        character_id: row.get(1)?,
+        character: row.get(2)?,
+        direction: row.get(3)?,
+        grade: row.get(4)?,
+        is_correct: row.get(5)?,
+        interval_before: row.get(6)?,
+        interval_after: row.get(7)?,
+        ease_before: row.get(8)?,
+        ease_after: row.get(9)?,
+        scheduled_at: row.get(10)?,
+        reviewed_at: row.get(11)?,
+        response_time_ms: row.get(12)?,
+    })
+}
 
-    // Check for mastery (9 correct reviews total)
-    if correct {
-        let new_times_correct = card.times_correct + 1;
-
-        if new_times_correct >= 9 {
-            println!("[SRS] Character {} has reached MASTERY after {} correct reviews!",
-                     character_id, new_times_correct);
-
-            conn.execute(
-                "UPDATE user_progress
-                 SET is_mastered = 1,
-                     next_review_date = NULL
-                 WHERE character_id = ?1",
-                [character_id]
-            )?;
-        }
-    }
+/// Get the review history of one character in the active track, most recent first
+pub fn get_review_log_for_character(
+    conn: &Connection,
+    character_id: i32,
+    limit: usize,
+) -> Result<Vec<ReviewLogEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT r.id, r.character_id, c.character, r.direction, r.grade, r.is_correct,
+                r.interval_before, r.interval_after, r.ease_before, r.ease_after,
+                r.scheduled_at, r.reviewed_at, r.response_time_ms
+         FROM review_log r
+         JOIN characters c ON r.character_id = c.id
+         WHERE r.character_id = ?1 AND r.track = {}
+         ORDER BY r.reviewed_at DESC, r.id DESC
+         LIMIT ?2",
+        ACTIVE_TRACK
+    ))?;
+
+    let entries = stmt.query_map(rusqlite::params![character_id, limit], review_log_entry_from_row)?;
+    entries.collect()
+}
 
-    Ok(update.reached_week_for_first_time)
+/// Get the active track's reviews between two dates (YYYY-MM-DD, both inclusive), oldest first
+pub fn get_review_log_between(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<ReviewLogEntry>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT r.id, r.character_id, c.character, r.direction, r.grade, r.is_correct,
+                r.interval_before, r.interval_after, r.ease_before, r.ease_after,
+                r.scheduled_at, r.reviewed_at, r.response_time_ms
+         FROM review_log r
+         JOIN characters c ON r.character_id = c.id
+         WHERE r.reviewed_at >= datetime(?1)
+           AND r.reviewed_at < datetime(?2, '+1 day')
+           AND r.track = {}
+         ORDER BY r.reviewed_at ASC, r.id ASC",
+        ACTIVE_TRACK
+    ))?;
+
+    let entries = stmt.query_map([start_date, end_date], review_log_entry_from_row)?;
+    entries.collect()
 }
 
 pub fn unlock_next_character(conn: &Connection) -> Result<Option<Character>> {
-    // Get next character by frequency that isn't in user_progress yet
+    // Get the next character in the track's introduction order that isn't in its progress yet
     let result: Result<Character> = conn.query_row(
-        "SELECT c.id, c.character, c.simplified, c.traditional,
-                c.mandarin_pinyin, c.definition, c.frequency_rank, c.is_word
-         FROM characters c
-         WHERE c.is_word = 0
-           AND NOT EXISTS (
-               SELECT 1 FROM user_progress p
-               WHERE p.character_id = c.id
-           )
-         ORDER BY c.frequency_rank ASC
-         LIMIT 1",
+        &format!(
+            "SELECT c.id, c.character, c.simplified, c.traditional,
+                    c.mandarin_pinyin, c.definition, c.frequency_rank, c.is_word
+             FROM characters c
+             WHERE (c.is_word = 0 OR {})
+               AND {}
+             ORDER BY {}
+             LIMIT 1",
+            TRACK_PULLED_FORWARD, TRACK_CANDIDATE_FILTER, TRACK_INTRODUCTION_ORDER
+        ),
         [],
         |row| {
             Ok(Character {
@@ -403,6 +1832,7 @@ pub fn unlock_next_character(conn: &Connection) -> Result<Option<Character>> {
                 definition: row.get(5)?,
                 frequency_rank: row.get(6)?,
                 is_word: row.get(7)?,
+                senses: Vec::new(),
             })
         }
     );
@@ -410,13 +1840,7 @@ pub fn unlock_next_character(conn: &Connection) -> Result<Option<Character>> {
     match result {
         Ok(character) => {
             // Add to user_progress (not yet introduced)
-            // Start with 1 hour interval (0.0417 days)
-            conn.execute(
-                "INSERT INTO user_progress
-                 (character_id, current_interval_days, previous_interval_days, next_review_date, introduced)
-                 VALUES (?1, 0.0417, 0.0417, datetime('now'), 0)",
-                [character.id]
-            )?;
+            insert_progress_rows(conn, character.id, false)?;
 
             Ok(Some(character))
         }
@@ -428,18 +1852,17 @@ pub fn unlock_next_character(conn: &Connection) -> Result<Option<Character>> {
 /// (all component characters have been introduced)
 pub fn get_eligible_words(conn: &Connection, limit: usize) -> Result<Vec<Character>> {
     // Get all words not yet in user_progress
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT c.id, c.character, c.simplified, c.traditional,
                 c.mandarin_pinyin, c.definition, c.frequency_rank,
                 c.is_word, c.component_characters
          FROM characters c
          WHERE c.is_word = 1
            AND c.component_characters IS NOT NULL
-           AND NOT EXISTS (
-               SELECT 1 FROM user_progress p WHERE p.character_id = c.id
-           )
-         ORDER BY c.frequency_rank ASC"
-    )?;
+           AND {}
+         ORDER BY {}",
+        TRACK_CANDIDATE_FILTER, TRACK_INTRODUCTION_ORDER
+    ))?;
 
     let words: Vec<(Character, Option<String>)> = stmt.query_map([], |row| {
         Ok((
@@ -452,6 +1875,7 @@ pub fn get_eligible_words(conn: &Connection, limit: usize) -> Result<Vec<Charact
                 definition: row.get(5)?,
                 frequency_rank: row.get(6)?,
                 is_word: row.get(7)?,
+                senses: Vec::new(),
             },
             row.get(8)? // component_characters
         ))
@@ -475,7 +1899,10 @@ pub fn get_eligible_words(conn: &Connection, limit: usize) -> Result<Vec<Charact
             // Check if all components are introduced
             let all_introduced = comp_ids.iter().all(|comp_id| {
                 conn.query_row(
-                    "SELECT introduced FROM user_progress WHERE character_id = ?1",
+                    &format!(
+                        "SELECT MAX(introduced) FROM user_progress WHERE character_id = ?1 AND track = {}",
+                        ACTIVE_TRACK
+                    ),
                     [comp_id],
                     |row| row.get::<_, bool>(0)
                 ).unwrap_or(false)
@@ -581,6 +2008,7 @@ pub fn get_browse_items_introduction_order(
                     definition: row.get(5)?,
                     frequency_rank: row.get(6)?,
                     is_word: row.get(7)?,
+                    senses: Vec::new(),
                 },
                 None
             ))
@@ -611,6 +2039,7 @@ pub fn get_browse_items_introduction_order(
                     definition: row.get(5)?,
                     frequency_rank: row.get(6)?,
                     is_word: row.get(7)?,
+                    senses: Vec::new(),
                 },
                 row.get(8)?
             ))
@@ -649,17 +2078,16 @@ pub fn get_next_introduction_batch_mixed(
     batch_size: usize
 ) -> Result<Vec<Character>> {
     // Get eligible characters (not in user_progress)
-    let eligible_chars_query = conn.prepare(
+    let eligible_chars_query = conn.prepare(&format!(
         "SELECT c.id, c.character, c.simplified, c.traditional,
                 c.mandarin_pinyin, c.definition, c.frequency_rank, c.is_word
          FROM characters c
          WHERE c.is_word = 0
-           AND NOT EXISTS (
-               SELECT 1 FROM user_progress p WHERE p.character_id = c.id
-           )
-         ORDER BY c.frequency_rank ASC
-         LIMIT ?1"
-    );
+           AND {}
+         ORDER BY {}
+         LIMIT ?1",
+        TRACK_CANDIDATE_FILTER, TRACK_INTRODUCTION_ORDER
+    ));
 
     let eligible_chars: Vec<(Character, Option<String>)> = {
         let mut stmt = eligible_chars_query?;
@@ -674,6 +2102,7 @@ pub fn get_next_introduction_batch_mixed(
                     definition: row.get(5)?,
                     frequency_rank: row.get(6)?,
                     is_word: row.get(7)?,
+                    senses: Vec::new(),
                 },
                 None // Characters don't have component_characters
             ))
@@ -697,24 +2126,39 @@ pub fn get_next_introduction_batch_mixed(
             .collect()
     };
 
+    // Entries on the track's configured list come first, in list order
+    let listed_position = |id: i32| -> i64 {
+        conn.query_row(
+            &format!(
+                "SELECT position FROM track_introduction_order WHERE character_id = ?1 AND track = {}",
+                ACTIVE_TRACK
+            ),
+            [id],
+            |row| row.get(0)
+        ).unwrap_or(i64::MAX)
+    };
+
     // Combine and score all eligible items
-    let mut all_eligible: Vec<(Character, f64)> = eligible_chars
+    let mut all_eligible: Vec<(Character, i64, f64)> = eligible_chars
         .into_iter()
         .chain(eligible_words_with_components.into_iter())
         .map(|(item, components)| {
             let score = calculate_introduction_score(&item, &components, conn);
-            (item, score)
+            let position = listed_position(item.id);
+            (item, position, score)
         })
         .collect();
 
-    // Sort by score (lower is better = more frequent/important)
-    all_eligible.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    // Sort by list position, then score (lower is better = more frequent/important)
+    all_eligible.sort_by(|a, b| {
+        a.1.cmp(&b.1).then(a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))
+    });
 
     // Take top N and return
     let selected: Vec<Character> = all_eligible
         .into_iter()
         .take(batch_size)
-        .map(|(item, _score)| item)
+        .map(|(item, _position, _score)| item)
         .collect();
 
     Ok(selected)
@@ -722,10 +2166,14 @@ pub fn get_next_introduction_batch_mixed(
 
 pub fn mark_character_introduced(conn: &Connection, character_id: i32) -> Result<()> {
     conn.execute(
-        "UPDATE user_progress
-         SET introduced = 1,
-             updated_at = datetime('now')
-         WHERE character_id = ?1",
+        &format!(
+            "UPDATE user_progress
+             SET introduced = 1,
+                 introduced_at = COALESCE(introduced_at, datetime('now')),
+                 updated_at = datetime('now')
+             WHERE character_id = ?1 AND track = {}",
+            ACTIVE_TRACK
+        ),
         [character_id]
     )?;
     Ok(())
@@ -736,32 +2184,27 @@ pub fn mark_character_introduced(conn: &Connection, character_id: i32) -> Result
 /// Get cards for self-study (not currently due in SRS)
 /// Prioritizes least recently practiced cards
 pub fn get_self_study_cards(conn: &Connection, limit: usize) -> Result<Vec<DueCard>> {
-    let mut stmt = conn.prepare(
-        "SELECT c.id, c.character, c.mandarin_pinyin, c.definition,
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id, p.direction, c.character, {}, c.definition,
                 p.current_interval_days, p.times_reviewed
          FROM characters c
          JOIN user_progress p ON c.id = p.character_id
          WHERE p.next_review_date > datetime('now')
            AND p.introduced = 1
+           AND {}
+           AND {}
+           AND {}
          ORDER BY
            COALESCE(
              (SELECT MAX(practiced_at) FROM practice_history
               WHERE character_id = c.id AND practice_mode = 'self-study'),
              datetime('1970-01-01')
            ) ASC
-         LIMIT ?1"
-    )?;
+         LIMIT ?1",
+        TRACK_READING, ENABLED_DIRECTION_FILTER, ACTIVE_TRACK_FILTER, CARD_AVAILABLE_FILTER
+    ))?;
 
-    let cards = stmt.query_map([limit], |row| {
-        Ok(DueCard {
-            character_id: row.get(0)?,
-            character: row.get(1)?,
-            pinyin: row.get(2)?,
-            definition: row.get(3)?,
-            current_interval: row.get(4)?,
-            times_reviewed: row.get(5)?,
-        })
-    })?;
+    let cards = stmt.query_map([limit], due_card_from_row)?;
 
     cards.collect()
 }
@@ -811,30 +2254,98 @@ pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
 
 // === Time-Based Character Introduction Functions ===
 
-/// Get count of characters ready to learn (unlocked but not introduced)
+/// Get count of characters ready to learn (unlocked but not introduced) in the active track.
+/// Suspended characters don't count, so they never hold back the next unlock.
 pub fn get_ready_to_learn_count(conn: &Connection) -> Result<usize> {
     let count: usize = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress WHERE introduced = 0",
+        &format!(
+            "SELECT COUNT(DISTINCT character_id) FROM user_progress p
+             WHERE p.introduced = 0 AND p.is_suspended = 0 AND {}",
+            ACTIVE_TRACK_FILTER
+        ),
         [],
         |row| row.get(0)
     )?;
     Ok(count)
 }
 
-/// Get count of all introduced characters
+/// The next unlocked characters to introduce, in introduction order, never more than
+/// today's new card budget allows
+pub fn get_ready_to_learn_batch(conn: &Connection, batch_size: usize) -> Result<Vec<Character>> {
+    let batch_size = batch_size.min(get_new_card_budget(conn)?);
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id, c.character, c.simplified, c.traditional,
+                c.mandarin_pinyin, c.definition, c.frequency_rank, c.is_word
+         FROM characters c
+         INNER JOIN character_progress p ON c.id = p.character_id
+         WHERE p.introduced = 0 AND p.is_suspended = 0
+         ORDER BY {}
+         LIMIT ?1",
+        TRACK_INTRODUCTION_ORDER
+    ))?;
+
+    let characters = stmt.query_map([batch_size], |row| {
+        Ok(Character {
+            id: row.get(0)?,
+            character: row.get(1)?,
+            simplified: row.get(2)?,
+            traditional: row.get(3)?,
+            mandarin_pinyin: row.get(4)?,
+            definition: row.get(5)?,
+            frequency_rank: row.get(6)?,
+            is_word: row.get(7)?,
+            senses: Vec::new(),
+        })
+    })?;
+
+    characters.collect()
+}
+
+/// Add the next characters in introduction order to user_progress, already introduced,
+/// within today's new card budget. Returns their ids.
+pub fn introduce_next_characters(conn: &Connection, count: usize) -> Result<Vec<i32>> {
+    let count = count.min(get_new_card_budget(conn)?);
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id FROM characters c
+         WHERE (c.is_word = 0 OR {})
+           AND {}
+         ORDER BY {}
+         LIMIT ?1",
+        TRACK_PULLED_FORWARD, TRACK_CANDIDATE_FILTER, TRACK_INTRODUCTION_ORDER
+    ))?;
+    let character_ids: Vec<i32> = stmt.query_map([count], |row| row.get(0))?
+        .collect::<Result<Vec<_>>>()?;
+
+    println!("[DB] Introducing {} characters: {:?}", character_ids.len(), character_ids);
+    for character_id in &character_ids {
+        insert_progress_rows(conn, *character_id, true)?;
+    }
+
+    Ok(character_ids)
+}
+
+/// Get count of all introduced characters in the active track, leaving out suspended ones
 pub fn get_introduced_count(conn: &Connection) -> Result<usize> {
     let count: usize = conn.query_row(
-        "SELECT COUNT(*) FROM user_progress WHERE introduced = 1",
+        &format!(
+            "SELECT COUNT(DISTINCT character_id) FROM user_progress p
+             WHERE p.introduced = 1 AND p.is_suspended = 0 AND {}",
+            ACTIVE_TRACK_FILTER
+        ),
         [],
         |row| row.get(0)
     )?;
     Ok(count)
 }
 
-/// Initialize new user with first 30 characters
+/// Initialize new user (or a newly started track) with the first characters
 pub fn initialize_new_user_characters(conn: &Connection) -> Result<usize> {
+    let track = get_study_track(conn)?;
+
     // Check if already initialized
-    let initial_unlock_completed = get_setting(conn, "initial_unlock_completed")
+    let initial_unlock_completed = get_setting(conn, &track_setting_key(&track, "initial_unlock_completed"))
         .unwrap_or_else(|_| "false".to_string());
 
     if initial_unlock_completed == "true" {
@@ -842,50 +2353,45 @@ pub fn initialize_new_user_characters(conn: &Connection) -> Result<usize> {
         return Ok(0);
     }
 
-    println!("[DB] Initializing new user with first 30 characters");
+    println!("[DB] Initializing {} track with first characters", track);
 
-    // Get first 30 characters by frequency that aren't in user_progress
-    let mut stmt = conn.prepare(
+    // Get the initial batch in introduction order that isn't in user_progress
+    let policy = get_pacing_policy(conn)?;
+    let mut stmt = conn.prepare(&format!(
         "SELECT c.id
          FROM characters c
-         WHERE c.is_word = 0
-           AND NOT EXISTS (
-               SELECT 1 FROM user_progress p
-               WHERE p.character_id = c.id
-           )
-         ORDER BY c.frequency_rank ASC
-         LIMIT 100"
-    )?;
-
-    let character_ids: Vec<i32> = stmt.query_map([], |row| row.get(0))?
+         WHERE (c.is_word = 0 OR {})
+           AND {}
+         ORDER BY {}
+         LIMIT ?1",
+        TRACK_PULLED_FORWARD, TRACK_CANDIDATE_FILTER, TRACK_INTRODUCTION_ORDER
+    ))?;
+
+    let character_ids: Vec<i32> = stmt.query_map([policy.initial_batch_size], |row| row.get(0))?
         .collect::<Result<Vec<i32>>>()?;
 
     let count = character_ids.len();
 
-    // Insert all 30 into user_progress with introduced = 0
+    // Insert the batch into user_progress with introduced = 0
     for character_id in character_ids {
-        conn.execute(
-            "INSERT INTO user_progress
-             (character_id, current_interval_days, previous_interval_days, next_review_date, introduced)
-             VALUES (?1, 0.0417, 0.0417, datetime('now'), 0)",
-            [character_id]
-        )?;
+        insert_progress_rows(conn, character_id, false)?;
     }
 
     // Mark initial unlock as completed
-    set_setting(conn, "initial_unlock_completed", "true")?;
+    set_setting(conn, &track_setting_key(&track, "initial_unlock_completed"), "true")?;
 
     println!("[DB] Unlocked {} initial characters", count);
     Ok(count)
 }
 
-/// Unlock next batch of characters (10) if conditions are met
+/// Unlock the next batch of characters if the pacing policy allows it
 /// Returns: (number_unlocked, can_unlock_more)
 pub fn check_and_unlock_characters(conn: &Connection) -> Result<(usize, bool)> {
-    use chrono::{DateTime, Utc, Duration};
+    // Each track unlocks on its own schedule
+    let track = get_study_track(conn)?;
 
     // Check if initial unlock is done
-    let initial_unlock_completed = get_setting(conn, "initial_unlock_completed")
+    let initial_unlock_completed = get_setting(conn, &track_setting_key(&track, "initial_unlock_completed"))
         .unwrap_or_else(|_| "false".to_string());
 
     if initial_unlock_completed != "true" {
@@ -901,53 +2407,33 @@ pub fn check_and_unlock_characters(conn: &Connection) -> Result<(usize, bool)> {
         return Ok((0, false));
     }
 
-    // Check if 2 days have passed since last unlock
-    let last_unlock_str = get_setting(conn, "last_unlock_date")
-        .unwrap_or_else(|_| "".to_string());
-
-    let can_unlock = if last_unlock_str.is_empty() {
-        // First unlock after initial batch - allow it
-        true
-    } else {
-        // Parse last unlock date from SQLite datetime format (YYYY-MM-DD HH:MM:SS)
-        use chrono::NaiveDateTime;
-        if let Ok(naive_dt) = NaiveDateTime::parse_from_str(&last_unlock_str, "%Y-%m-%d %H:%M:%S") {
-            let last_unlock = DateTime::<Utc>::from_naive_utc_and_offset(naive_dt, Utc);
-            let now = Utc::now();
-            let elapsed = now.signed_duration_since(last_unlock);
-
-            println!("[DB] Last unlock was {} hours ago", elapsed.num_hours());
-
-            // Must wait 2 days (48 hours)
-            elapsed >= Duration::hours(48)
-        } else {
-            // Invalid date format, allow unlock
-            println!("[DB] Invalid last_unlock_date format: {}", last_unlock_str);
-            true
-        }
-    };
+    // Check the cooldown since the last unlock
+    let policy = get_pacing_policy(conn)?;
+    let remaining = unlock_cooldown_remaining(conn, &policy)?;
+    if remaining > 0 {
+        println!("[DB] Unlock cooldown has {} hours left", remaining);
+        return Ok((0, false));
+    }
 
-    if !can_unlock {
-        println!("[DB] Haven't waited 2 days since last unlock yet");
+    // Workload-aware pacing: clear the review backlog before taking on new characters
+    if is_unlock_held_for_workload(conn, &policy)? {
+        println!("[DB] More than {} reviews due. Not unlocking more.", policy.max_due_reviews);
         return Ok((0, false));
     }
 
-    // Unlock next 10 characters
-    println!("[DB] Unlocking next 10 characters");
+    println!("[DB] Unlocking next {} characters", policy.batch_size);
 
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT c.id
          FROM characters c
-         WHERE c.is_word = 0
-           AND NOT EXISTS (
-               SELECT 1 FROM user_progress p
-               WHERE p.character_id = c.id
-           )
-         ORDER BY c.frequency_rank ASC
-         LIMIT 10"
-    )?;
-
-    let character_ids: Vec<i32> = stmt.query_map([], |row| row.get(0))?
+         WHERE (c.is_word = 0 OR {})
+           AND {}
+         ORDER BY {}
+         LIMIT ?1",
+        TRACK_PULLED_FORWARD, TRACK_CANDIDATE_FILTER, TRACK_INTRODUCTION_ORDER
+    ))?;
+
+    let character_ids: Vec<i32> = stmt.query_map([policy.batch_size], |row| row.get(0))?
         .collect::<Result<Vec<i32>>>()?;
 
     let count = character_ids.len();
@@ -959,58 +2445,34 @@ pub fn check_and_unlock_characters(conn: &Connection) -> Result<(usize, bool)> {
 
     // Insert into user_progress
     for character_id in character_ids {
-        conn.execute(
-            "INSERT INTO user_progress
-             (character_id, current_interval_days, previous_interval_days, next_review_date, introduced)
-             VALUES (?1, 0.0417, 0.0417, datetime('now'), 0)",
-            [character_id]
-        )?;
+        insert_progress_rows(conn, character_id, false)?;
     }
 
     // Update last unlock date (use SQLite datetime format)
     let now = Utc::now();
     let now_sqlite = now.format("%Y-%m-%d %H:%M:%S").to_string();
-    set_setting(conn, "last_unlock_date", &now_sqlite)?;
+    set_setting(conn, &track_setting_key(&track, "last_unlock_date"), &now_sqlite)?;
 
     println!("[DB] Unlocked {} characters", count);
     Ok((count, true))
 }
 
-/// Get time until next unlock is available (in hours)
-/// Returns None if queue is not empty or characters can be unlocked now
+/// Get time until next unlock is available (in hours), following the pacing policy's cooldown
+/// Returns None if the ready-to-learn queue is not empty or the workload-aware mode is holding
+/// unlocks back (neither clears with time), Some(0) if characters can be unlocked now
 pub fn get_hours_until_next_unlock(conn: &Connection) -> Result<Option<i64>> {
-    use chrono::{DateTime, Utc};
-
     // Check if ready-to-learn queue is empty
     let ready_to_learn = get_ready_to_learn_count(conn)?;
     if ready_to_learn > 0 {
         return Ok(None); // Queue not empty
     }
 
-    // Check last unlock date
-    let last_unlock_str = get_setting(conn, "last_unlock_date")
-        .unwrap_or_else(|_| "".to_string());
-
-    if last_unlock_str.is_empty() {
-        return Ok(Some(0)); // Can unlock now
+    let policy = get_pacing_policy(conn)?;
+    if is_unlock_held_for_workload(conn, &policy)? {
+        return Ok(None); // Waiting for the review backlog to shrink
     }
 
-    // Parse from SQLite datetime format (YYYY-MM-DD HH:MM:SS)
-    use chrono::NaiveDateTime;
-    if let Ok(naive_dt) = NaiveDateTime::parse_from_str(&last_unlock_str, "%Y-%m-%d %H:%M:%S") {
-        let last_unlock = DateTime::<Utc>::from_naive_utc_and_offset(naive_dt, Utc);
-        let now = Utc::now();
-        let elapsed = now.signed_duration_since(last_unlock);
-        let hours_elapsed = elapsed.num_hours();
-
-        if hours_elapsed >= 48 {
-            Ok(Some(0)) // Can unlock now
-        } else {
-            Ok(Some(48 - hours_elapsed)) // Hours remaining
-        }
-    } else {
-        Ok(Some(0)) // Invalid date, can unlock now
-    }
+    Ok(Some(unlock_cooldown_remaining(conn, &policy)?))
 }
 
 /// Start a new study session and return the session ID
@@ -1046,56 +2508,21 @@ pub fn end_study_session(
     Ok(())
 }
 
-/// Calculate study streak (consecutive days with study sessions)
+/// Calculate study streak (consecutive local study days with study sessions)
 pub fn calculate_study_streak(conn: &Connection) -> Result<i32> {
-    use chrono::{NaiveDate, Utc, Duration};
+    let clock = StudyClock::from_settings(conn)?;
 
-    // Get all unique study dates, ordered by date descending
     let mut stmt = conn.prepare(
-        "SELECT DISTINCT DATE(started_at) as study_date
-         FROM study_sessions
-         WHERE started_at IS NOT NULL
-         ORDER BY study_date DESC"
+        "SELECT started_at FROM study_sessions WHERE started_at IS NOT NULL"
     )?;
+    let days: Vec<chrono::NaiveDate> = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?
+        .iter()
+        .filter_map(|started_at| clock.day_of_sqlite(started_at))
+        .collect();
 
-    let dates: Vec<NaiveDate> = stmt.query_map([], |row| {
-        let date_str: String = row.get(0)?;
-        // Parse SQLite date format (YYYY-MM-DD)
-        NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
-    })?
-    .collect::<Result<Vec<_>>>()?;
-
-    if dates.is_empty() {
-        return Ok(0);
-    }
-
-    // Check if user studied today or yesterday (streak is still active)
-    let today = Utc::now().date_naive();
-    let yesterday = today - Duration::days(1);
-
-    let most_recent_date = dates[0];
-
-    // Streak is broken if last study was more than 1 day ago
-    if most_recent_date < yesterday {
-        return Ok(0);
-    }
-
-    // Count consecutive days
-    let mut streak = 1;
-    let mut expected_date = most_recent_date - Duration::days(1);
-
-    for date in dates.iter().skip(1) {
-        if *date == expected_date {
-            streak += 1;
-            expected_date = expected_date - Duration::days(1);
-        } else {
-            // Gap found, streak broken
-            break;
-        }
-    }
-
-    Ok(streak)
+    Ok(study_day::streak_length(&days, clock.today()))
 }
 
 /// Build the database automatically from dataset files
@@ -1152,7 +2579,618 @@ fn build_database_if_needed() -> std::result::Result<PathBuf, Box<dyn std::error
     println!("[DB BUILD] Creating SQLite database...");
     db_builder::create_database(enriched, output_path.to_str().unwrap())?;
 
+    println!("[DB BUILD] Adding CC-Canto Jyutping...");
+    db_builder::populate_cantonese(output_path.to_str().unwrap(), &datasets_dir.join("cccanto"))?;
+
+    println!("[DB BUILD] Adding Make Me a Hanzi stroke data...");
+    db_builder::populate_makemeahanzi(output_path.to_str().unwrap(), &datasets_dir.join("makemeahanzi"))?;
+
     println!("[DB BUILD] Database created successfully!");
 
     Ok(output_path)
 }
+
+/// A user database for tests: the bundled schema, the reference rows the test needs
+/// (SQL statements) and every migration
+#[cfg(test)]
+pub(crate) fn test_user_db(rows: &str) -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(include_str!("../../../data-processing/schema.sql")).unwrap();
+    conn.execute_batch(rows).unwrap();
+    run_migrations(&conn).unwrap();
+    conn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_db() -> Connection {
+        test_user_db(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, cantonese_jyutping, definition, frequency_rank, is_word)
+             VALUES ('的', '的', '的', 'de5', 'dik1', 'possessive particle', 1, 0),
+                    ('一', '一', '一', 'yi1', 'jat1', 'one', 2, 0),
+                    ('们', '们', '們', 'men5', NULL, 'plural marker', 3, 0),
+                    ('是', '是', '是', 'shi4', 'si6', 'is', 4, 0),
+                    ('冇', '冇', '冇', 'mao3', 'mou5', 'not have', 999999, 0);"
+        )
+    }
+
+    fn unlocked(conn: &Connection, track: &str) -> Vec<String> {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT c.character FROM user_progress p JOIN characters c ON c.id = p.character_id
+             WHERE p.track = ?1 ORDER BY c.id"
+        ).unwrap();
+        stmt.query_map([track], |row| row.get(0)).unwrap().collect::<Result<Vec<String>>>().unwrap()
+    }
+
+    #[test]
+    fn test_tracks_keep_separate_progress() {
+        let conn = user_db();
+        assert_eq!(initialize_new_user_characters(&conn).unwrap(), 5);
+        mark_character_introduced(&conn, 1).unwrap();
+        record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+
+        // Cantonese starts from scratch and only unlocks entries with Jyutping
+        set_study_track(&conn, "cantonese").unwrap();
+        assert_eq!(get_introduced_count(&conn).unwrap(), 0);
+        assert_eq!(initialize_new_user_characters(&conn).unwrap(), 4);
+        assert_eq!(unlocked(&conn, "cantonese"), vec!["的", "一", "是", "冇"]);
+
+        mark_character_introduced(&conn, 1).unwrap();
+        let due = get_due_cards(&conn).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].pinyin, "dik1");
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        assert_eq!(get_review_log_for_character(&conn, 1, 10).unwrap().len(), 1);
+
+        // The Mandarin card only saw its own answer
+        set_study_track(&conn, "mandarin").unwrap();
+        let card = get_srs_card_state(&conn, 1, DEFAULT_DIRECTION).unwrap();
+        assert_eq!((card.times_correct, card.times_incorrect), (1, 0));
+        let log = get_review_log_for_character(&conn, 1, 10).unwrap();
+        assert_eq!(log.len(), 1);
+        assert!(log[0].is_correct);
+        assert_eq!(get_introduced_count(&conn).unwrap(), 1);
+        assert_eq!(get_ready_to_learn_count(&conn).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_card_directions() {
+        let conn = user_db();
+        insert_progress_rows(&conn, 1, true).unwrap();
+        let directions: Vec<String> = ["zh_to_en", "en_to_zh", "zh_to_pinyin"].iter().map(|d| d.to_string()).collect();
+        assert_eq!(set_card_directions(&conn, &directions).unwrap(), 2);
+        let due = |conn: &Connection| get_due_cards(conn).unwrap().into_iter().map(|c| c.direction).collect::<Vec<_>>();
+        assert_eq!(due(&conn).len(), 3);
+
+        // Answering one direction leaves the others' schedules alone
+        let en_to_zh = get_progress_snapshot(&conn, 1, "en_to_zh").unwrap();
+        let zh_to_pinyin = get_progress_snapshot(&conn, 1, "zh_to_pinyin").unwrap();
+        record_srs_answer(&conn, 1, Grade::Good, Some("zh_to_en"), None).unwrap();
+        assert_eq!(get_progress_snapshot(&conn, 1, "zh_to_en").unwrap().times_correct, 1);
+        assert_eq!(get_progress_snapshot(&conn, 1, "en_to_zh").unwrap(), en_to_zh);
+        assert_eq!(get_progress_snapshot(&conn, 1, "zh_to_pinyin").unwrap(), zh_to_pinyin);
+        let mut remaining = due(&conn);
+        remaining.sort();
+        assert_eq!(remaining, vec!["en_to_zh", "zh_to_pinyin"]);
+
+        // Disabled directions keep their progress but drop out of the due queue
+        set_card_directions(&conn, &["zh_to_en".to_string(), "zh_to_pinyin".to_string()]).unwrap();
+        assert_eq!(due(&conn), vec!["zh_to_pinyin"]);
+        assert_eq!(get_due_count(&conn).unwrap(), 1);
+        assert_eq!(get_progress_snapshot(&conn, 1, "en_to_zh").unwrap(), en_to_zh);
+
+        // A newly enabled direction brings in a daily new-card limit of known characters per day
+        for id in 2..=4 {
+            insert_progress_rows(&conn, id, true).unwrap();
+        }
+        set_daily_limits(&conn, &DailyLimits { review_limit: 100, new_card_limit: 3 }).unwrap();
+        set_card_directions(&conn, &["pinyin_to_zh".to_string()]).unwrap();
+        assert_eq!(get_due_count(&conn).unwrap(), 3);
+        let later: bool = conn.query_row(
+            "SELECT next_review_date > datetime('now', '+23 hours') FROM user_progress
+             WHERE character_id = 4 AND direction = 'pinyin_to_zh'",
+            [], |row| row.get(0)
+        ).unwrap();
+        assert!(later);
+    }
+
+    #[test]
+    fn test_review_log() {
+        let conn = user_db();
+        insert_progress_rows(&conn, 1, true).unwrap();
+
+        // Each answer writes one row with the card's state before and after it
+        let before = get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap();
+        record_srs_answer(&conn, 1, Grade::Good, None, Some(1500)).unwrap();
+        let after = get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap();
+        let log = get_review_log_for_character(&conn, 1, 10).unwrap();
+        assert_eq!(log.len(), 1);
+        let entry = &log[0];
+        assert_eq!((entry.grade, entry.is_correct, entry.direction.as_deref()), (Grade::Good.as_i32(), true, Some(DEFAULT_DIRECTION)));
+        assert_eq!(entry.interval_before, Some(before.current_interval_days as f32));
+        assert_eq!(entry.interval_after, Some(after.current_interval_days as f32));
+        assert_eq!(entry.ease_before, Some(before.ease_factor as f32));
+        assert_eq!(entry.ease_after, Some(after.ease_factor as f32));
+        assert_eq!(entry.scheduled_at, before.next_review_date);
+        assert_eq!(entry.response_time_ms, Some(1500));
+
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        let log = get_review_log_for_character(&conn, 1, 10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert!(!log[0].is_correct);
+        assert_eq!(log[0].interval_before, log[1].interval_after);
+        assert_eq!(log[0].scheduled_at, after.next_review_date);
+
+        // Both ends of the date range are whole days
+        conn.execute("DELETE FROM review_log", []).unwrap();
+        conn.execute_batch(
+            "INSERT INTO review_log (character_id, track, direction, grade, is_correct, reviewed_at)
+             VALUES (1, 'mandarin', 'zh_to_en', 3, 1, '2024-03-08 23:59:59'),
+                    (1, 'mandarin', 'zh_to_en', 3, 1, '2024-03-09 00:00:00'),
+                    (1, 'mandarin', 'zh_to_en', 1, 0, '2024-03-10 23:59:59'),
+                    (1, 'mandarin', 'zh_to_en', 3, 1, '2024-03-11 00:00:00');"
+        ).unwrap();
+        let reviewed_at: Vec<String> = get_review_log_between(&conn, "2024-03-09", "2024-03-10").unwrap()
+            .into_iter()
+            .map(|entry| entry.reviewed_at)
+            .collect();
+        assert_eq!(reviewed_at, vec!["2024-03-09 00:00:00", "2024-03-10 23:59:59"]);
+        assert_eq!(get_review_log_between(&conn, "2024-03-10", "2024-03-10").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_undo_last_answer() {
+        let conn = user_db();
+        insert_progress_rows(&conn, 1, true).unwrap();
+        insert_progress_rows(&conn, 2, true).unwrap();
+        let fresh = get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap();
+
+        record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+        let after_good = get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap();
+        record_srs_answer(&conn, 2, Grade::Good, None, None).unwrap();
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+
+        // Undo steps back one answer at a time, newest first
+        let undone = undo_last_answer(&conn).unwrap().unwrap();
+        assert_eq!((undone.character_id, undone.grade), (1, Grade::Again.as_i32()));
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap(), after_good);
+        assert_eq!(undo_last_answer(&conn).unwrap().unwrap().character_id, 2);
+        assert_eq!(undo_last_answer(&conn).unwrap().unwrap().character_id, 1);
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap(), fresh);
+        assert!(get_review_log_for_character(&conn, 1, 10).unwrap().is_empty());
+        assert!(undo_last_answer(&conn).unwrap().is_none());
+
+        // A mis-clicked lapse on a mastered card is fully reverted
+        conn.execute("UPDATE user_progress SET times_correct = 9, has_reached_week = 1, is_mastered = 1 WHERE character_id = 1", []).unwrap();
+        let before = get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap();
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap().times_incorrect, 1);
+        undo_last_answer(&conn).unwrap();
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap(), before);
+
+        // An answer logged before undo support can't be undone
+        conn.execute_batch(
+            "INSERT INTO review_log (character_id, track, direction, grade, is_correct, reviewed_at)
+             VALUES (1, 'mandarin', 'zh_to_en', 3, 1, datetime('now'));"
+        ).unwrap();
+        assert!(undo_last_answer(&conn).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_undo_stops_at_later_changes() {
+        let conn = user_db();
+        insert_progress_rows(&conn, 1, true).unwrap();
+        insert_progress_rows(&conn, 2, true).unwrap();
+
+        // Only answers from today's study day can be undone
+        record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+        conn.execute("UPDATE review_log SET reviewed_at = datetime('now', '-30 days')", []).unwrap();
+        assert!(undo_last_answer(&conn).unwrap().is_none());
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap().times_reviewed, 1);
+
+        // Undo doesn't revert a suspend made after the answer
+        record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+        set_suspended(&conn, &[1], true).unwrap();
+        assert!(undo_last_answer(&conn).unwrap().is_none());
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap().is_suspended, Some(true));
+
+        // Nor bring back the progress of a reset card
+        record_srs_answer(&conn, 2, Grade::Good, None, None).unwrap();
+        record_srs_answer(&conn, 2, Grade::Good, None, None).unwrap();
+        reset_to_new(&conn, &[2]).unwrap();
+        assert!(undo_last_answer(&conn).unwrap().is_none());
+        let card = get_progress_snapshot(&conn, 2, DEFAULT_DIRECTION).unwrap();
+        assert_eq!((card.times_reviewed, card.current_interval_days), (0, 0.0417));
+        assert_eq!(get_ready_to_learn_count(&conn).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_suspend_bury_and_reset() {
+        let conn = user_db();
+        for id in 1..=4 {
+            insert_progress_rows(&conn, id, true).unwrap();
+        }
+        insert_progress_rows(&conn, 5, false).unwrap();
+        let due_ids = |conn: &Connection| get_due_cards(conn).unwrap().iter().map(|c| c.character_id).collect::<Vec<_>>();
+
+        // Suspended characters are never due and don't hold back unlocks
+        assert_eq!(set_suspended(&conn, &[1, 2, 5, 99], true).unwrap(), 3);
+        assert_eq!(due_ids(&conn), vec![3, 4]);
+        assert_eq!(get_due_count(&conn).unwrap(), 2);
+        assert_eq!(get_introduced_count(&conn).unwrap(), 2);
+        assert_eq!(get_ready_to_learn_count(&conn).unwrap(), 0);
+
+        // Buried characters come back at the next study day
+        assert_eq!(bury_until_tomorrow(&conn, &[3]).unwrap(), 1);
+        assert_eq!(due_ids(&conn), vec![4]);
+        conn.execute("UPDATE user_progress SET buried_until = datetime('now', '-1 minute') WHERE character_id = 3", []).unwrap();
+        assert_eq!(due_ids(&conn).len(), 2);
+
+        assert_eq!(set_suspended(&conn, &[1], false).unwrap(), 1);
+        assert_eq!(get_due_count(&conn).unwrap(), 3);
+
+        // Self-study skips suspended and buried characters too
+        conn.execute("UPDATE user_progress SET next_review_date = datetime('now', '+1 day')", []).unwrap();
+        bury_until_tomorrow(&conn, &[4]).unwrap();
+        let mut self_study: Vec<i32> = get_self_study_cards(&conn, 10).unwrap().iter().map(|c| c.character_id).collect();
+        self_study.sort();
+        assert_eq!(self_study, vec![1, 3]);
+
+        // Cards for a newly enabled direction stay suspended or buried with their character
+        set_card_directions(&conn, &["zh_to_en".to_string(), "en_to_zh".to_string()]).unwrap();
+        let mut due = due_ids(&conn);
+        due.sort();
+        assert_eq!(due, vec![1, 3]);
+        assert_eq!(get_progress_snapshot(&conn, 2, "en_to_zh").unwrap().is_suspended, Some(true));
+
+        // Reset forgets progress and returns the character to ready-to-learn
+        record_srs_answer(&conn, 4, Grade::Good, None, None).unwrap();
+        assert_eq!(reset_to_new(&conn, &[4]).unwrap(), 1);
+        let card = get_progress_snapshot(&conn, 4, DEFAULT_DIRECTION).unwrap();
+        assert_eq!((card.times_reviewed, card.times_correct, card.is_mastered, card.last_reviewed), (0, 0, false, None));
+        assert_eq!(get_ready_to_learn_count(&conn).unwrap(), 1);
+        assert_eq!(get_review_log_for_character(&conn, 4, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_leeches() {
+        let conn = user_db();
+        insert_progress_rows(&conn, 1, true).unwrap();
+        insert_progress_rows(&conn, 2, true).unwrap();
+        set_leech_settings(&conn, &LeechSettings { threshold: 2, auto_suspend: true }).unwrap();
+        let graduate = |conn: &Connection| {
+            conn.execute("UPDATE user_progress SET current_interval_days = 3.0 WHERE character_id = 1", []).unwrap();
+        };
+
+        // Failing a card that is still being learned isn't a lapse, even after a correct answer
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap().lapses, Some(0));
+
+        graduate(&conn);
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap().lapses, Some(1));
+        assert!(get_leeches(&conn).unwrap().is_empty());
+
+        record_practice_history(&conn, 1, "self-study", DEFAULT_DIRECTION, "白", false).unwrap();
+        record_practice_history(&conn, 1, "self-study", DEFAULT_DIRECTION, "白", false).unwrap();
+        record_practice_history(&conn, 1, "self-study", DEFAULT_DIRECTION, "xyz", false).unwrap();
+        record_practice_history(&conn, 1, "self-study", DEFAULT_DIRECTION, "的", true).unwrap();
+        // Wrong answers in the other direction belong to the other card
+        record_practice_history(&conn, 1, "self-study", "en_to_zh", "黑", false).unwrap();
+        conn.execute("INSERT INTO characters (id, character, simplified, mandarin_pinyin, definition, frequency_rank, is_word) VALUES (6, '白', '白', 'bai2', 'white', 50, 0)", []).unwrap();
+
+        graduate(&conn);
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        let leeches = get_leeches(&conn).unwrap();
+        assert_eq!(leeches.len(), 1);
+        let leech = &leeches[0];
+        assert_eq!((leech.character_id, leech.lapses, leech.times_incorrect, leech.is_suspended), (1, 2, 4, true));
+        assert_eq!(leech.failures.len(), 4);
+        assert_eq!(leech.confusions.len(), 2);
+        assert_eq!((leech.confusions[0].answer.as_str(), leech.confusions[0].times, leech.confusions[0].character_id), ("白", 2, Some(6)));
+        assert_eq!(leech.confusions[1].character_id, None);
+        assert!(get_due_cards(&conn).unwrap().iter().all(|card| card.character_id != 1));
+
+        // Undoing the answer that made it a leech restores the card
+        undo_last_answer(&conn).unwrap();
+        assert!(get_leeches(&conn).unwrap().is_empty());
+        let card = get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap();
+        assert_eq!((card.lapses, card.is_suspended), (Some(1), Some(false)));
+
+        // Changing the threshold re-flags existing cards; only newly flagged ones are suspended
+        let leech_state = |conn: &Connection, id| {
+            let card = get_progress_snapshot(conn, id, DEFAULT_DIRECTION).unwrap();
+            (card.is_leech, card.is_suspended)
+        };
+        conn.execute("UPDATE user_progress SET lapses = 3 WHERE character_id = 2", []).unwrap();
+        set_leech_settings(&conn, &LeechSettings { threshold: 3, auto_suspend: false }).unwrap();
+        assert_eq!(leech_state(&conn, 2), (Some(true), Some(false)));
+        set_leech_settings(&conn, &LeechSettings { threshold: 1, auto_suspend: true }).unwrap();
+        assert_eq!(leech_state(&conn, 1), (Some(true), Some(true)));
+        assert_eq!(leech_state(&conn, 2), (Some(true), Some(false)));
+        set_leech_settings(&conn, &LeechSettings { threshold: 5, auto_suspend: true }).unwrap();
+        assert!(get_leeches(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mastery_and_maintenance_reviews() {
+        let conn = user_db();
+        for id in 1..=3 {
+            insert_progress_rows(&conn, id, true).unwrap();
+        }
+        let mastered = |conn: &Connection, id| get_progress_snapshot(conn, id, DEFAULT_DIRECTION).unwrap().is_mastered;
+
+        // Default rule: 9 correct answers in total, then maintenance reviews 180+ days apart
+        conn.execute("UPDATE user_progress SET times_correct = 8 WHERE character_id = 1", []).unwrap();
+        record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+        assert!(mastered(&conn, 1));
+        let card = get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap();
+        assert!(card.current_interval_days >= 180.0);
+        let far_off: bool = conn.query_row("SELECT ?1 > datetime('now', '+179 days')", [card.next_review_date], |row| row.get(0)).unwrap();
+        assert!(far_off);
+        assert!(get_due_cards(&conn).unwrap().iter().all(|c| c.character_id != 1));
+
+        // A maintenance review comes due like any other card; failing it demotes the card
+        conn.execute("UPDATE user_progress SET next_review_date = datetime('now', '-1 hour') WHERE character_id = 1", []).unwrap();
+        assert!(get_due_cards(&conn).unwrap().iter().any(|c| c.character_id == 1));
+        record_srs_answer(&conn, 1, Grade::Again, None, None).unwrap();
+        assert!(!mastered(&conn, 1));
+        assert_eq!(get_progress_snapshot(&conn, 1, DEFAULT_DIRECTION).unwrap().correct_streak, Some(0));
+
+        // A demoted card has to reach the rule again from the demotion on
+        for _ in 0..8 {
+            record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+            assert!(!mastered(&conn, 1));
+        }
+        record_srs_answer(&conn, 1, Grade::Good, None, None).unwrap();
+        assert!(mastered(&conn, 1));
+
+        // Consecutive rule: a failure restarts the count
+        set_mastery_policy(&conn, &MasteryPolicy { rule: "consecutive_correct".to_string(), threshold: 3.0, maintenance_interval_days: 90.0 }).unwrap();
+        for grade in [Grade::Good, Grade::Again, Grade::Good, Grade::Good] {
+            record_srs_answer(&conn, 2, grade, None, None).unwrap();
+        }
+        assert!(!mastered(&conn, 2));
+        record_srs_answer(&conn, 2, Grade::Good, None, None).unwrap();
+        assert!(mastered(&conn, 2));
+
+        // Interval rule: mastered once the scheduled interval reaches the threshold
+        set_mastery_policy(&conn, &MasteryPolicy { rule: "min_interval".to_string(), threshold: 21.0, maintenance_interval_days: 90.0 }).unwrap();
+        record_srs_answer(&conn, 3, Grade::Good, None, None).unwrap();
+        assert!(!mastered(&conn, 3));
+        conn.execute("UPDATE user_progress SET current_interval_days = 30, previous_interval_days = 14 WHERE character_id = 3", []).unwrap();
+        record_srs_answer(&conn, 3, Grade::Good, None, None).unwrap();
+        assert!(mastered(&conn, 3));
+        assert_eq!(get_mastery_policy(&conn).unwrap().rule, "min_interval");
+    }
+
+    #[test]
+    fn test_simulation_state_follows_app_rules() {
+        let conn = user_db();
+        conn.execute_batch(
+            "INSERT INTO characters (id, character, simplified, mandarin_pinyin, cantonese_jyutping, definition, frequency_rank, is_word, component_characters)
+             VALUES (6, '的是', '的是', 'de5 shi4', NULL, 'test word', 10, 1, '的,是'),
+                    (7, '一是', '一是', 'yi1 shi4', NULL, 'test word', 11, 1, '一,是');"
+        ).unwrap();
+        let directions = |names: &[&str]| names.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        set_card_directions(&conn, &directions(&["zh_to_en", "zh_to_pinyin"])).unwrap();
+        insert_progress_rows(&conn, 1, true).unwrap();
+        insert_progress_rows(&conn, 2, false).unwrap();
+        insert_progress_rows(&conn, 3, true).unwrap();
+        set_card_directions(&conn, &directions(&["zh_to_en", "en_to_zh"])).unwrap();
+        set_suspended(&conn, &[3], true).unwrap();
+        pull_forward(&conn, &[6]).unwrap();
+        conn.execute("UPDATE user_progress SET next_review_date = datetime('now', '+2 days') WHERE character_id = 1 AND direction = 'zh_to_en'", []).unwrap();
+        set_setting(&conn, "initial_unlock_completed", "true").unwrap();
+        let twelve_hours_ago: String = conn.query_row("SELECT datetime('now', '-12 hours')", [], |row| row.get(0)).unwrap();
+        set_setting(&conn, "last_unlock_date", &twelve_hours_ago).unwrap();
+
+        // Disabled directions and suspended characters are left out; the pulled-forward word can be unlocked
+        let state = data_processing::simulation::load_state(&conn, "mandarin").unwrap();
+        assert_eq!(state.cards.len(), 2);
+        assert_eq!(state.cards.iter().filter(|card| card.due_in_days > 1.9 && card.due_in_days < 2.1).count(), 1);
+        assert_eq!(state.ready_to_learn, get_ready_to_learn_count(&conn).unwrap());
+        assert_eq!((state.ready_to_learn, state.unlockable, state.cards_per_character), (1, 3, 2));
+        assert!(state.initial_unlock_completed);
+        assert!((state.hours_since_unlock.unwrap() - 12.0).abs() < 0.1);
+
+        set_study_track(&conn, "cantonese").unwrap();
+        let cantonese = data_processing::simulation::load_state(&conn, "cantonese").unwrap();
+        assert_eq!((cantonese.cards.len(), cantonese.unlockable, cantonese.hours_since_unlock), (0, 4, None));
+
+        // Unlocking everything the app would unlock gives the same counts
+        let unlock_all = |conn: &Connection| std::iter::from_fn(|| unlock_next_character(conn).unwrap()).count();
+        assert_eq!(unlock_all(&conn), cantonese.unlockable);
+        set_study_track(&conn, "mandarin").unwrap();
+        assert_eq!(unlock_all(&conn), state.unlockable);
+    }
+
+    #[test]
+    fn test_answer_key_covers_every_sense() {
+        let conn = user_db();
+        conn.execute_batch(
+            "INSERT INTO senses (character_id, traditional, pinyin, definition)
+             VALUES (1, '的', 'de5', 'possessive particle'), (1, '的', 'di2', 'really and truly'), (1, '的', 'di4', 'aim');"
+        ).unwrap();
+
+        let key = get_answer_key(&conn, 1).unwrap();
+        assert_eq!(key.readings, vec!["de5", "di2", "di4"]);
+        assert_eq!(key.jyutping.as_deref(), Some("dik1"));
+        let graded = crate::grading::grade(&key, crate::grading::Expected::Meaning, "really and truly");
+        assert!(graded.is_correct);
+        assert!(crate::grading::grade(&key, crate::grading::Expected::Pinyin, "dì").is_correct);
+    }
+
+    #[test]
+    fn test_track_introduction_order() {
+        let conn = user_db();
+        let list: Vec<String> = ["冇", "是", "不存在"].iter().map(|s| s.to_string()).collect();
+        let report = set_track_introduction_order(&conn, "cantonese", &list).unwrap();
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.unmatched, vec!["不存在"]);
+
+        // Listed entries first, then frequency; Mandarin keeps frequency order
+        set_study_track(&conn, "cantonese").unwrap();
+        let order: Vec<String> = (0..4)
+            .map(|_| unlock_next_character(&conn).unwrap().unwrap().character)
+            .collect();
+        assert_eq!(order, vec!["冇", "是", "的", "一"]);
+        assert!(unlock_next_character(&conn).unwrap().is_none());
+
+        set_study_track(&conn, "mandarin").unwrap();
+        assert_eq!(unlock_next_character(&conn).unwrap().unwrap().character, "的");
+
+        // Unlock schedules are per track
+        set_setting(&conn, &track_setting_key("cantonese", "last_unlock_date"), "2020-01-01 00:00:00").unwrap();
+        assert_eq!(get_setting(&conn, "last_unlock_date").unwrap(), "");
+    }
+
+    #[test]
+    fn test_pull_forward_queue() {
+        let conn = user_db();
+        conn.execute(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word, component_characters)
+             VALUES ('是的', '是的', '是的', 'shi4 de5', 'yes', 5000, 1, '4,1')",
+            []
+        ).unwrap();
+        let word_id = conn.last_insert_rowid() as i32;
+
+        // The word brings its missing characters along, ahead of itself
+        let report = pull_forward(&conn, &[word_id]).unwrap();
+        assert_eq!((report.queued, report.components, report.skipped), (3, 2, 0));
+        let queue: Vec<String> = get_pull_forward_queue(&conn).unwrap().into_iter().map(|e| e.character).collect();
+        assert_eq!(queue, vec!["是", "的", "是的"]);
+
+        // Queued entries jump frequency order, words included, then frequency resumes
+        let order: Vec<String> = (0..4)
+            .map(|_| unlock_next_character(&conn).unwrap().unwrap().character)
+            .collect();
+        assert_eq!(order, vec!["是", "的", "是的", "一"]);
+        assert!(get_pull_forward_queue(&conn).unwrap().is_empty());
+        assert_eq!(pull_forward(&conn, &[word_id]).unwrap().skipped, 1);
+
+        // The ready-to-learn limit and the unlock cooldown still hold back queued entries
+        set_setting(&conn, "initial_unlock_completed", "true").unwrap();
+        set_setting(&conn, "last_unlock_date", "2020-01-01 00:00:00").unwrap();
+        pull_forward(&conn, &[3]).unwrap();
+        assert_eq!(check_and_unlock_characters(&conn).unwrap(), (0, false));
+
+        for id in [1, 2, 4, word_id] {
+            mark_character_introduced(&conn, id).unwrap();
+        }
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        set_setting(&conn, "last_unlock_date", &now).unwrap();
+        assert_eq!(check_and_unlock_characters(&conn).unwrap(), (0, false));
+
+        set_setting(&conn, "last_unlock_date", "2020-01-01 00:00:00").unwrap();
+        assert_eq!(check_and_unlock_characters(&conn).unwrap(), (2, true));
+        assert_eq!(unlocked(&conn, "mandarin"), vec!["的", "一", "们", "是", "冇", "是的"]);
+        assert_eq!(clear_pull_forward_queue(&conn).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pacing_policy() {
+        let conn = user_db();
+        assert_eq!(get_pacing_policy(&conn).unwrap(), PacingPolicy::default());
+
+        let mut policy = PacingPolicy {
+            initial_batch_size: 2,
+            batch_size: 1,
+            cooldown_hours: 24,
+            workload_aware: true,
+            max_due_reviews: 1,
+        };
+        set_pacing_policy(&conn, &policy).unwrap();
+        assert_eq!(get_pacing_policy(&conn).unwrap(), policy);
+
+        assert_eq!(initialize_new_user_characters(&conn).unwrap(), 2);
+        for id in [1, 2] {
+            mark_character_introduced(&conn, id).unwrap();
+        }
+
+        // Past the cooldown, but two reviews are due and the threshold is one
+        let hours_ago = |hours: i64| (Utc::now() - chrono::Duration::hours(hours)).format("%Y-%m-%d %H:%M:%S").to_string();
+        set_setting(&conn, "last_unlock_date", &hours_ago(30)).unwrap();
+        assert_eq!(get_hours_until_next_unlock(&conn).unwrap(), None);
+        assert_eq!(check_and_unlock_characters(&conn).unwrap(), (0, false));
+
+        policy.max_due_reviews = 5;
+        set_pacing_policy(&conn, &policy).unwrap();
+        assert_eq!(get_hours_until_next_unlock(&conn).unwrap(), Some(0));
+        assert_eq!(check_and_unlock_characters(&conn).unwrap(), (1, true));
+        assert_eq!(unlocked(&conn, "mandarin"), vec!["的", "一", "们"]);
+
+        // The cooldown follows the policy instead of 48 hours
+        mark_character_introduced(&conn, 3).unwrap();
+        set_setting(&conn, "last_unlock_date", &hours_ago(20)).unwrap();
+        assert_eq!(get_hours_until_next_unlock(&conn).unwrap(), Some(4));
+        assert_eq!(check_and_unlock_characters(&conn).unwrap(), (0, false));
+    }
+
+    #[test]
+    fn test_daily_limits() {
+        let conn = user_db();
+        for id in 1..=4 {
+            insert_progress_rows(&conn, id, true).unwrap();
+        }
+        conn.execute("UPDATE user_progress SET introduced_at = datetime('now', '-2 days')", []).unwrap();
+        set_daily_limits(&conn, &DailyLimits { review_limit: 2, new_card_limit: 10 }).unwrap();
+
+        let queue = get_review_queue(&conn).unwrap();
+        assert_eq!((queue.cards.len(), queue.held_back, queue.reviewed_today), (2, 2, 0));
+
+        // A card answered today uses one slot but comes back for free
+        conn.execute_batch(
+            "INSERT INTO review_log (character_id, track, direction, grade, is_correct, reviewed_at)
+             VALUES (3, 'mandarin', 'zh_to_en', 1, 0, datetime('now')),
+                    (4, 'mandarin', 'zh_to_en', 3, 1, datetime('now', '-2 days'));"
+        ).unwrap();
+        let queue = get_review_queue(&conn).unwrap();
+        assert_eq!((queue.cards.len(), queue.held_back, queue.reviewed_today), (2, 2, 1));
+        assert!(queue.cards.iter().any(|card| card.character_id == 3));
+
+        // Reviews don't make a card new; introductions before today don't count
+        assert_eq!(get_new_cards_today(&conn).unwrap(), 0);
+        set_daily_limits(&conn, &DailyLimits { review_limit: 100, new_card_limit: 10 }).unwrap();
+        assert_eq!(get_review_queue(&conn).unwrap().held_back, 0);
+    }
+
+    #[test]
+    fn test_new_card_limit_counts_introductions() {
+        let conn = user_db();
+        set_daily_limits(&conn, &DailyLimits { review_limit: 100, new_card_limit: 3 }).unwrap();
+        set_pacing_policy(&conn, &PacingPolicy { initial_batch_size: 4, ..PacingPolicy::default() }).unwrap();
+        assert_eq!(initialize_new_user_characters(&conn).unwrap(), 4);
+
+        // The first batch is introduced without being answered yet; it still uses up the budget
+        let batch: Vec<i32> = get_ready_to_learn_batch(&conn, 2).unwrap().iter().map(|c| c.id).collect();
+        assert_eq!(batch, vec![1, 2]);
+        for id in &batch {
+            mark_character_introduced(&conn, *id).unwrap();
+        }
+        assert_eq!(get_new_cards_today(&conn).unwrap(), 2);
+        assert_eq!(get_ready_to_learn_batch(&conn, 2).unwrap().len(), 1);
+        mark_character_introduced(&conn, 3).unwrap();
+        assert!(get_ready_to_learn_batch(&conn, 2).unwrap().is_empty());
+
+        // Introducing again, or in more directions, is still the same character
+        mark_character_introduced(&conn, 1).unwrap();
+        set_card_directions(&conn, &["zh_to_en".to_string(), "en_to_zh".to_string()]).unwrap();
+        assert_eq!(get_new_cards_today(&conn).unwrap(), 3);
+
+        // Introductions from an earlier study day don't count
+        conn.execute("UPDATE user_progress SET introduced_at = datetime('now', '-2 days')", []).unwrap();
+        assert_eq!(introduce_next_characters(&conn, 5).unwrap().len(), 1);
+        assert_eq!(introduce_next_characters(&conn, 5).unwrap().len(), 0);
+        assert_eq!(get_new_card_budget(&conn).unwrap(), 2);
+
+        // A reset character is new again when it is next introduced
+        reset_to_new(&conn, &[1]).unwrap();
+        mark_character_introduced(&conn, 1).unwrap();
+        assert_eq!(get_new_cards_today(&conn).unwrap(), 2);
+    }
+}
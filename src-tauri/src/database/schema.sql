@@ -12,7 +12,10 @@ CREATE TABLE IF NOT EXISTS characters (
     character TEXT UNIQUE NOT NULL,              -- The actual character(s) or word
     simplified TEXT NOT NULL,                     -- Simplified form
     traditional TEXT,                             -- Traditional form (may be same as simplified)
-    mandarin_pinyin TEXT NOT NULL,                -- Mandarin pronunciation (Phase 1)
+    mandarin_pinyin TEXT NOT NULL,                -- Mandarin pronunciation (Phase 1), CEDICT style: 'han4 zi4'
+    pinyin_marked TEXT,                           -- Tone marks: 'hàn zì' (filled at build time by the pinyin module)
+    pinyin_toneless TEXT,                         -- Lowercase, no tones or spaces: 'hanzi' (for search)
+    pinyin_sandhi TEXT,                           -- Tone marks as spoken, after tone sandhi: 'ní hǎo'
     cantonese_jyutping TEXT,                      -- Cantonese pronunciation (Phase 3)
     definition TEXT NOT NULL,                     -- English definition(s)
     frequency_rank INTEGER NOT NULL,              -- From SUBTLEX-CH (lower = more common)
@@ -33,27 +36,83 @@ CREATE INDEX idx_frequency ON characters(frequency_rank);
 CREATE INDEX idx_is_word ON characters(is_word);
 CREATE INDEX idx_character ON characters(character);
 CREATE INDEX idx_simplified ON characters(simplified);
+CREATE INDEX idx_traditional ON characters(traditional);
 CREATE INDEX idx_introduction_rank ON characters(introduction_rank);
+CREATE INDEX idx_pinyin_toneless ON characters(pinyin_toneless);
+
+-- =============================================================================
+-- SENSES TABLE
+-- =============================================================================
+-- One row per dictionary entry, so heteronyms keep every reading
+-- (e.g. 了 le5 and liao3). characters holds the first entry as the primary reading.
+CREATE TABLE IF NOT EXISTS senses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    character_id INTEGER NOT NULL,                -- Reference to the character/word
+    traditional TEXT,                             -- Traditional form for this entry
+    pinyin TEXT NOT NULL,                         -- Reading for this entry
+    definition TEXT NOT NULL,                     -- English definitions, '; ' separated
+    source TEXT NOT NULL DEFAULT 'cedict',        -- Dictionary the entry came from
+    source_line INTEGER,                          -- Line number in the source file
+    FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_senses_character ON senses(character_id);
+
+-- =============================================================================
+-- CHARACTER STROKES TABLE
+-- =============================================================================
+-- Stroke order from Make Me a Hanzi graphics.txt, one row per single character
+CREATE TABLE IF NOT EXISTS character_strokes (
+    character_id INTEGER PRIMARY KEY,             -- Reference to the character
+    strokes TEXT NOT NULL,                        -- JSON array of SVG paths, in stroke order
+    medians TEXT NOT NULL,                        -- JSON array of stroke medians ([[x, y], ...] per stroke)
+    FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+);
+
+-- =============================================================================
+-- DICTIONARY SEARCH INDEX (FTS5)
+-- =============================================================================
+-- Built from senses by data_processing::search::build_search_index
+-- One row per sense; hanzi are space-separated so multi-character queries become phrases
+CREATE VIRTUAL TABLE dictionary_fts USING fts5(
+    character_id UNINDEXED,                       -- Reference to characters(id)
+    toneless UNINDEXED,                           -- Joined toneless pinyin ('hanzi'), for exact-match ranking
+    hanzi,                                        -- Simplified and traditional, one character per token
+    pinyin,                                       -- Tone-numbered, toneless and joined forms
+    definition,                                   -- English definitions
+    tokenize = 'unicode61 remove_diacritics 2'    -- Folds tone marks so 'hàn' matches 'han'
+);
 
 -- =============================================================================
 -- USER PROGRESS TABLE (SPACED REPETITION)
 -- =============================================================================
 -- Tracks user's learning progress for spaced repetition algorithm
 -- Based on SM-2 algorithm with modifications
+-- One row (card) per track, character and direction, each with its own schedule
 CREATE TABLE IF NOT EXISTS user_progress (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
+    track TEXT NOT NULL DEFAULT 'mandarin',       -- Study track: 'mandarin' or 'cantonese'
     character_id INTEGER NOT NULL,                -- Reference to character being learned
+    direction TEXT NOT NULL DEFAULT 'zh_to_en',   -- Direction tested: 'zh_to_en', 'zh_to_pinyin', 'en_to_zh', 'pinyin_to_zh'
     current_interval_days REAL DEFAULT 1.0,       -- Current interval before next review
-    previous_interval_days REAL DEFAULT 1.0,      -- Previous interval (for undo/rollback)
+    previous_interval_days REAL DEFAULT 1.0,      -- Interval before the last answer
     next_review_date TIMESTAMP NOT NULL,          -- When to review next
     times_reviewed INTEGER DEFAULT 0,             -- Total review count
     times_correct INTEGER DEFAULT 0,              -- Correct answer count
     times_incorrect INTEGER DEFAULT 0,            -- Incorrect answer count
     ease_factor REAL DEFAULT 2.25,                -- SM-2 ease factor (difficulty, capped at 2.25)
     has_reached_week BOOLEAN DEFAULT 0,           -- Progress milestone tracking
-    is_mastered BOOLEAN DEFAULT 0,                -- Has reached 9 correct reviews (mastery)
+    is_mastered BOOLEAN DEFAULT 0,                -- Met the mastery rule; now only due for maintenance reviews
+    stability REAL,                               -- FSRS stability in days (NULL until seeded/scheduled)
+    difficulty REAL,                              -- FSRS difficulty, 1 (easy) to 10 (hard)
     last_reviewed TIMESTAMP,                      -- Last review timestamp
     introduced BOOLEAN DEFAULT 0,                 -- Has user seen this card yet?
+    introduced_at TIMESTAMP,                      -- When it was introduced (counts against daily_new_cards)
+    is_suspended BOOLEAN NOT NULL DEFAULT 0,      -- Parked: never due, never unlocked into a lesson
+    buried_until TIMESTAMP,                       -- Hidden from reviews until this time (next study day)
+    lapses INTEGER NOT NULL DEFAULT 0,            -- Failures after the card had been answered correctly
+    is_leech BOOLEAN NOT NULL DEFAULT 0,          -- Lapsed leech_threshold times
+    correct_streak INTEGER NOT NULL DEFAULT 0,    -- Correct answers since the last failure
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
     FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
@@ -63,6 +122,42 @@ CREATE TABLE IF NOT EXISTS user_progress (
 CREATE INDEX idx_next_review ON user_progress(next_review_date);
 CREATE INDEX idx_introduced ON user_progress(introduced);
 CREATE INDEX idx_character_progress ON user_progress(character_id);
+CREATE UNIQUE INDEX idx_progress_track_character_direction ON user_progress(track, character_id, direction);
+
+-- Per-character summary across all directions of the active track (used by browse and dashboard)
+CREATE VIEW IF NOT EXISTS character_progress AS
+    SELECT character_id,
+           MAX(introduced) AS introduced,            -- Introduction applies to every direction
+           MIN(is_mastered) AS is_mastered,          -- Mastered once every direction is mastered
+           MIN(is_suspended) AS is_suspended,
+           SUM(times_reviewed) AS times_reviewed,
+           SUM(times_correct) AS times_correct,
+           SUM(times_incorrect) AS times_incorrect,
+           MIN(current_interval_days) AS current_interval_days,
+           MIN(next_review_date) AS next_review_date
+    FROM user_progress
+    WHERE track = COALESCE((SELECT value FROM app_settings WHERE key = 'study_track'), 'mandarin')
+    GROUP BY character_id;
+
+-- Optional per-track introduction order (e.g. a textbook list); unlisted entries follow by frequency
+CREATE TABLE IF NOT EXISTS track_introduction_order (
+    track TEXT NOT NULL,
+    character_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,                    -- 0-based position in the imported list
+    PRIMARY KEY (track, character_id),
+    FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+);
+
+-- Entries the user asked to learn next (e.g. words from a text), unlocked before the order above.
+-- Rows are removed once the entry is unlocked.
+CREATE TABLE IF NOT EXISTS pull_forward_queue (
+    track TEXT NOT NULL,
+    character_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,                    -- Queue order; a word's missing components come first
+    added_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (track, character_id),
+    FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+);
 
 -- =============================================================================
 -- PRACTICE HISTORY TABLE
@@ -84,6 +179,33 @@ CREATE TABLE IF NOT EXISTS practice_history (
 CREATE INDEX idx_practice_mode ON practice_history(practice_mode, practiced_at);
 CREATE INDEX idx_character_practice ON practice_history(character_id);
 
+-- =============================================================================
+-- REVIEW LOG TABLE
+-- =============================================================================
+-- One row per spaced repetition answer (user_progress only keeps the latest state)
+-- Used for auditing and tuning the schedule
+CREATE TABLE IF NOT EXISTS review_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    character_id INTEGER NOT NULL,
+    track TEXT NOT NULL DEFAULT 'mandarin',       -- Study track the card belongs to
+    direction TEXT,                               -- Card direction tested (matches user_progress.direction)
+    grade INTEGER NOT NULL,                       -- 1 = again, 2 = hard, 3 = good, 4 = easy
+    is_correct BOOLEAN NOT NULL,
+    interval_before REAL,                         -- Interval (days) before the answer
+    interval_after REAL,                          -- Interval (days) after the answer
+    ease_before REAL,
+    ease_after REAL,
+    scheduled_at TIMESTAMP,                       -- When the card was due
+    reviewed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, -- When it was actually answered
+    response_time_ms INTEGER,                     -- Response latency reported by the frontend
+    prior_state TEXT,                             -- JSON of the user_progress row before the answer (for undo)
+    FOREIGN KEY (character_id) REFERENCES characters(id) ON DELETE CASCADE
+);
+
+-- Indexes for history lookups
+CREATE INDEX idx_review_log_character ON review_log(character_id, reviewed_at);
+CREATE INDEX idx_review_log_time ON review_log(reviewed_at);
+
 -- =============================================================================
 -- STUDY SESSIONS TABLE
 -- =============================================================================
@@ -114,13 +236,29 @@ CREATE TABLE IF NOT EXISTS app_settings (
 
 -- Default settings
 INSERT OR IGNORE INTO app_settings (key, value) VALUES
-    ('daily_new_cards', '10'),
-    ('daily_review_limit', '100'),
+    ('daily_new_cards', '10'),                    -- New characters per study day (first answered that day)
+    ('daily_review_limit', '100'),                -- Cards reviewed per study day; the rest wait for tomorrow
+    ('day_rollover_hour', '4'),                   -- Local hour at which a new study day starts
+    ('timezone', ''),                             -- IANA timezone for study days (empty = system timezone)
+    ('leech_threshold', '8'),                     -- Lapses before a card is flagged as a leech
+    ('leech_auto_suspend', 'false'),              -- Suspend cards when they become leeches
+    ('mastery_rule', 'total_correct'),            -- 'total_correct', 'consecutive_correct' or 'min_interval'
+    ('mastery_threshold', '9'),                   -- Answers for the counting rules, days for min_interval
+    ('maintenance_interval_days', '180'),         -- Minimum days between reviews of mastered cards
     ('show_traditional', 'true'),
     ('default_study_mode', 'spaced_repetition'),
     ('audio_enabled', 'true'),
     ('last_unlock_date', ''),                     -- Tracks when we last unlocked characters (empty = never)
-    ('initial_unlock_completed', 'false');        -- Whether initial 30 characters have been unlocked
+    ('initial_unlock_completed', 'false'),        -- Whether initial 30 characters have been unlocked
+    ('srs_scheduler', 'ladder'),                  -- Scheduling algorithm: 'ladder' or 'fsrs'
+    ('desired_retention', '0.9'),                 -- FSRS target recall probability
+    ('card_directions', 'zh_to_en'),              -- Comma-separated directions that get reviewed
+    ('study_track', 'mandarin'),                  -- Active track; unlock settings of other tracks are suffixed (e.g. last_unlock_date_cantonese)
+    ('initial_unlock_size', '100'),               -- Characters unlocked when a track is started
+    ('unlock_batch_size', '10'),                  -- Characters unlocked per batch afterwards
+    ('unlock_cooldown_hours', '48'),              -- Hours between a batch being introduced and the next unlock
+    ('workload_aware_unlock', 'false'),           -- Hold off unlocking while too many reviews are due
+    ('unlock_max_due_reviews', '50');             -- Due reviews allowed before workload-aware mode holds off
 
 -- =============================================================================
 -- SCHEMA VERSION TABLE
@@ -141,8 +279,12 @@ VALUES (1, 'Initial schema - Phase 1/2/3 support');
 -- =============================================================================
 -- Relationship diagram:
 --
+--   characters (1) ----< (many) senses
 --   characters (1) ----< (many) user_progress
 --   characters (1) ----< (many) practice_history
+--   characters (1) ----< (many) review_log
+--   characters (1) ----< (many) track_introduction_order
+--   characters (1) ----< (many) pull_forward_queue
 --
 -- All foreign keys use ON DELETE CASCADE to maintain referential integrity
 -- =============================================================================
@@ -0,0 +1,215 @@
+// Study days in the user's local time.
+//
+// Timestamps are stored in UTC, but a "day" for streaks, calendars, daily limits and due-today counts
+// is a local day in the user's IANA timezone (the system timezone until one is chosen), starting at
+// day_rollover_hour so late-night study counts for the day it belongs to.
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::{Connection, Result};
+
+/// Hour (local time) at which a new study day starts
+pub const DEFAULT_DAY_ROLLOVER_HOUR: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StudyDaySettings {
+    // IANA name such as "America/Los_Angeles"; empty follows the system timezone
+    pub timezone: String,
+    pub day_start_hour: u32,
+}
+
+pub fn get_study_day_settings(conn: &Connection) -> Result<StudyDaySettings> {
+    Ok(StudyDaySettings {
+        timezone: super::get_setting(conn, "timezone").unwrap_or_default(),
+        day_start_hour: super::parsed_setting(conn, "day_rollover_hour", DEFAULT_DAY_ROLLOVER_HOUR).min(23),
+    })
+}
+
+pub fn set_study_day_settings(conn: &Connection, settings: &StudyDaySettings) -> Result<()> {
+    super::set_setting(conn, "timezone", &settings.timezone)?;
+    super::set_setting(conn, "day_rollover_hour", &settings.day_start_hour.to_string())?;
+    println!("[DB] Study days: timezone '{}', starting at {}:00", settings.timezone, settings.day_start_hour);
+    Ok(())
+}
+
+/// Whether `name` is a timezone the app can use (an IANA name, or empty for the system timezone)
+pub fn is_valid_timezone(name: &str) -> bool {
+    name.is_empty() || name.parse::<Tz>().is_ok()
+}
+
+/// Maps UTC instants to study days and back
+#[derive(Debug, Clone, Copy)]
+pub struct StudyClock {
+    timezone: Option<Tz>,
+    day_start_hour: u32,
+}
+
+impl StudyClock {
+    pub fn new(timezone: Option<Tz>, day_start_hour: u32) -> Self {
+        StudyClock { timezone, day_start_hour: day_start_hour.min(23) }
+    }
+
+    /// The clock configured in app_settings; an unknown timezone name falls back to the system's
+    pub fn from_settings(conn: &Connection) -> Result<Self> {
+        let settings = get_study_day_settings(conn)?;
+        Ok(StudyClock::new(settings.timezone.parse().ok(), settings.day_start_hour))
+    }
+
+    /// The study day `instant` falls in
+    pub fn day_of(&self, instant: &DateTime<Utc>) -> NaiveDate {
+        let local = match self.timezone {
+            Some(tz) => instant.with_timezone(&tz).naive_local(),
+            None => instant.with_timezone(&chrono::Local).naive_local(),
+        };
+        (local - Duration::hours(self.day_start_hour as i64)).date()
+    }
+
+    /// When study day `day` starts, in UTC. If the start hour is skipped by a DST change, the day
+    /// starts at the first local time after the gap; if it occurs twice, at the first one.
+    pub fn day_start(&self, day: NaiveDate) -> DateTime<Utc> {
+        let start = day.and_hms_opt(self.day_start_hour, 0, 0).unwrap();
+        match self.timezone {
+            Some(tz) => local_to_utc(&tz, start),
+            None => local_to_utc(&chrono::Local, start),
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.day_of(&Utc::now())
+    }
+
+    /// Start of the current study day as a SQLite UTC timestamp
+    pub fn today_start(&self) -> String {
+        sqlite_datetime(&self.day_start(self.today()))
+    }
+
+    /// End of the current study day (the next one's start) as a SQLite UTC timestamp
+    pub fn today_end(&self) -> String {
+        sqlite_datetime(&self.day_start(self.today() + Duration::days(1)))
+    }
+
+    /// The study day of a stored SQLite UTC timestamp, if it parses
+    pub fn day_of_sqlite(&self, timestamp: &str) -> Option<NaiveDate> {
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .map(|naive| self.day_of(&DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc)))
+    }
+}
+
+fn local_to_utc<Z: TimeZone>(tz: &Z, local: NaiveDateTime) -> DateTime<Utc> {
+    // Day starts are on the hour and DST gaps are whole hours; step forward to the first time that exists
+    let mut candidate = local;
+    for _ in 0..4 {
+        match tz.from_local_datetime(&candidate) {
+            LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => return time.with_timezone(&Utc),
+            LocalResult::None => candidate += Duration::hours(1),
+        }
+    }
+    DateTime::<Utc>::from_naive_utc_and_offset(local, Utc)
+}
+
+pub fn sqlite_datetime(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Consecutive study days ending today or yesterday (a streak isn't broken until a whole day is missed)
+pub fn streak_length(days: &[NaiveDate], today: NaiveDate) -> i32 {
+    let mut days = days.to_vec();
+    days.sort_unstable_by(|a, b| b.cmp(a));
+    days.dedup();
+
+    let Some(&most_recent) = days.first() else {
+        return 0;
+    };
+    if most_recent < today - Duration::days(1) {
+        return 0;
+    }
+
+    let mut streak = 1;
+    let mut expected = most_recent - Duration::days(1);
+    for day in days.iter().skip(1) {
+        if *day != expected {
+            break;
+        }
+        streak += 1;
+        expected -= Duration::days(1);
+    }
+    streak
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn pacific(day_start_hour: u32) -> StudyClock {
+        StudyClock::new(Some(chrono_tz::America::Los_Angeles), day_start_hour)
+    }
+
+    #[test]
+    fn test_late_night_counts_for_the_same_day() {
+        let clock = pacific(4);
+        // 8pm and 2am Pacific are the next UTC day, but still the evening's study day
+        assert_eq!(clock.day_of(&utc(2026, 6, 11, 3, 0)), date(2026, 6, 10));
+        assert_eq!(clock.day_of(&utc(2026, 6, 11, 9, 0)), date(2026, 6, 10));
+        assert_eq!(clock.day_of(&utc(2026, 6, 11, 11, 0)), date(2026, 6, 11));
+        assert_eq!(clock.day_start(date(2026, 6, 11)), utc(2026, 6, 11, 11, 0));
+        assert_eq!(pacific(0).day_start(date(2026, 6, 11)), utc(2026, 6, 11, 7, 0));
+    }
+
+    #[test]
+    fn test_spring_forward() {
+        // 2026-03-08: clocks jump from 2:00 PST to 3:00 PDT, so the study day running into it is 23 hours long
+        let clock = pacific(4);
+        assert_eq!(clock.day_start(date(2026, 3, 7)), utc(2026, 3, 7, 12, 0));
+        assert_eq!(clock.day_start(date(2026, 3, 8)), utc(2026, 3, 8, 11, 0));
+        assert_eq!(clock.day_start(date(2026, 3, 8)) - clock.day_start(date(2026, 3, 7)), Duration::hours(23));
+        // 3:30 PDT is before the 4:00 start
+        assert_eq!(clock.day_of(&utc(2026, 3, 8, 10, 30)), date(2026, 3, 7));
+
+        // A start hour inside the gap moves to the end of the gap
+        let clock = pacific(2);
+        assert_eq!(clock.day_start(date(2026, 3, 8)), utc(2026, 3, 8, 10, 0));
+        assert_eq!(clock.day_of(&utc(2026, 3, 8, 10, 0)), date(2026, 3, 8));
+    }
+
+    #[test]
+    fn test_fall_back() {
+        // 2026-11-01: 1:00-2:00 happens twice, so the study day running into it is 25 hours long
+        let clock = pacific(4);
+        assert_eq!(clock.day_start(date(2026, 11, 1)) - clock.day_start(date(2026, 10, 31)), Duration::hours(25));
+        assert_eq!(clock.day_of(&utc(2026, 11, 1, 11, 30)), date(2026, 10, 31));
+
+        // A start hour that occurs twice uses the first (PDT) occurrence
+        let clock = pacific(1);
+        assert_eq!(clock.day_start(date(2026, 11, 1)), utc(2026, 11, 1, 8, 0));
+        assert_eq!(clock.day_of(&utc(2026, 11, 1, 9, 30)), date(2026, 11, 1));
+    }
+
+    #[test]
+    fn test_streak_across_dst() {
+        let clock = pacific(4);
+        // Morning, evening, morning across the spring-forward weekend: UTC dates would be 6, 8, 8
+        let sessions = [utc(2026, 3, 6, 18, 0), utc(2026, 3, 8, 4, 0), utc(2026, 3, 8, 17, 0)];
+        let days: Vec<NaiveDate> = sessions.iter().map(|s| clock.day_of(s)).collect();
+        assert_eq!(days, vec![date(2026, 3, 6), date(2026, 3, 7), date(2026, 3, 8)]);
+        assert_eq!(streak_length(&days, date(2026, 3, 8)), 3);
+        assert_eq!(streak_length(&days, date(2026, 3, 9)), 3);
+        assert_eq!(streak_length(&days, date(2026, 3, 10)), 0);
+        assert_eq!(streak_length(&[], date(2026, 3, 10)), 0);
+    }
+
+    #[test]
+    fn test_timezone_validation() {
+        assert!(is_valid_timezone("Europe/Berlin"));
+        assert!(is_valid_timezone(""));
+        assert!(!is_valid_timezone("Mars/Olympus_Mons"));
+    }
+}
@@ -0,0 +1,517 @@
+// Anki .apkg export
+// An .apkg is a zip holding `collection.anki2` (an SQLite collection, schema version 11)
+// and a `media` manifest. Each introduced character becomes one note; each direction
+// in user_progress becomes one card of that note, carrying its interval, ease and history.
+// Only the active study track is exported; Cantonese notes carry Jyutping in the Pinyin field.
+
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use rusqlite::Connection;
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::database::{ACTIVE_TRACK, ACTIVE_TRACK_FILTER, CARD_DIRECTIONS, DEFAULT_DIRECTION, TRACK_READING};
+
+const MODEL_ID: i64 = 1_700_000_000_000;
+const DECK_ID: i64 = 1_700_000_000_001;
+const DECK_NAME: &str = "Chinese Flashcards";
+
+// Anki ids are millisecond timestamps; offsetting ours keeps them unique and stable across exports
+const NOTE_ID_BASE: i64 = 1_000_000_000_000;
+const CARD_ID_BASE: i64 = 1_100_000_000_000;
+
+// Anki's `left` for learning cards is 1000 * steps left today + steps left in total: one step, today
+const LEARNING_STEPS_LEFT: i64 = 1001;
+
+/// A temporary collection file no other export or import in this process is using
+pub(crate) fn temp_collection_path(kind: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("chinese-flashcards-{}-{}-{}.anki2", kind, std::process::id(), n))
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ExportSummary {
+    pub notes: usize,
+    pub cards: usize,
+    pub reviews: usize,
+}
+
+struct ExportCard {
+    progress_id: i64,
+    character_id: i64,
+    direction: String,
+    interval_days: f64,
+    ease_factor: f64,
+    next_review_date: Option<String>,
+    times_reviewed: i64,
+    lapses: i64,
+}
+
+/// Write the user's introduced cards and review history to an Anki .apkg file
+pub fn export_apkg(conn: &Connection, output_path: &Path) -> Result<ExportSummary, Box<dyn std::error::Error>> {
+    println!("[EXPORT] Exporting Anki deck to {:?}", output_path);
+
+    // Build the collection in a temporary file, then zip it up
+    let collection_path = temp_collection_path("export");
+    if collection_path.exists() {
+        std::fs::remove_file(&collection_path)?;
+    }
+
+    let summary = {
+        let collection = Connection::open(&collection_path)?;
+        write_collection(conn, &collection)?
+    };
+
+    let collection_bytes = std::fs::read(&collection_path)?;
+    std::fs::remove_file(&collection_path).ok();
+
+    let file = std::fs::File::create(output_path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("collection.anki2", options)?;
+    zip.write_all(&collection_bytes)?;
+    zip.start_file("media", options)?;
+    zip.write_all(b"{}")?;
+    zip.finish()?;
+
+    println!("[EXPORT] Wrote {} notes, {} cards, {} reviews", summary.notes, summary.cards, summary.reviews);
+    Ok(summary)
+}
+
+fn write_collection(conn: &Connection, collection: &Connection) -> Result<ExportSummary, Box<dyn std::error::Error>> {
+    collection.execute_batch(COLLECTION_SCHEMA)?;
+
+    let now = Utc::now();
+    let now_secs = now.timestamp();
+
+    // Card due dates are day numbers counted from the collection creation day
+    let first_created: Option<String> = conn.query_row(
+        &format!("SELECT MIN(created_at) FROM user_progress p WHERE p.introduced = 1 AND {}", ACTIVE_TRACK_FILTER),
+        [],
+        |row| row.get(0)
+    )?;
+    let crt_day = first_created
+        .as_deref()
+        .and_then(parse_sqlite_datetime)
+        .map(|dt| dt.date_naive())
+        .unwrap_or_else(|| now.date_naive())
+        .min(now.date_naive());
+    let crt = Utc.from_utc_datetime(&crt_day.and_hms_opt(0, 0, 0).unwrap()).timestamp();
+
+    collection.execute(
+        "INSERT INTO col (id, crt, mod, scm, ver, dty, usn, ls, conf, models, decks, dconf, tags)
+         VALUES (1, ?1, ?2, ?2, 11, 0, 0, 0, ?3, ?4, ?5, ?6, '{}')",
+        rusqlite::params![
+            crt,
+            now_secs * 1000,
+            collection_config().to_string(),
+            note_model(now_secs).to_string(),
+            decks(now_secs).to_string(),
+            deck_config().to_string(),
+        ]
+    )?;
+
+    // === Notes (one per character) ===
+    let mut stmt = conn.prepare(&format!(
+        "SELECT DISTINCT c.id, c.simplified, c.traditional, {}, c.definition
+         FROM characters c
+         JOIN user_progress p ON c.id = p.character_id
+         WHERE p.introduced = 1 AND {}
+         ORDER BY c.id",
+        TRACK_READING, ACTIVE_TRACK_FILTER
+    ))?;
+    let notes: Vec<(i64, String, Option<String>, String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for (character_id, simplified, traditional, pinyin, definition) in &notes {
+        let fields = [
+            simplified.as_str(),
+            traditional.as_deref().unwrap_or(""),
+            pinyin.as_str(),
+            definition.as_str(),
+        ];
+        collection.execute(
+            "INSERT INTO notes (id, guid, mid, mod, usn, tags, flds, sfld, csum, flags, data)
+             VALUES (?1, ?2, ?3, ?4, -1, '', ?5, ?6, ?7, 0, '')",
+            rusqlite::params![
+                NOTE_ID_BASE + character_id,
+                format!("chinese-flashcards-{}", character_id),
+                MODEL_ID,
+                now_secs,
+                fields.join("\x1f"),
+                simplified,
+                field_checksum(simplified),
+            ]
+        )?;
+    }
+
+    // === Cards (one per direction) ===
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, character_id, direction, current_interval_days, ease_factor,
+                next_review_date, times_reviewed, lapses
+         FROM user_progress p
+         WHERE introduced = 1 AND {}
+         ORDER BY id",
+        ACTIVE_TRACK_FILTER
+    ))?;
+    let cards: Vec<ExportCard> = stmt
+        .query_map([], |row| {
+            Ok(ExportCard {
+                progress_id: row.get(0)?,
+                character_id: row.get(1)?,
+                direction: row.get(2)?,
+                interval_days: row.get(3)?,
+                ease_factor: row.get(4)?,
+                next_review_date: row.get(5)?,
+                times_reviewed: row.get(6)?,
+                lapses: row.get(7)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut card_ids: HashMap<(i64, String), i64> = HashMap::new();
+
+    for card in &cards {
+        let card_id = CARD_ID_BASE + card.progress_id;
+        card_ids.insert((card.character_id, card.direction.clone()), card_id);
+
+        let ord = CARD_DIRECTIONS.iter().position(|d| *d == card.direction).unwrap_or(0);
+        let due_at = card
+            .next_review_date
+            .as_deref()
+            .and_then(parse_sqlite_datetime)
+            .unwrap_or(now);
+
+        // Sub-day intervals are Anki learning cards (due is a timestamp),
+        // anything longer is a review card (due is a day number)
+        let (card_type, queue, due, interval, left) = if card.interval_days < 1.0 {
+            (1, 1, due_at.timestamp(), 0, LEARNING_STEPS_LEFT)
+        } else {
+            let due_day = (due_at.date_naive() - crt_day).num_days();
+            (2, 2, due_day, card.interval_days.round() as i64, 0)
+        };
+
+        collection.execute(
+            "INSERT INTO cards (id, nid, did, ord, mod, usn, type, queue, due, ivl, factor,
+                                reps, lapses, left, odue, odid, flags, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, -1, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, 0, 0, 0, '')",
+            rusqlite::params![
+                card_id,
+                NOTE_ID_BASE + card.character_id,
+                DECK_ID,
+                ord as i64,
+                now_secs,
+                card_type,
+                queue,
+                due,
+                interval,
+                (card.ease_factor * 1000.0).round() as i64,
+                card.times_reviewed,
+                card.lapses,
+                left,
+            ]
+        )?;
+    }
+
+    // === Review history ===
+    let mut stmt = conn.prepare(&format!(
+        "SELECT character_id, direction, grade, interval_before, interval_after,
+                ease_after, reviewed_at, response_time_ms
+         FROM review_log
+         WHERE track = {}
+         ORDER BY reviewed_at, id",
+        ACTIVE_TRACK
+    ))?;
+    #[allow(clippy::type_complexity)]
+    let reviews: Vec<(i64, Option<String>, i64, Option<f64>, Option<f64>, Option<f64>, String, Option<i64>)> = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?,
+                row.get(5)?, row.get(6)?, row.get(7)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut exported_reviews = 0;
+    let mut last_review_id = 0;
+
+    for (character_id, direction, grade, interval_before, interval_after, ease_after, reviewed_at, response_time_ms) in reviews {
+        let direction = direction.unwrap_or_else(|| DEFAULT_DIRECTION.to_string());
+        let Some(card_id) = card_ids.get(&(character_id, direction)) else {
+            continue; // Card not exported (e.g. never introduced)
+        };
+        let Some(reviewed_at) = parse_sqlite_datetime(&reviewed_at) else {
+            continue;
+        };
+
+        // revlog ids are millisecond timestamps and must be unique
+        let review_id = (reviewed_at.timestamp_millis()).max(last_review_id + 1);
+        last_review_id = review_id;
+
+        let review_type = if interval_before.unwrap_or(0.0) < 1.0 { 0 } else { 1 };
+
+        collection.execute(
+            "INSERT INTO revlog (id, cid, usn, ease, ivl, lastIvl, factor, time, type)
+             VALUES (?1, ?2, -1, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                review_id,
+                card_id,
+                grade,
+                anki_interval(interval_after.unwrap_or(0.0)),
+                anki_interval(interval_before.unwrap_or(0.0)),
+                (ease_after.unwrap_or(2.25) * 1000.0).round() as i64,
+                response_time_ms.unwrap_or(0).min(60_000),
+                review_type,
+            ]
+        )?;
+        exported_reviews += 1;
+    }
+
+    Ok(ExportSummary {
+        notes: notes.len(),
+        cards: cards.len(),
+        reviews: exported_reviews,
+    })
+}
+
+/// revlog intervals are days when positive, seconds when negative
+fn anki_interval(days: f64) -> i64 {
+    if days >= 1.0 {
+        days.round() as i64
+    } else {
+        -(days * 86400.0).round() as i64
+    }
+}
+
+/// Anki's duplicate check: first 8 hex digits of the SHA-1 of the sort field
+fn field_checksum(field: &str) -> i64 {
+    let digest = Sha1::digest(field.as_bytes());
+    i64::from(u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]))
+}
+
+fn parse_sqlite_datetime(value: &str) -> Option<chrono::DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+fn note_model(now_secs: i64) -> serde_json::Value {
+    let field_names = ["Hanzi", "Traditional", "Pinyin", "Definition"];
+    let flds: Vec<serde_json::Value> = field_names
+        .iter()
+        .enumerate()
+        .map(|(ord, name)| json!({
+            "name": name, "ord": ord, "sticky": false, "rtl": false,
+            "font": "Arial", "size": 20, "media": []
+        }))
+        .collect();
+
+    // One template per direction, in CARD_DIRECTIONS order (card.ord indexes into this)
+    let templates = [
+        ("Hanzi → Meaning", "<div class=hanzi>{{Hanzi}}</div>", "{{Pinyin}}<br>{{Definition}}", 0),
+        ("Hanzi → Pinyin", "<div class=hanzi>{{Hanzi}}</div>", "{{Pinyin}}", 0),
+        ("Meaning → Hanzi", "{{Definition}}", "<div class=hanzi>{{Hanzi}}</div>{{Pinyin}}", 3),
+        ("Pinyin → Hanzi", "{{Pinyin}}", "<div class=hanzi>{{Hanzi}}</div>{{Definition}}", 2),
+    ];
+    let tmpls: Vec<serde_json::Value> = templates
+        .iter()
+        .enumerate()
+        .map(|(ord, (name, front, back, _))| json!({
+            "name": name, "ord": ord,
+            "qfmt": front,
+            "afmt": format!("{{{{FrontSide}}}}<hr id=answer>{}", back),
+            "did": null, "bqfmt": "", "bafmt": ""
+        }))
+        .collect();
+    let req: Vec<serde_json::Value> = templates
+        .iter()
+        .enumerate()
+        .map(|(ord, (_, _, _, field))| json!([ord, "any", [field]]))
+        .collect();
+
+    json!({
+        MODEL_ID.to_string(): {
+            "id": MODEL_ID,
+            "name": "Chinese Flashcards",
+            "type": 0,
+            "mod": now_secs,
+            "usn": -1,
+            "sortf": 0,
+            "did": DECK_ID,
+            "tmpls": tmpls,
+            "flds": flds,
+            "css": ".card { font-family: arial; font-size: 20px; text-align: center; }\n.hanzi { font-size: 64px; }",
+            "latexPre": "",
+            "latexPost": "",
+            "tags": [],
+            "vers": [],
+            "req": req
+        }
+    })
+}
+
+fn decks(now_secs: i64) -> serde_json::Value {
+    let deck = |id: i64, name: &str| json!({
+        "id": id, "name": name, "mod": now_secs, "usn": -1,
+        "lrnToday": [0, 0], "revToday": [0, 0], "newToday": [0, 0], "timeToday": [0, 0],
+        "collapsed": false, "desc": "", "dyn": 0, "conf": 1,
+        "extendNew": 10, "extendRev": 50
+    });
+    json!({
+        "1": deck(1, "Default"),
+        DECK_ID.to_string(): deck(DECK_ID, DECK_NAME),
+    })
+}
+
+fn deck_config() -> serde_json::Value {
+    json!({
+        "1": {
+            "id": 1, "name": "Default", "mod": 0, "usn": 0, "maxTaken": 60,
+            "autoplay": true, "timer": 0, "replayq": true, "dyn": false,
+            "new": { "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500,
+                     "order": 1, "perDay": 20, "bury": true },
+            "rev": { "perDay": 200, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1,
+                     "maxIvl": 36500, "bury": true },
+            "lapse": { "delays": [10], "mult": 0, "minInt": 1, "leechFails": 8,
+                       "leechAction": 0 }
+        }
+    })
+}
+
+fn collection_config() -> serde_json::Value {
+    json!({
+        "activeDecks": [DECK_ID], "curDeck": DECK_ID, "newSpread": 0, "collapseTime": 1200,
+        "timeLim": 0, "estTimes": true, "dueCounts": true, "curModel": MODEL_ID.to_string(),
+        "nextPos": 1, "sortType": "noteFld", "sortBackwards": false
+    })
+}
+
+// Anki 2.1 legacy collection schema (version 11)
+const COLLECTION_SCHEMA: &str = "
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null, scm integer not null,
+    ver integer not null, dty integer not null, usn integer not null, ls integer not null,
+    conf text not null, models text not null, decks text not null, dconf text not null,
+    tags text not null
+);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null, mod integer not null,
+    usn integer not null, tags text not null, flds text not null, sfld integer not null,
+    csum integer not null, flags integer not null, data text not null
+);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null, ord integer not null,
+    mod integer not null, usn integer not null, type integer not null, queue integer not null,
+    due integer not null, ivl integer not null, factor integer not null, reps integer not null,
+    lapses integer not null, left integer not null, odue integer not null, odid integer not null,
+    flags integer not null, data text not null
+);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null, ease integer not null,
+    ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null,
+    type integer not null
+);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_db() -> Connection {
+        let conn = crate::database::test_user_db(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word)
+             VALUES ('的', '的', '的', 'de5', 'possessive particle', 1, 0),
+                    ('一', '一', '一', 'yi1', 'one', 2, 0),
+                    ('是', '是', '是', 'shi4', 'is', 3, 0);"
+        );
+        for id in 1..=3 {
+            crate::database::insert_progress_rows(&conn, id, id != 3).unwrap();
+        }
+        conn
+    }
+
+    fn read_collection(apkg: &Path) -> Connection {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(apkg).unwrap()).unwrap();
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut archive.by_name("collection.anki2").unwrap(), &mut bytes).unwrap();
+        assert!(archive.by_name("media").is_ok());
+
+        let path = apkg.with_extension("anki2");
+        std::fs::write(&path, bytes).unwrap();
+        Connection::open(&path).unwrap()
+    }
+
+    #[test]
+    fn test_export_introduced_cards_with_schedule_and_history() {
+        let conn = user_db();
+        for _ in 0..4 {
+            crate::database::record_srs_answer(&conn, 1, true, None, Some(1500)).unwrap();
+        }
+        crate::database::record_srs_answer(&conn, 2, false, None, None).unwrap();
+
+        let apkg = std::env::temp_dir().join("chinese-flashcards-export-test.apkg");
+        let summary = export_apkg(&conn, &apkg).unwrap();
+        assert_eq!(summary.notes, 2); // 是 was never introduced
+        assert_eq!(summary.cards, 2);
+        assert_eq!(summary.reviews, 5);
+
+        let collection = read_collection(&apkg);
+
+        // 的 went 1h → 12h → 1d → 3d → 7d: a review card with a 7-day interval
+        let (card_type, ivl, factor, reps): (i64, i64, i64, i64) = collection.query_row(
+            "SELECT c.type, c.ivl, c.factor, c.reps FROM cards c
+             JOIN notes n ON n.id = c.nid WHERE n.sfld = '的'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        ).unwrap();
+        assert_eq!((card_type, ivl, factor, reps), (2, 7, 2250, 4));
+
+        // 一 was answered wrong while learning: a learning card with one step left today, but no lapse
+        let (card_type, lapses, left): (i64, i64, i64) = collection.query_row(
+            "SELECT c.type, c.lapses, c.left FROM cards c JOIN notes n ON n.id = c.nid WHERE n.sfld = '一'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        ).unwrap();
+        assert_eq!((card_type, lapses, left), (1, 0, 1001));
+
+        let fields: String = collection.query_row(
+            "SELECT flds FROM notes WHERE sfld = '一'", [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(fields, "一\x1f一\x1fyi1\x1fone");
+
+        let revlog: Vec<(i64, i64)> = collection
+            .prepare("SELECT ease, ivl FROM revlog ORDER BY id").unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(revlog.len(), 5);
+        assert_eq!(revlog[3], (3, 7));
+        assert!(revlog.iter().any(|(ease, _)| *ease == 1));
+
+        let models: String = collection.query_row("SELECT models FROM col", [], |row| row.get(0)).unwrap();
+        let models: serde_json::Value = serde_json::from_str(&models).unwrap();
+        assert_eq!(models[MODEL_ID.to_string()]["tmpls"].as_array().unwrap().len(), CARD_DIRECTIONS.len());
+
+        std::fs::remove_file(&apkg).ok();
+        std::fs::remove_file(apkg.with_extension("anki2")).ok();
+    }
+
+    #[test]
+    fn test_field_checksum_matches_anki() {
+        // sha1("hello") = aaf4c61d...
+        assert_eq!(field_checksum("hello"), 0xaaf4c61d);
+    }
+}
@@ -0,0 +1,492 @@
+// Grading of typed answers. Input is compared against every sense and reading of a card,
+// and misses that are close get an explanation ("Right syllables, wrong tone on 2nd").
+use data_processing::pinyin;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Correct,
+    /// Wrong, but close enough to say what was off
+    NearMiss,
+    Incorrect,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GradedAnswer {
+    pub verdict: Verdict,
+    pub is_correct: bool,
+    // Accepted answer the input matched or came closest to
+    pub matched: Option<String>,
+    // What was off for a near miss, or what was forgiven for a correct answer (a typo)
+    pub explanation: Option<String>,
+    // 1-based syllables with only the tone wrong, when every syllable is otherwise right
+    pub wrong_tones: Vec<usize>,
+    // Every accepted answer, for showing after a miss
+    pub accepted: Vec<String>,
+}
+
+/// What counts as a right answer for one card, from all of its senses
+#[derive(Debug, Clone, Default)]
+pub struct AnswerKey {
+    pub simplified: String,
+    pub traditional: Option<String>,
+    // Mandarin readings as CEDICT writes them, primary first
+    pub readings: Vec<String>,
+    pub jyutping: Option<String>,
+    // Definitions, one per sense ('; ' separated glosses)
+    pub definitions: Vec<String>,
+}
+
+/// What the card asks for in a given direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    Meaning,
+    Pinyin,
+    Jyutping,
+    Hanzi,
+}
+
+impl Expected {
+    /// zh_to_pinyin asks for the track's reading; the *_to_zh directions ask for the characters
+    pub fn for_direction(direction: &str, track: &str) -> Option<Self> {
+        match direction {
+            "zh_to_en" => Some(Expected::Meaning),
+            "zh_to_pinyin" if track == "cantonese" => Some(Expected::Jyutping),
+            "zh_to_pinyin" => Some(Expected::Pinyin),
+            "en_to_zh" | "pinyin_to_zh" => Some(Expected::Hanzi),
+            _ => None,
+        }
+    }
+}
+
+pub fn grade(key: &AnswerKey, expected: Expected, input: &str) -> GradedAnswer {
+    match expected {
+        Expected::Meaning => grade_meaning(&key.definitions, input),
+        Expected::Pinyin => grade_pinyin(&key.simplified, &key.readings, input),
+        Expected::Jyutping => grade_jyutping(key.jyutping.as_deref().unwrap_or(""), input),
+        Expected::Hanzi => grade_hanzi(key, input),
+    }
+}
+
+// === Readings ===
+
+/// A syllable as compared: letters (with a trailing "r" for erhua) and tone
+type Spoken = (String, Option<u8>);
+
+/// Tone numbers or tone marks, any spacing. A neutral tone may be typed without a number, and
+/// the spoken form after tone sandhi (ní hǎo for 你好) is accepted too.
+pub fn grade_pinyin(hanzi: &str, readings: &[String], input: &str) -> GradedAnswer {
+    let accepted: Vec<String> = unique(readings.iter().map(|r| pinyin::to_marked(r)));
+    let answer = pinyin_syllables(input);
+
+    let mut candidates = Vec::new();
+    for reading in readings {
+        let marked = pinyin::to_marked(reading);
+        candidates.push((marked.clone(), pinyin_syllables(reading)));
+        let spoken = pinyin::to_sandhi_marked(hanzi, reading);
+        if spoken != marked {
+            candidates.push((marked, pinyin_syllables(&spoken)));
+        }
+    }
+
+    grade_syllables(&answer, &candidates, accepted)
+}
+
+/// Jyutping with tone numbers 1-6, any spacing
+pub fn grade_jyutping(jyutping: &str, input: &str) -> GradedAnswer {
+    let accepted: Vec<String> = unique(jyutping.split(['/', ';']).map(|j| j.trim().to_string()).filter(|j| !j.is_empty()));
+    let candidates: Vec<(String, Vec<Spoken>)> =
+        accepted.iter().map(|j| (j.clone(), jyutping_syllables(j))).collect();
+    grade_syllables(&jyutping_syllables(input), &candidates, accepted)
+}
+
+fn pinyin_syllables(text: &str) -> Vec<Spoken> {
+    pinyin::syllables(text)
+        .into_iter()
+        .map(|s| {
+            let letters = if s.erhua { format!("{}r", s.letters) } else { s.letters };
+            // An unmarked syllable is read as the neutral tone
+            (letters, Some(s.tone.unwrap_or(5)))
+        })
+        .collect()
+}
+
+fn jyutping_syllables(text: &str) -> Vec<Spoken> {
+    let mut syllables = Vec::new();
+    let mut letters = String::new();
+    for c in text.to_lowercase().chars() {
+        match c {
+            'a'..='z' => letters.push(c),
+            '1'..='6' if !letters.is_empty() => {
+                syllables.push((std::mem::take(&mut letters), c.to_digit(10).map(|d| d as u8)));
+            }
+            _ if !letters.is_empty() => syllables.push((std::mem::take(&mut letters), None)),
+            _ => {}
+        }
+    }
+    if !letters.is_empty() {
+        syllables.push((letters, None));
+    }
+    syllables
+}
+
+/// Best verdict over every candidate reading; candidates are (answer to show, syllables)
+fn grade_syllables(answer: &[Spoken], candidates: &[(String, Vec<Spoken>)], accepted: Vec<String>) -> GradedAnswer {
+    if answer.is_empty() {
+        return verdict(Verdict::Incorrect, None, None, accepted);
+    }
+
+    // (mistakes, answer to show, explanation, wrong tones)
+    let mut near_miss: Option<(usize, &str, String, Vec<usize>)> = None;
+    for (shown, expected) in candidates {
+        if answer == expected.as_slice() {
+            return verdict(Verdict::Correct, Some(shown.clone()), None, accepted);
+        }
+        if answer.len() != expected.len() {
+            continue;
+        }
+
+        let wrong_tones: Vec<usize> = (0..answer.len())
+            .filter(|&i| answer[i].0 == expected[i].0 && answer[i].1 != expected[i].1)
+            .collect();
+        let wrong_letters: Vec<usize> = (0..answer.len()).filter(|&i| answer[i].0 != expected[i].0).collect();
+
+        let (explanation, tones_only) = if wrong_letters.is_empty() {
+            (format!("Right syllables, wrong tone on {}", ordinals(&wrong_tones)), true)
+        } else if wrong_letters.len() == 1 && wrong_tones.is_empty() {
+            let i = wrong_letters[0];
+            (format!("Right tones, wrong {} syllable ({}, not {})", ordinal(i + 1), expected[i].0, answer[i].0), false)
+        } else {
+            continue;
+        };
+
+        let misses = wrong_tones.len() + wrong_letters.len();
+        let closer = match &near_miss {
+            Some((best, _, _, _)) => misses < *best,
+            None => true,
+        };
+        if closer {
+            let wrong_tones = if tones_only { wrong_tones.iter().map(|i| i + 1).collect() } else { Vec::new() };
+            near_miss = Some((misses, shown, explanation, wrong_tones));
+        }
+    }
+
+    match near_miss {
+        Some((_, shown, explanation, wrong_tones)) => GradedAnswer {
+            wrong_tones,
+            ..verdict(Verdict::NearMiss, Some(shown.to_string()), Some(explanation), accepted)
+        },
+        None => verdict(Verdict::Incorrect, None, None, accepted),
+    }
+}
+
+// === Meanings ===
+
+const ARTICLES: [&str; 3] = ["a", "an", "the"];
+
+/// English is compared gloss by gloss, ignoring case, articles, a leading "to ", parentheticals
+/// and punctuation. Small typos are forgiven in longer words that start with the right letter;
+/// other close spellings and sharing a word with a gloss are near misses.
+pub fn grade_meaning(definitions: &[String], input: &str) -> GradedAnswer {
+    let glosses: Vec<String> = unique(
+        definitions
+            .iter()
+            .flat_map(|d| d.split(['/', ';']))
+            .map(|g| g.trim().to_string())
+            // Classifier notes ("CL:個|个[ge4]") aren't meanings
+            .filter(|g| !g.is_empty() && !g.starts_with("CL:")),
+    );
+    let answer = normalize_meaning(input);
+    if answer.is_empty() {
+        return verdict(Verdict::Incorrect, None, None, glosses);
+    }
+
+    let mut typo: Option<&String> = None;
+    let mut overlap: Option<&String> = None;
+    for gloss in &glosses {
+        for form in meaning_forms(gloss) {
+            if form == answer {
+                return verdict(Verdict::Correct, Some(gloss.clone()), None, glosses);
+            }
+            let close_spelling = levenshtein(&form, &answer) <= typo_allowance(&form);
+            if typo.is_none() && close_spelling && is_forgivable_typo(&form, &answer) {
+                typo = Some(gloss);
+            }
+            let shares_word = form.split(' ').any(|word| word.len() > 2 && answer.split(' ').any(|w| w == word));
+            // Close spellings that aren't forgiven ("talk" for "walk") are near misses
+            if overlap.is_none() && (shares_word || close_spelling) {
+                overlap = Some(gloss);
+            }
+        }
+    }
+
+    if let Some(gloss) = typo {
+        let explanation = format!("Accepted with a typo: {}", gloss);
+        return verdict(Verdict::Correct, Some(gloss.clone()), Some(explanation), glosses);
+    }
+    if let Some(gloss) = overlap {
+        let explanation = format!("Close, the meaning is \"{}\"", gloss);
+        return verdict(Verdict::NearMiss, Some(gloss.clone()), Some(explanation), glosses);
+    }
+    verdict(Verdict::Incorrect, None, None, glosses)
+}
+
+/// The ways a gloss can be answered: each comma-separated alternative, and the text inside the
+/// parentheses when the gloss is only a note ("(completed action marker)")
+fn meaning_forms(gloss: &str) -> Vec<String> {
+    let mut forms = vec![normalize_meaning(gloss)];
+    forms.extend(gloss.split(',').map(normalize_meaning));
+    if forms[0].is_empty() {
+        forms.push(normalize_meaning(&gloss.replace(['(', ')'], " ")));
+    }
+    forms.retain(|f| !f.is_empty());
+    forms.dedup();
+    forms
+}
+
+fn normalize_meaning(text: &str) -> String {
+    let mut plain = String::new();
+    let mut depth = 0;
+    for c in text.to_lowercase().chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = (depth - 1).max(0),
+            _ if depth > 0 => {}
+            c if c.is_alphanumeric() || c == '\'' => plain.push(c),
+            _ => plain.push(' '),
+        }
+    }
+
+    let mut words: Vec<&str> = plain.split_whitespace().filter(|w| !ARTICLES.contains(w)).collect();
+    if words.len() > 1 && words[0] == "to" {
+        words.remove(0);
+    }
+    words.join(" ")
+}
+
+/// One typo for words of 4-7 letters, two from 8
+fn typo_allowance(expected: &str) -> usize {
+    match expected.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Short words are too often one letter away from another real word, and a wrong first letter
+/// usually makes a different word
+fn is_forgivable_typo(expected: &str, answer: &str) -> bool {
+    expected.chars().count() >= 5 && expected.chars().next() == answer.chars().next()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// === Characters ===
+
+/// Simplified or traditional forms are both right; one wrong character is a near miss
+pub fn grade_hanzi(key: &AnswerKey, input: &str) -> GradedAnswer {
+    let accepted: Vec<String> = unique(std::iter::once(key.simplified.clone()).chain(key.traditional.clone()));
+    let answer: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if answer.is_empty() {
+        return verdict(Verdict::Incorrect, None, None, accepted);
+    }
+
+    for form in &accepted {
+        let expected: Vec<char> = form.chars().collect();
+        if answer == expected {
+            return verdict(Verdict::Correct, Some(form.clone()), None, accepted);
+        }
+    }
+    for form in &accepted {
+        let expected: Vec<char> = form.chars().collect();
+        if expected.len() > 1 && answer.len() == expected.len() {
+            let wrong: Vec<usize> = (0..answer.len()).filter(|&i| answer[i] != expected[i]).collect();
+            if wrong.len() == 1 {
+                let explanation = format!(
+                    "Right except the {} character ({}, not {})",
+                    ordinal(wrong[0] + 1), expected[wrong[0]], answer[wrong[0]]
+                );
+                return verdict(Verdict::NearMiss, Some(form.clone()), Some(explanation), accepted);
+            }
+        }
+    }
+    verdict(Verdict::Incorrect, None, None, accepted)
+}
+
+// === Helpers ===
+
+fn verdict(verdict: Verdict, matched: Option<String>, explanation: Option<String>, accepted: Vec<String>) -> GradedAnswer {
+    GradedAnswer {
+        verdict,
+        is_correct: verdict == Verdict::Correct,
+        matched,
+        explanation,
+        wrong_tones: Vec::new(),
+        accepted,
+    }
+}
+
+fn unique(items: impl Iterator<Item = String>) -> Vec<String> {
+    let mut seen = Vec::new();
+    for item in items {
+        if !seen.contains(&item) {
+            seen.push(item);
+        }
+    }
+    seen
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// 0-based positions as "1st", "1st and 3rd", "1st, 2nd and 3rd"
+fn ordinals(positions: &[usize]) -> String {
+    let words: Vec<String> = positions.iter().map(|i| ordinal(i + 1)).collect();
+    match words.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => words.concat(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> AnswerKey {
+        AnswerKey {
+            simplified: "汉字".to_string(),
+            traditional: Some("漢字".to_string()),
+            readings: vec!["han4 zi4".to_string()],
+            jyutping: Some("hon3 zi6".to_string()),
+            definitions: vec!["Chinese character; CL:個|个[ge4]".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_pinyin_accepts_numbers_and_marks() {
+        let key = key();
+        for input in ["han4 zi4", "han4zi4", "hànzì", "Hàn zì"] {
+            assert_eq!(grade(&key, Expected::Pinyin, input).verdict, Verdict::Correct, "{}", input);
+        }
+        assert_eq!(grade(&key, Expected::Pinyin, "hanzi").verdict, Verdict::NearMiss);
+    }
+
+    #[test]
+    fn test_pinyin_near_misses() {
+        let key = key();
+        let answer = grade(&key, Expected::Pinyin, "han4 zi3");
+        assert_eq!(answer.verdict, Verdict::NearMiss);
+        assert_eq!(answer.explanation.as_deref(), Some("Right syllables, wrong tone on 2nd"));
+        assert_eq!(answer.matched.as_deref(), Some("hàn zì"));
+        assert_eq!(answer.wrong_tones, vec![2]);
+
+        let answer = grade(&key, Expected::Pinyin, "han3 zi3");
+        assert_eq!(answer.explanation.as_deref(), Some("Right syllables, wrong tone on 1st and 2nd"));
+
+        let answer = grade(&key, Expected::Pinyin, "hang4 zi4");
+        assert_eq!(answer.explanation.as_deref(), Some("Right tones, wrong 1st syllable (han, not hang)"));
+        assert!(answer.wrong_tones.is_empty());
+
+        assert_eq!(grade(&key, Expected::Pinyin, "ma1").verdict, Verdict::Incorrect);
+        assert_eq!(grade(&key, Expected::Pinyin, "").verdict, Verdict::Incorrect);
+    }
+
+    #[test]
+    fn test_pinyin_every_reading_neutral_tone_and_sandhi() {
+        let le = ["le5".to_string(), "liao3".to_string()];
+        assert!(grade_pinyin("了", &le, "le").is_correct);
+        assert!(grade_pinyin("了", &le, "liǎo").is_correct);
+
+        let nihao = ["ni3 hao3".to_string()];
+        assert!(grade_pinyin("你好", &nihao, "ni3hao3").is_correct);
+        assert!(grade_pinyin("你好", &nihao, "ní hǎo").is_correct);
+        assert_eq!(grade_pinyin("你好", &nihao, "ni3 hao3").matched.as_deref(), Some("nǐ hǎo"));
+
+        let nu = ["nu:3".to_string()];
+        assert!(grade_pinyin("女", &nu, "nv3").is_correct);
+        assert!(grade_pinyin("女", &nu, "nǚ").is_correct);
+    }
+
+    #[test]
+    fn test_jyutping() {
+        let key = key();
+        assert!(grade(&key, Expected::Jyutping, "hon3zi6").is_correct);
+        let answer = grade(&key, Expected::Jyutping, "hon3 zi2");
+        assert_eq!(answer.explanation.as_deref(), Some("Right syllables, wrong tone on 2nd"));
+    }
+
+    #[test]
+    fn test_meaning_is_fuzzy() {
+        let definitions = ["to study; to learn".to_string(), "(completed action marker)".to_string()];
+        for input in ["study", "to learn", "Learn!", "completed action marker", "the study"] {
+            assert!(grade_meaning(&definitions, input).is_correct, "{}", input);
+        }
+
+        let typo = grade_meaning(&definitions, "studdy");
+        assert_eq!(typo.verdict, Verdict::Correct);
+        assert_eq!(typo.explanation.as_deref(), Some("Accepted with a typo: to study"));
+
+        let partial = grade_meaning(&definitions, "study hard");
+        assert_eq!(partial.verdict, Verdict::NearMiss);
+        assert_eq!(partial.matched.as_deref(), Some("to study"));
+
+        assert_eq!(grade_meaning(&definitions, "eat").verdict, Verdict::Incorrect);
+    }
+
+    #[test]
+    fn test_meaning_parentheticals_and_alternatives() {
+        let definitions = ["good; well; (after a verb) indicates completion".to_string(), "big, large".to_string()];
+        assert!(grade_meaning(&definitions, "indicates completion").is_correct);
+        assert!(grade_meaning(&definitions, "large").is_correct);
+        assert!(grade_meaning(&definitions, "Big, large").is_correct);
+    }
+
+    #[test]
+    fn test_meaning_typos_that_are_other_words() {
+        let pairs = [("to walk", "talk"), ("to sell", "tell"), ("bear", "beer"), ("big, large", "barge")];
+        for (definition, input) in pairs {
+            let answer = grade_meaning(&[definition.to_string()], input);
+            assert_eq!(answer.verdict, Verdict::NearMiss, "{} for {}", input, definition);
+        }
+    }
+
+    #[test]
+    fn test_hanzi() {
+        let key = key();
+        assert!(grade(&key, Expected::Hanzi, "汉字").is_correct);
+        assert!(grade(&key, Expected::Hanzi, " 漢字 ").is_correct);
+        let answer = grade(&key, Expected::Hanzi, "汉子");
+        assert_eq!(answer.verdict, Verdict::NearMiss);
+        assert_eq!(answer.explanation.as_deref(), Some("Right except the 2nd character (字, not 子)"));
+        assert_eq!(grade(&key, Expected::Hanzi, "好").verdict, Verdict::Incorrect);
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Expected::for_direction("zh_to_pinyin", "cantonese"), Some(Expected::Jyutping));
+        assert_eq!(Expected::for_direction("pinyin_to_zh", "mandarin"), Some(Expected::Hanzi));
+        assert_eq!(Expected::for_direction("sideways", "mandarin"), None);
+    }
+}
@@ -0,0 +1,140 @@
+// Anki .apkg / .colpkg reader
+// Reads the SQLite collection inside the zip. Each note is one entry; its schedule comes
+// from the most advanced of its cards. Notes whose cards are all still new are reported.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+use super::{hanzi_forms, ImportEntry, UnmatchedRow};
+
+// Older collection formats first; collection.anki21b (zstd) needs a newer reader
+const COLLECTION_NAMES: [&str; 2] = ["collection.anki21", "collection.anki2"];
+
+struct NoteSchedule {
+    interval_days: f64,
+    ease_factor: Option<f64>,
+    correct_count: u32,
+    due: Option<DateTime<Utc>>,
+}
+
+pub fn read_entries(
+    path: &Path,
+    unmatched: &mut Vec<UnmatchedRow>,
+) -> Result<Vec<ImportEntry>, Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+
+    let name = COLLECTION_NAMES
+        .iter()
+        .find(|name| archive.by_name(name).is_ok())
+        .ok_or("No collection.anki2 in this file. In Anki, export with \"Support older Anki versions\" ticked.")?;
+
+    let mut bytes = Vec::new();
+    archive.by_name(name)?.read_to_end(&mut bytes)?;
+
+    // rusqlite needs a file to open
+    let collection_path = crate::export::temp_collection_path("import");
+    std::fs::write(&collection_path, bytes)?;
+    let result = Connection::open(&collection_path)
+        .map_err(|e| e.into())
+        .and_then(|collection| read_collection(&collection, unmatched));
+    std::fs::remove_file(&collection_path).ok();
+    result
+}
+
+fn read_collection(
+    collection: &Connection,
+    unmatched: &mut Vec<UnmatchedRow>,
+) -> Result<Vec<ImportEntry>, Box<dyn std::error::Error>> {
+    let crt: i64 = collection.query_row("SELECT crt FROM col", [], |row| row.get(0))?;
+    let schedules = read_schedules(collection, crt)?;
+
+    let mut stmt = collection.prepare("SELECT id, flds FROM notes ORDER BY id")?;
+    let notes: Vec<(i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut entries = Vec::new();
+    for (index, (note_id, fields)) in notes.into_iter().enumerate() {
+        let line = index + 1;
+        let fields: Vec<&str> = fields.split('\x1f').collect();
+        let text = fields.join(" | ");
+
+        // The first field with hanzi is the headword; the others may hold another script form
+        let Some(headword) = fields.iter().position(|f| !hanzi_forms(f).is_empty()) else {
+            unmatched.push(UnmatchedRow { line, text, reason: "No hanzi in this note".to_string() });
+            continue;
+        };
+        let mut forms = hanzi_forms(fields[headword]);
+        if let Some(second) = fields.get(headword + 1) {
+            forms.extend(hanzi_forms(second).into_iter().filter(|f| !forms.contains(f)).collect::<Vec<_>>());
+        }
+
+        let Some(schedule) = schedules.get(&note_id) else {
+            unmatched.push(UnmatchedRow { line, text, reason: "Not studied yet in Anki".to_string() });
+            continue;
+        };
+
+        entries.push(ImportEntry {
+            line,
+            forms,
+            interval_days: Some(schedule.interval_days),
+            ease_factor: schedule.ease_factor,
+            correct_count: Some(schedule.correct_count),
+            due: schedule.due,
+            text,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Most advanced studied card per note
+fn read_schedules(collection: &Connection, crt: i64) -> rusqlite::Result<HashMap<i64, NoteSchedule>> {
+    let mut stmt = collection.prepare(
+        "SELECT nid, type, queue, due, ivl, factor, reps, lapses FROM cards WHERE type != 0"
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, i64>(4)?,
+            row.get::<_, i64>(5)?,
+            row.get::<_, i64>(6)?,
+            row.get::<_, i64>(7)?,
+        ))
+    })?;
+
+    let mut schedules: HashMap<i64, NoteSchedule> = HashMap::new();
+    for row in rows {
+        let (note_id, card_type, queue, due, ivl, factor, reps, lapses) = row?;
+
+        // Review cards: ivl in days, due in days since collection creation.
+        // Learning/relearning cards: due is a unix timestamp; treat as the first ladder step.
+        let (interval_days, due) = if card_type == 2 && ivl > 0 {
+            (ivl as f64, Utc.timestamp_opt(crt + due * 86400, 0).single())
+        } else {
+            (0.0417, Utc.timestamp_opt(due, 0).single().filter(|_| queue == 1))
+        };
+
+        let schedule = NoteSchedule {
+            interval_days,
+            ease_factor: (factor > 0).then(|| factor as f64 / 1000.0),
+            correct_count: (reps - lapses).max(0) as u32,
+            due,
+        };
+
+        match schedules.get(&note_id) {
+            Some(existing) if existing.interval_days >= schedule.interval_days => {}
+            _ => {
+                schedules.insert(note_id, schedule);
+            }
+        }
+    }
+
+    Ok(schedules)
+}
@@ -0,0 +1,408 @@
+// Progress import from other flashcard apps
+// Each reader turns a file into ImportEntry rows; this module matches them to `characters`
+// and seeds the active track's user_progress so known characters skip the new-user unlock flow.
+
+mod anki;
+mod plain_csv;
+mod pleco;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::database::{end_undo_history, get_card_directions, insert_progress_rows, round_down_to_half_hour, ACTIVE_TRACK};
+use data_processing::search::is_hanzi;
+use data_processing::srs::{interval_after_correct_answers, seed_memory_state};
+
+/// Interval for imported cards that carry no review information: soon enough to confirm
+/// the character is really known, late enough not to flood the first review session
+pub const DEFAULT_IMPORT_INTERVAL_DAYS: f64 = 3.0;
+
+/// Longest interval an import can seed (about a hundred years)
+pub const MAX_IMPORT_INTERVAL_DAYS: f64 = 36500.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Anki,
+    Pleco,
+    Csv,
+}
+
+impl ImportFormat {
+    /// Guess the format from the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "apkg" | "colpkg" => Some(ImportFormat::Anki),
+            "xml" | "txt" => Some(ImportFormat::Pleco),
+            "csv" | "tsv" => Some(ImportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// One card read from an import file, before matching
+#[derive(Debug, Default)]
+pub struct ImportEntry {
+    /// Line (text formats) or note/card number (Anki, Pleco XML) for the report
+    pub line: usize,
+    /// Hanzi forms to try, in order (e.g. simplified then traditional)
+    pub forms: Vec<String>,
+    pub interval_days: Option<f64>,
+    pub ease_factor: Option<f64>,
+    /// Correct answers so far, used to place the card on the ladder when there is no interval
+    pub correct_count: Option<u32>,
+    pub due: Option<DateTime<Utc>>,
+    /// Original text, shown in the report when the entry can't be matched
+    pub text: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct UnmatchedRow {
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ImportReport {
+    pub format: ImportFormat,
+    pub total_rows: usize,
+    /// Characters that were new to the user
+    pub imported: usize,
+    /// Characters already being studied whose interval was raised
+    pub updated: usize,
+    /// Characters already further along than the import
+    pub unchanged: usize,
+    pub unmatched: Vec<UnmatchedRow>,
+}
+
+/// Read an export file and seed user_progress from it
+pub fn import_progress(
+    conn: &Connection,
+    path: &Path,
+    format: Option<ImportFormat>,
+) -> Result<ImportReport, Box<dyn std::error::Error>> {
+    let format = format
+        .or_else(|| ImportFormat::from_path(path))
+        .ok_or_else(|| format!("Can't tell the import format of {:?}", path))?;
+
+    println!("[IMPORT] Importing {:?} file {:?}", format, path);
+
+    let mut unmatched = Vec::new();
+    let entries = match format {
+        ImportFormat::Anki => anki::read_entries(path, &mut unmatched)?,
+        ImportFormat::Pleco => pleco::read_entries(&std::fs::read_to_string(path)?)?,
+        ImportFormat::Csv => plain_csv::read_entries(&std::fs::read_to_string(path)?)?,
+    };
+
+    let report = seed_progress(conn, format, entries, unmatched)?;
+    println!("[IMPORT] {} imported, {} updated, {} unchanged, {} unmatched",
+             report.imported, report.updated, report.unchanged, report.unmatched.len());
+    Ok(report)
+}
+
+/// Match entries to characters and write their schedule, all in one transaction
+fn seed_progress(
+    conn: &Connection,
+    format: ImportFormat,
+    entries: Vec<ImportEntry>,
+    mut unmatched: Vec<UnmatchedRow>,
+) -> rusqlite::Result<ImportReport> {
+    let total_rows = entries.len() + unmatched.len();
+
+    // Match first; the same character may appear several times (e.g. one Anki card per direction)
+    let mut matched: HashMap<i32, ImportEntry> = HashMap::new();
+    let mut order: Vec<i32> = Vec::new();
+    for entry in entries {
+        if entry.interval_days.is_some_and(|days| !days.is_finite()) {
+            unmatched.push(UnmatchedRow {
+                line: entry.line,
+                text: entry.text,
+                reason: "Interval is not a number of days".to_string(),
+            });
+            continue;
+        }
+        match find_character(conn, &entry.forms)? {
+            Some(character_id) => {
+                let interval = seed_interval(&entry);
+                match matched.get(&character_id) {
+                    Some(existing) if seed_interval(existing) >= interval => {}
+                    Some(_) => {
+                        matched.insert(character_id, entry);
+                    }
+                    None => {
+                        order.push(character_id);
+                        matched.insert(character_id, entry);
+                    }
+                }
+            }
+            None => unmatched.push(UnmatchedRow {
+                line: entry.line,
+                text: entry.text,
+                reason: "No matching character in the dictionary".to_string(),
+            }),
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let directions = get_card_directions(&tx)?;
+    let now = Utc::now();
+    let (mut imported, mut updated, mut unchanged) = (0, 0, 0);
+
+    for (position, character_id) in order.iter().enumerate() {
+        let entry = &matched[character_id];
+        let interval = seed_interval(entry);
+        let ease = entry.ease_factor.filter(|ease| ease.is_finite()).unwrap_or(2.25).clamp(1.3, 2.5);
+
+        // Without a real due date, spread cards over the second half of their interval
+        // so a large import doesn't all come due on the same day
+        let due = entry.due.map(|due| due.max(now)).unwrap_or_else(|| {
+            let fraction = 0.5 + 0.5 * position as f64 / order.len() as f64;
+            now + Duration::minutes((interval * fraction * 24.0 * 60.0) as i64)
+        });
+        // The imported interval ends at the due date, so that's when the card was last seen
+        let last_reviewed = (due - Duration::minutes((interval * 24.0 * 60.0) as i64)).min(now);
+        let due = round_down_to_half_hour(due).format("%Y-%m-%d %H:%M:%S").to_string();
+        let last_reviewed = last_reviewed.format("%Y-%m-%d %H:%M:%S").to_string();
+        let (stability, difficulty) = seed_memory_state(interval as f32, ease as f32);
+
+        let was_introduced: bool = tx.query_row(
+            &format!(
+                "SELECT COALESCE(MAX(introduced), 0) FROM user_progress WHERE character_id = ?1 AND track = {}",
+                ACTIVE_TRACK
+            ),
+            [character_id],
+            |row| row.get(0)
+        )?;
+
+        // Imported characters are already known, so they don't use up today's new cards
+        insert_progress_rows(&tx, *character_id, false)?;
+
+        let mut changed = 0;
+        for direction in &directions {
+            changed += tx.execute(
+                &format!(
+                    "UPDATE user_progress
+                     SET introduced = 1,
+                         previous_interval_days = current_interval_days,
+                         current_interval_days = ?1,
+                         ease_factor = ?2,
+                         next_review_date = ?3,
+                         has_reached_week = has_reached_week OR ?4,
+                         stability = ?7,
+                         difficulty = ?8,
+                         last_reviewed = ?9,
+                         updated_at = datetime('now')
+                     WHERE character_id = ?5 AND direction = ?6 AND track = {}
+                       AND is_mastered = 0
+                       AND (introduced = 0 OR current_interval_days < ?1)",
+                    ACTIVE_TRACK
+                ),
+                rusqlite::params![
+                    interval, ease, due, interval >= 7.0, character_id, direction,
+                    stability, difficulty, last_reviewed,
+                ]
+            )?;
+        }
+
+        if changed > 0 {
+            end_undo_history(&tx, *character_id)?;
+        }
+        if !was_introduced {
+            imported += 1;
+        } else if changed > 0 {
+            updated += 1;
+        } else {
+            unchanged += 1;
+        }
+    }
+
+    tx.commit()?;
+
+    unmatched.sort_by_key(|row| row.line);
+    Ok(ImportReport { format, total_rows, imported, updated, unchanged, unmatched })
+}
+
+/// Interval to seed: the source's own interval, else the ladder position its correct count implies
+fn seed_interval(entry: &ImportEntry) -> f64 {
+    entry
+        .interval_days
+        .or_else(|| entry.correct_count.map(|count| interval_after_correct_answers(count) as f64))
+        .unwrap_or(DEFAULT_IMPORT_INTERVAL_DAYS)
+        .clamp(0.0417, MAX_IMPORT_INTERVAL_DAYS)
+}
+
+/// Find a character or word by simplified or traditional form, preferring simplified matches
+fn find_character(conn: &Connection, forms: &[String]) -> rusqlite::Result<Option<i32>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id FROM characters
+         WHERE simplified = ?1 OR traditional = ?1
+         ORDER BY simplified = ?1 DESC, frequency_rank ASC
+         LIMIT 1"
+    )?;
+
+    for form in forms {
+        if let Some(id) = stmt.query_row([form], |row| row.get(0)).optional()? {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
+/// Hanzi forms in a headword such as "汉字", "汉字[漢字]" or "<b>汉字</b>"
+pub(crate) fn hanzi_forms(text: &str) -> Vec<String> {
+    let text = strip_html(text);
+    text.split(['[', ']', '|', '/', ',', ' '])
+        .map(|part| part.chars().filter(|c| is_hanzi(*c)).collect::<String>())
+        .filter(|part| !part.is_empty())
+        .fold(Vec::new(), |mut forms, form| {
+            if !forms.contains(&form) {
+                forms.push(form);
+            }
+            forms
+        })
+}
+
+fn strip_html(text: &str) -> String {
+    let mut result = String::new();
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result.replace("&nbsp;", " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_db() -> Connection {
+        crate::database::test_user_db(
+            "INSERT INTO characters (character, simplified, traditional, mandarin_pinyin, definition, frequency_rank, is_word)
+             VALUES ('的', '的', '的', 'de5', 'possessive particle', 1, 0),
+                    ('汉', '汉', '漢', 'han4', 'Chinese', 400, 0),
+                    ('汉字', '汉字', '漢字', 'han4 zi4', 'Chinese character', 900, 1),
+                    ('好', '好', '好', 'hao3', 'good', 80, 0);"
+        )
+    }
+
+    fn progress(conn: &Connection, character: &str) -> (bool, f64) {
+        conn.query_row(
+            "SELECT p.introduced, p.current_interval_days FROM user_progress p
+             JOIN characters c ON c.id = p.character_id WHERE c.simplified = ?1",
+            [character],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_hanzi_forms() {
+        assert_eq!(hanzi_forms("汉字[漢字]"), vec!["汉字", "漢字"]);
+        assert_eq!(hanzi_forms("<div>好</div>&nbsp;"), vec!["好"]);
+        assert_eq!(hanzi_forms("hao3"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_csv_import_matches_simplified_and_traditional() {
+        let conn = user_db();
+        let entries = plain_csv::read_entries("hanzi,interval\n漢字,10\n的\n麤,5\n").unwrap();
+        let report = seed_progress(&conn, ImportFormat::Csv, entries, Vec::new()).unwrap();
+
+        assert_eq!(report.total_rows, 3);
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.unmatched.len(), 1);
+        assert_eq!(report.unmatched[0].line, 4);
+        assert_eq!(report.unmatched[0].text, "麤,5");
+
+        assert_eq!(progress(&conn, "汉字"), (true, 10.0));
+        assert_eq!(progress(&conn, "的"), (true, DEFAULT_IMPORT_INTERVAL_DAYS));
+
+        // Spread: nothing imported is due right away
+        let due_now: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM user_progress WHERE next_review_date <= datetime('now')",
+            [],
+            |row| row.get(0)
+        ).unwrap();
+        assert_eq!(due_now, 0);
+    }
+
+    #[test]
+    fn test_csv_import_rejects_intervals_that_are_not_numbers() {
+        let conn = user_db();
+        let entries = plain_csv::read_entries("的,inf\n汉,NaN\n好,1e300\n").unwrap();
+        let report = seed_progress(&conn, ImportFormat::Csv, entries, Vec::new()).unwrap();
+
+        assert_eq!(report.imported, 1);
+        let rejected: Vec<usize> = report.unmatched.iter().map(|row| row.line).collect();
+        assert_eq!(rejected, vec![1, 2]);
+        assert_eq!(progress(&conn, "好"), (true, MAX_IMPORT_INTERVAL_DAYS));
+    }
+
+    #[test]
+    fn test_import_never_shortens_existing_progress() {
+        let conn = user_db();
+        let entries = plain_csv::read_entries("好,30\n").unwrap();
+        seed_progress(&conn, ImportFormat::Csv, entries, Vec::new()).unwrap();
+
+        let entries = plain_csv::read_entries("好,2\n汉,2\n").unwrap();
+        let report = seed_progress(&conn, ImportFormat::Csv, entries, Vec::new()).unwrap();
+        assert_eq!((report.imported, report.updated, report.unchanged), (1, 0, 1));
+        assert_eq!(progress(&conn, "好"), (true, 30.0));
+
+        let entries = plain_csv::read_entries("好,60\n").unwrap();
+        let report = seed_progress(&conn, ImportFormat::Csv, entries, Vec::new()).unwrap();
+        assert_eq!(report.updated, 1);
+        assert_eq!(progress(&conn, "好"), (true, 60.0));
+    }
+
+    #[test]
+    fn test_fsrs_keeps_imported_memory_state() {
+        let conn = user_db();
+        crate::database::set_srs_settings(&conn, &crate::database::SrsSettings {
+            scheduler: "fsrs".to_string(),
+            desired_retention: 0.9,
+        }).unwrap();
+
+        let entries = plain_csv::read_entries("好,30\n").unwrap();
+        seed_progress(&conn, ImportFormat::Csv, entries, Vec::new()).unwrap();
+        let card = crate::database::get_srs_card_state(&conn, 4, crate::database::DEFAULT_DIRECTION).unwrap();
+        assert_eq!(card.stability, Some(30.0));
+        assert!(card.elapsed_days.unwrap() > 0.0);
+
+        // The first review builds on the imported interval instead of starting over
+        crate::database::record_srs_answer(&conn, 4, data_processing::srs::Grade::Good, None, None).unwrap();
+        assert!(progress(&conn, "好").1 > 30.0);
+    }
+
+    #[test]
+    fn test_anki_import_round_trips_export() {
+        let source = user_db();
+        for id in 1..=2 {
+            crate::database::insert_progress_rows(&source, id, true).unwrap();
+        }
+        for _ in 0..5 {
+            crate::database::record_srs_answer(&source, 1, true, None, None).unwrap();
+        }
+
+        let apkg = std::env::temp_dir().join("chinese-flashcards-import-test.apkg");
+        crate::export::export_apkg(&source, &apkg).unwrap();
+
+        let conn = user_db();
+        let report = import_progress(&conn, &apkg, None).unwrap();
+        std::fs::remove_file(&apkg).ok();
+
+        assert_eq!(report.format, ImportFormat::Anki);
+        assert_eq!(report.imported, 2);
+        assert!(report.unmatched.is_empty());
+        assert_eq!(progress(&conn, "的"), (true, 16.0)); // Anki stores whole days
+        assert!(progress(&conn, "汉").1 < 1.0);
+    }
+}
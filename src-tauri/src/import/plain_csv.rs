@@ -0,0 +1,63 @@
+// Plain CSV reader: hanzi in the first column, optional interval in days in the second.
+// Comma, tab or semicolon separated; a header row and "#" comment lines are skipped.
+
+use super::{hanzi_forms, ImportEntry};
+
+pub fn read_entries(contents: &str) -> Result<Vec<ImportEntry>, Box<dyn std::error::Error>> {
+    let contents = contents.trim_start_matches('\u{feff}');
+    let first_line = contents.lines().next().unwrap_or("");
+    let delimiter = [b'\t', b';', b',']
+        .into_iter()
+        .find(|d| first_line.contains(*d as char))
+        .unwrap_or(b',');
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        let hanzi = record.get(0).unwrap_or("").trim();
+        if hanzi.starts_with('#') {
+            continue;
+        }
+        let interval = record.get(1).map(str::trim).filter(|v| !v.is_empty());
+
+        // Header row: no hanzi in the first column and a non-numeric second column
+        let forms = hanzi_forms(hanzi);
+        if entries.is_empty() && forms.is_empty() && !interval.is_some_and(|v| v.parse::<f64>().is_ok()) {
+            continue;
+        }
+
+        entries.push(ImportEntry {
+            line,
+            forms,
+            interval_days: interval.and_then(|v| v.parse().ok()),
+            text: record.iter().collect::<Vec<_>>().join(&(delimiter as char).to_string()),
+            ..Default::default()
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_comments_and_delimiters() {
+        let entries = read_entries("hanzi,interval\n# known already\n好,14\n的\n").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].line, entries[0].interval_days), (3, Some(14.0)));
+        assert_eq!(entries[1].interval_days, None);
+
+        let entries = read_entries("汉字\t2.5\n").unwrap();
+        assert_eq!(entries[0].forms, vec!["汉字"]);
+        assert_eq!(entries[0].interval_days, Some(2.5));
+    }
+}
@@ -0,0 +1,122 @@
+// Pleco flashcard export reader
+// XML exports carry headwords plus score info (correct/incorrect counts);
+// text exports are "headword<TAB>pinyin<TAB>definition" lines with "//" category headers.
+
+use super::{hanzi_forms, ImportEntry};
+
+pub fn read_entries(contents: &str) -> Result<Vec<ImportEntry>, Box<dyn std::error::Error>> {
+    let contents = contents.trim_start_matches('\u{feff}');
+    if contents.trim_start().starts_with('<') {
+        read_xml(contents)
+    } else {
+        Ok(read_text(contents))
+    }
+}
+
+fn read_text(contents: &str) -> Vec<ImportEntry> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with("//"))
+        .map(|(index, line)| {
+            let headword = line.split('\t').next().unwrap_or("");
+            ImportEntry {
+                line: index + 1,
+                forms: hanzi_forms(headword),
+                text: line.to_string(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn read_xml(contents: &str) -> Result<Vec<ImportEntry>, Box<dyn std::error::Error>> {
+    let document = roxmltree::Document::parse(contents)?;
+
+    let entries = document
+        .descendants()
+        .filter(|node| node.has_tag_name("card"))
+        .enumerate()
+        .map(|(index, card)| {
+            // Simplified headword first, then traditional
+            let mut headwords: Vec<(Option<&str>, &str)> = card
+                .descendants()
+                .filter(|node| node.has_tag_name("headword"))
+                .map(|node| (node.attribute("charset"), node.text().unwrap_or("")))
+                .collect();
+            headwords.sort_by_key(|(charset, _)| *charset != Some("sc"));
+
+            let mut forms: Vec<String> = Vec::new();
+            for (_, headword) in &headwords {
+                for form in hanzi_forms(headword) {
+                    if !forms.contains(&form) {
+                        forms.push(form);
+                    }
+                }
+            }
+
+            // Pleco keeps one scoreinfo per score file; use the best-known one
+            let correct_count = card
+                .descendants()
+                .filter(|node| node.has_tag_name("scoreinfo"))
+                .filter_map(|node| {
+                    let correct: u32 = node.attribute("correct")?.parse().ok()?;
+                    let incorrect: u32 = node.attribute("incorrect").and_then(|v| v.parse().ok()).unwrap_or(0);
+                    Some(correct.saturating_sub(incorrect))
+                })
+                .max();
+
+            ImportEntry {
+                line: index + 1,
+                forms,
+                correct_count,
+                text: headwords.iter().map(|(_, h)| *h).collect::<Vec<_>>().join(" / "),
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_export() {
+        let entries = read_entries(
+            "// Lesson 1\n汉字[漢字]\than4zi4\tChinese character\n\n好\thao3\tgood\n"
+        ).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].line, 2);
+        assert_eq!(entries[0].forms, vec!["汉字", "漢字"]);
+        assert_eq!(entries[1].forms, vec!["好"]);
+        assert!(entries[1].correct_count.is_none());
+    }
+
+    #[test]
+    fn test_xml_export() {
+        let entries = read_entries(r#"<?xml version="1.0" encoding="UTF-8"?>
+<plecoflash formatversion="2">
+  <cards>
+    <card language="chinese">
+      <entry>
+        <headword charset="tc">漢字</headword>
+        <headword charset="sc">汉字</headword>
+        <pron type="hypy" tones="numbers">han4zi4</pron>
+      </entry>
+      <scoreinfo scorefile="Default" score="250" correct="6" incorrect="1" reviewed="7"/>
+    </card>
+    <card language="chinese">
+      <entry><headword charset="sc">好</headword></entry>
+    </card>
+  </cards>
+</plecoflash>"#).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].forms, vec!["汉字", "漢字"]);
+        assert_eq!(entries[0].correct_count, Some(5));
+        assert_eq!(entries[1].correct_count, None);
+    }
+}
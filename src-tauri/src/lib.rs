@@ -1,6 +1,9 @@
 mod database;
 mod commands;
-mod srs;
+mod export;
+mod import;
+mod grading;
+mod analysis;
 
 use database::initialize_database;
 
@@ -14,9 +17,40 @@ pub fn run() {
     .invoke_handler(tauri::generate_handler![
       commands::test_database_connection,
       commands::get_character,
+      commands::get_character_details,
       commands::get_top_characters,
       commands::get_due_cards_for_review,
+      commands::get_daily_limits,
+      commands::set_daily_limits,
+      commands::get_study_day_settings,
+      commands::set_study_day_settings,
       commands::submit_srs_answer,
+      commands::undo_last_answer,
+      commands::suspend_character,
+      commands::suspend_characters,
+      commands::unsuspend_character,
+      commands::unsuspend_characters,
+      commands::bury_character,
+      commands::bury_characters,
+      commands::reset_character,
+      commands::reset_characters,
+      commands::get_leeches,
+      commands::get_leech_settings,
+      commands::set_leech_settings,
+      commands::get_mastery_policy,
+      commands::set_mastery_policy,
+      commands::get_srs_settings,
+      commands::set_srs_settings,
+      commands::get_pacing_policy,
+      commands::set_pacing_policy,
+      commands::simulate_workload,
+      commands::get_card_directions,
+      commands::set_card_directions,
+      commands::get_study_track,
+      commands::set_study_track,
+      commands::set_track_introduction_order,
+      commands::get_character_review_log,
+      commands::get_review_log_by_date,
       commands::unlock_new_character,
       commands::introduce_character,
       commands::introduce_character_immediately_reviewable,
@@ -30,12 +64,20 @@ pub fn run() {
       commands::get_unlock_status,
       commands::mark_all_ready_characters_introduced,
       commands::get_self_study_cards,
+      commands::grade_answer,
+      commands::analyze_text,
+      commands::pull_forward_characters,
+      commands::get_pull_forward_queue,
+      commands::clear_pull_forward_queue,
       commands::record_practice,
       commands::get_dashboard_stats,
       commands::get_recent_sessions,
       commands::start_session,
       commands::end_session,
       commands::browse_characters,
+      commands::search_dictionary,
+      commands::export_anki_deck,
+      commands::import_progress,
       commands::get_total_characters_count,
       commands::browse_introduction_order,
       commands::get_total_items_count,
@@ -120,7 +120,7 @@ function App() {
   const handleInitialSrsComplete = async () => {
     try {
       // The SpacedRepetition component now handles marking characters as completed/incomplete
-      // We just need to start the 2-day timer for next unlock (if all ready characters are introduced)
+      // We just need to start the unlock cooldown for next unlock (if all ready characters are introduced)
       console.log('Initial SRS session complete. Updating unlock timer...');
 
       try {
@@ -7,6 +7,7 @@ interface DashboardStats {
   characters_in_srs: number;
   cards_due_today: number;
   mastered_characters: number;
+  suspended_characters: number;
   study_streak_days: number;
 }
 
@@ -24,6 +25,8 @@ interface StudySession {
 interface ReviewCalendarEntry {
   review_time: string; // Full datetime in half-hour blocks (YYYY-MM-DD HH:MM:SS)
   cards_due: number;
+  study_day: string; // Local study day (YYYY-MM-DD), using the configured timezone and day start hour
+  is_today: boolean;
 }
 
 
@@ -39,6 +42,7 @@ function Dashboard({ onStartLearnNew, onStartSrsSession, onStartSelfStudy, onBro
   const [unlockStatus, setUnlockStatus] = useState<{
     ready_to_learn_count: number;
     hours_until_next_unlock: number | null;
+    held_for_workload: boolean;
   } | null>(null);
   const [recentSessions, setRecentSessions] = useState<StudySession[]>([]);
   const [calendar, setCalendar] = useState<ReviewCalendarEntry[]>([]);
@@ -59,6 +63,7 @@ function Dashboard({ onStartLearnNew, onStartSrsSession, onStartSelfStudy, onBro
         unlocked_count: number;
         ready_to_learn_count: number;
         hours_until_next_unlock: number | null;
+        held_for_workload: boolean;
       }>('check_and_unlock_characters');
 
       // Get dashboard statistics
@@ -150,6 +155,18 @@ function Dashboard({ onStartLearnNew, onStartSrsSession, onStartSelfStudy, onBro
               )}
             </div>
           )}
+          {unlockStatus?.held_for_workload && unlockStatus.ready_to_learn_count === 0 && (
+            <div style={{
+              marginTop: '12px',
+              padding: '8px 12px',
+              background: 'rgba(102, 126, 234, 0.1)',
+              borderRadius: '8px',
+              fontSize: '14px',
+              color: '#667eea'
+            }}>
+              📚 Catch up on due reviews to unlock more
+            </div>
+          )}
           <button
             className="action-button"
             disabled={unlockStatus?.ready_to_learn_count === 0}
@@ -219,6 +236,9 @@ function Dashboard({ onStartLearnNew, onStartSrsSession, onStartSelfStudy, onBro
             <div className="stat-content">
               <div className="stat-value">{stats?.characters_in_srs || 0}</div>
               <div className="stat-name">In SRS Pool</div>
+              {(stats?.suspended_characters || 0) > 0 && (
+                <div className="stat-name">{stats?.suspended_characters} suspended</div>
+              )}
             </div>
           </div>
 
@@ -258,7 +278,7 @@ function Dashboard({ onStartLearnNew, onStartSrsSession, onStartSelfStudy, onBro
               // SQLite format: "YYYY-MM-DD HH:MM:SS" (stored in UTC)
               const reviewTime = new Date(entry.review_time + 'Z'); // Add 'Z' to explicitly mark as UTC
 
-              const isToday = reviewTime.toDateString() === new Date().toDateString();
+              const isToday = entry.is_today;
 
               // Format time in 12-hour format with AM/PM in user's local timezone
               // Now includes minutes (will be :00 or :30 due to backend rounding)
@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { verifyAnswer, convertToneNumbersToMarks, hasCorrectSyllablesButWrongTones } from '../../utils/answerVerification';
+import { convertToneNumbersToMarks } from '../../utils/answerVerification';
 import './SelfStudy.css';
 
 interface DueCard {
@@ -14,6 +14,15 @@ interface DueCard {
 
 type QuestionType = 'definition' | 'pinyin';
 
+interface GradedAnswer {
+  verdict: 'correct' | 'near_miss' | 'incorrect';
+  is_correct: boolean;
+  matched: string | null;
+  explanation: string | null;
+  wrong_tones: number[]; // 1-based syllables where only the tone was wrong
+  accepted: string[];
+}
+
 interface Question {
   id: string;
   character_id: number;
@@ -156,8 +165,13 @@ function SelfStudy({ onComplete }: SelfStudyProps) {
 
   const getCurrentQuestion = () => questions[currentQuestionIndex];
 
-  const checkAnswer = (answer: string, correctAnswer: string, questionType: QuestionType): boolean => {
-    return verifyAnswer(answer, correctAnswer, questionType);
+  // Graded by the backend against every sense and reading of the card
+  const gradeAnswer = (question: Question, answer: string): Promise<GradedAnswer> => {
+    return invoke<GradedAnswer>('grade_answer', {
+      characterId: question.character_id,
+      direction: question.questionType === 'definition' ? 'zh_to_en' : 'zh_to_pinyin',
+      input: answer,
+    });
   };
 
   const handleAnswerChange = (e: React.ChangeEvent<HTMLInputElement>) => {
@@ -178,16 +192,19 @@ function SelfStudy({ onComplete }: SelfStudyProps) {
     const currentQuestion = getCurrentQuestion();
     if (!currentQuestion) return;
 
-    const correctAnswer = currentQuestion.questionType === 'definition'
-      ? currentQuestion.definition
-      : currentQuestion.pinyin;
-
-    const correct = checkAnswer(userAnswer, correctAnswer, currentQuestion.questionType);
+    let graded: GradedAnswer;
+    try {
+      graded = await gradeAnswer(currentQuestion, userAnswer);
+    } catch (error) {
+      console.error('Failed to grade answer:', error);
+      return;
+    }
+    const correct = graded.is_correct;
 
     // For pinyin questions, check if syllables are correct but tones are wrong
     let wrongTones = false;
     if (currentQuestion.questionType === 'pinyin' && !correct && !isRetryAttempt) {
-      wrongTones = hasCorrectSyllablesButWrongTones(userAnswer, correctAnswer);
+      wrongTones = graded.wrong_tones.length > 0;
       console.log('[SELF-STUDY] Wrong tones only:', wrongTones);
     }
 
@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { verifyAnswer, convertToneNumbersToMarks, hasCorrectSyllablesButWrongTones } from '../../utils/answerVerification';
+import { convertToneNumbersToMarks } from '../../utils/answerVerification';
 import './SpacedRepetition.css';
 
 interface DueCard {
@@ -12,8 +12,24 @@ interface DueCard {
   times_reviewed: number;
 }
 
+interface ReviewQueue {
+  cards: DueCard[];
+  held_back: number;
+  reviewed_today: number;
+  review_limit: number;
+}
+
 type QuestionType = 'definition' | 'pinyin';
 
+interface GradedAnswer {
+  verdict: 'correct' | 'near_miss' | 'incorrect';
+  is_correct: boolean;
+  matched: string | null;
+  explanation: string | null;
+  wrong_tones: number[]; // 1-based syllables where only the tone was wrong
+  accepted: string[];
+}
+
 interface Question {
   id: string; // Unique ID for this question
   character_id: number;
@@ -33,6 +49,7 @@ interface CharacterProgress {
   pinyinCorrect: boolean;
   submitted: boolean; // Has this been submitted to backend?
   hadIncorrectAnswer: boolean; // Track if this card ever had an incorrect answer
+  lastWrongAnswer?: string; // Most recent wrong answer, recorded for leech confusions
 }
 
 interface SpacedRepetitionProps {
@@ -139,8 +156,12 @@ function SpacedRepetition({ onComplete, isInitialStudy = false, initialStudyChar
           characterIds: initialStudyCharacterIds
         });
       } else {
-        // Regular review session
-        cards = await invoke<DueCard[]>('get_due_cards_for_review');
+        // Regular review session, capped by the daily review limit
+        const queue = await invoke<ReviewQueue>('get_due_cards_for_review');
+        cards = queue.cards;
+        if (queue.held_back > 0) {
+          console.log('[SRS] Daily limit reached:', queue.held_back, 'due cards held back until tomorrow');
+        }
       }
 
       if (cards.length === 0) {
@@ -218,9 +239,13 @@ function SpacedRepetition({ onComplete, isInitialStudy = false, initialStudyChar
 
   const getCurrentQuestion = () => questions[currentQuestionIndex];
 
-  // Use the new robust answer verification system
-  const checkAnswer = (answer: string, correctAnswer: string, questionType: QuestionType): boolean => {
-    return verifyAnswer(answer, correctAnswer, questionType);
+  // Graded by the backend against every sense and reading of the card
+  const gradeAnswer = (question: Question, answer: string): Promise<GradedAnswer> => {
+    return invoke<GradedAnswer>('grade_answer', {
+      characterId: question.character_id,
+      direction: question.questionType === 'definition' ? 'zh_to_en' : 'zh_to_pinyin',
+      input: answer,
+    });
   };
 
   // Handle input changes with real-time pinyin tone mark conversion
@@ -245,26 +270,20 @@ function SpacedRepetition({ onComplete, isInitialStudy = false, initialStudyChar
     const currentQuestion = getCurrentQuestion();
     if (!currentQuestion) return;
 
-    const correctAnswer = currentQuestion.questionType === 'definition'
-      ? currentQuestion.definition
-      : currentQuestion.pinyin;
-
-    // Debug logging
-    if (currentQuestion.questionType === 'pinyin') {
-      console.log('[VERIFY] User answer:', userAnswer);
-      console.log('[VERIFY] Correct answer:', correctAnswer);
-    }
-
-    const correct = checkAnswer(userAnswer, correctAnswer, currentQuestion.questionType);
-
-    if (currentQuestion.questionType === 'pinyin') {
-      console.log('[VERIFY] Result:', correct);
+    let graded: GradedAnswer;
+    try {
+      graded = await gradeAnswer(currentQuestion, userAnswer);
+    } catch (error) {
+      console.error('Failed to grade answer:', error);
+      return;
     }
+    console.log('[VERIFY]', userAnswer, '→', graded.verdict, graded.explanation ?? '');
+    const correct = graded.is_correct;
 
     // For pinyin questions, check if syllables are correct but tones are wrong
     let wrongTones = false;
     if (currentQuestion.questionType === 'pinyin' && !correct && !isRetryAttempt) {
-      wrongTones = hasCorrectSyllablesButWrongTones(userAnswer, correctAnswer);
+      wrongTones = graded.wrong_tones.length > 0;
       console.log('[VERIFY] Wrong tones only:', wrongTones);
     }
 
@@ -297,6 +316,9 @@ function SpacedRepetition({ onComplete, isInitialStudy = false, initialStudyChar
       } else {
         progress.pinyinCorrect = correct;
       }
+      if (!correct) {
+        progress.lastWrongAnswer = userAnswer.trim();
+      }
       setCharacterProgress(new Map(characterProgress.set(currentQuestion.character_id, progress)));
 
       // Track successful answers for progress bar (only increment if newly correct)
@@ -374,6 +396,7 @@ function SpacedRepetition({ onComplete, isInitialStudy = false, initialStudyChar
           const reachedWeek = await invoke<boolean>('submit_srs_answer', {
             characterId: currentQuestion.character_id,
             correct: true,
+            userAnswer: userAnswer.trim(),
           });
 
           console.log('[SRS] Answer submitted. Reached week:', reachedWeek);
@@ -548,6 +571,7 @@ function SpacedRepetition({ onComplete, isInitialStudy = false, initialStudyChar
             await invoke('submit_srs_answer', {
               characterId,
               correct: false,
+              userAnswer: characterProgress.get(characterId)?.lastWrongAnswer,
             });
             console.log('[SRS] Submitted incorrect character:', characterId);
           } catch (error) {